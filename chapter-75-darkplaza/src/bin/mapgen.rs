@@ -0,0 +1,192 @@
+//! Headless map generator. Runs the level builders without opening a window, and prints
+//! the results as ASCII art or JSON, along with anything `validate_map` objects to.
+//!
+//! Usage: mapgen [--branch NAME] [--depth N] [--width W] [--height H] [--seed S] [--count N] [--json] [--out FILE]
+//!
+//! Map `i` of a batch uses run seed `S + i`, so `--seed S --depth N` reproduces depth N of
+//! a game started with `--seed S`. `--branch` picks a side branch such as `spider_nest`;
+//! the default is the main dungeon. `--width` and `--height` only apply to levels whose
+//! chain in `levels.json` doesn't set a size; asking for a size on one that does is an
+//! error. Builder diagnostics go to stderr, so `--json` output on stdout stays clean.
+
+use chapter_75_darkplaza::{map_builders, raws, rex_assets, rng, Map, TileType, LevelKey};
+use serde_json::json;
+use std::io::Write;
+use std::sync::Arc;
+
+struct Options {
+    branch : String,
+    depth : i32,
+    width : Option<i32>,
+    height : Option<i32>,
+    seed : u64,
+    count : u64,
+    json : bool,
+    out : Option<String>
+}
+
+fn parse_args() -> Options {
+    let mut opts = Options{
+        branch : chapter_75_darkplaza::main_branch(),
+        depth : 1,
+        width : None,
+        height : None,
+        seed : rng::random_seed(),
+        count : 1,
+        json : false,
+        out : None
+    };
+
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--branch" => { opts.branch = arg_value(&args, i); i += 1; }
+            "--depth" => { opts.depth = arg_value(&args, i); i += 1; }
+            "--width" => { opts.width = Some(arg_value(&args, i)); i += 1; }
+            "--height" => { opts.height = Some(arg_value(&args, i)); i += 1; }
+            "--seed" => { opts.seed = arg_value(&args, i); i += 1; }
+            "--count" => { opts.count = arg_value(&args, i); i += 1; }
+            "--out" => { opts.out = Some(arg_value(&args, i)); i += 1; }
+            "--json" => opts.json = true,
+            other => usage(&format!("Unknown argument {}", other))
+        }
+        i += 1;
+    }
+
+    opts
+}
+
+fn arg_value<T : std::str::FromStr>(args : &[String], i : usize) -> T {
+    args.get(i+1)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| usage(&format!("{} needs a valid value", args[i])))
+}
+
+fn usage(problem : &str) -> ! {
+    eprintln!("{}", problem);
+    eprintln!("Usage: mapgen [--branch NAME] [--depth N] [--width W] [--height H] [--seed S] [--count N] [--json] [--out FILE]");
+    std::process::exit(2);
+}
+
+fn tile_char(map : &Map, tt : TileType) -> char {
+    match tt {
+        TileType::Wall => '#',
+        TileType::Stalactite => '^',
+        TileType::Stalagmite => '!',
+        TileType::Floor => '.',
+        TileType::DownStairs => '>',
+        TileType::UpStairs => '<',
+        TileType::BranchStairs => '}',
+        TileType::Road => '=',
+        TileType::Grass => '"',
+        TileType::ShallowWater => '~',
+        TileType::DeepWater => 'w',
+        TileType::WoodFloor => '_',
+        TileType::Bridge => '+',
+        TileType::Gravel => ':',
+        _ => {
+            // Tiles that only exist in the raws show their own glyph, if it's plain ASCII
+            let symbol = map.tile_set.appearance(tt, "").symbol;
+            if symbol.is_ascii_graphic() { symbol } else { '?' }
+        }
+    }
+}
+
+fn tile_rows(map : &Map) -> Vec<String> {
+    (0..map.height).map(|y| {
+        (0..map.width).map(|x| tile_char(map, map.tiles[map.xy_idx(x, y)])).collect()
+    }).collect()
+}
+
+fn stairs(map : &Map, stair : TileType) -> Vec<(i32, i32)> {
+    map.tiles.iter()
+        .enumerate()
+        .filter(|(_, tt)| **tt == stair)
+        .map(|(idx, _)| (idx as i32 % map.width, idx as i32 / map.width))
+        .collect()
+}
+
+fn main() {
+    let opts = parse_args();
+
+    let raws = Arc::new(raws::load_raws());
+    rex_assets::RexAssets::new();
+
+    let level = LevelKey::new(&opts.branch, opts.depth);
+    if let Some(chain) = raws::get_level_chain(&raws, &level) {
+        if (opts.width.is_some() && chain.width.is_some()) || (opts.height.is_some() && chain.height.is_some()) {
+            usage(&format!("{} depth {} sets its own size in levels.json, so --width and --height can't change it", opts.branch, opts.depth));
+        }
+    }
+    let width = opts.width.unwrap_or(map_builders::DEFAULT_MAP_WIDTH);
+    let height = opts.height.unwrap_or(map_builders::DEFAULT_MAP_HEIGHT);
+
+    let mut out : Box<dyn Write> = match &opts.out {
+        Some(path) => Box::new(std::fs::File::create(path).expect("Unable to create output file")),
+        None => Box::new(std::io::stdout())
+    };
+
+    let mut levels = Vec::new();
+    for n in 0..opts.count {
+        let run_seed = opts.seed.wrapping_add(n);
+        let builder = map_builders::build_level(&raws, run_seed, &level, width, height);
+        let data = &builder.build_data;
+        let map = &data.map;
+        let start = data.starting_position.as_ref().map(|p| (p.x, p.y));
+        let spawns : Vec<(i32, i32, String)> = data.spawn_list.iter()
+            .filter(|(_, name)| name != "None")
+            .map(|(idx, name)| (*idx as i32 % map.width, *idx as i32 / map.width, name.clone()))
+            .collect();
+        let problems : Vec<String> = match map_builders::validate_map(data) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect()
+        };
+
+        if opts.json {
+            levels.push(json!({
+                "seed" : run_seed,
+                "branch" : map.branch,
+                "depth" : map.depth,
+                "name" : map.name,
+                "width" : map.width,
+                "height" : map.height,
+                "tiles" : tile_rows(map),
+                "start" : start.map(|(x, y)| json!({ "x" : x, "y" : y })),
+                "down_stairs" : stairs(map, TileType::DownStairs).iter().map(|(x, y)| json!({ "x" : x, "y" : y })).collect::<Vec<_>>(),
+                "up_stairs" : stairs(map, TileType::UpStairs).iter().map(|(x, y)| json!({ "x" : x, "y" : y })).collect::<Vec<_>>(),
+                "branch_stairs" : stairs(map, TileType::BranchStairs).iter().map(|(x, y)| json!({ "x" : x, "y" : y, "branch" : map.branch_entrances.get(&map.xy_idx(*x, *y)) })).collect::<Vec<_>>(),
+                "spawns" : spawns.iter().map(|(x, y, name)| json!({ "x" : x, "y" : y, "name" : name })).collect::<Vec<_>>(),
+                "problems" : problems
+            }));
+        } else {
+            let mut rows : Vec<Vec<char>> = tile_rows(map).iter().map(|r| r.chars().collect()).collect();
+            for (x, y, _) in spawns.iter() {
+                rows[*y as usize][*x as usize] = '*';
+            }
+            if let Some((x, y)) = start {
+                rows[y as usize][x as usize] = '@';
+            }
+
+            writeln!(out, "== {} ({} depth {}, seed {}) ==", map.name, map.branch, map.depth, run_seed).unwrap();
+            for row in rows.iter() {
+                writeln!(out, "{}", row.iter().collect::<String>()).unwrap();
+            }
+            writeln!(out, "Start: {:?}", start).unwrap();
+            writeln!(out, "Down stairs: {:?}", stairs(map, TileType::DownStairs)).unwrap();
+            writeln!(out, "Up stairs: {:?}", stairs(map, TileType::UpStairs)).unwrap();
+            writeln!(out, "Branch stairs: {:?}", stairs(map, TileType::BranchStairs)).unwrap();
+            for (x, y, name) in spawns.iter() {
+                writeln!(out, "Spawn: ({}, {}) {}", x, y, name).unwrap();
+            }
+            for problem in problems.iter() {
+                writeln!(out, "Problem: {}", problem).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+    }
+
+    if opts.json {
+        writeln!(out, "{}", serde_json::to_string_pretty(&levels).unwrap()).unwrap();
+    }
+}
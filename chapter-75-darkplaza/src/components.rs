@@ -22,7 +22,7 @@ pub struct TileSize {
 pub struct OtherLevelPosition {
     pub x: i32,
     pub y: i32,
-    pub depth: i32
+    pub level: crate::map::LevelKey
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -86,7 +86,53 @@ pub struct Viewshed {
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct LightSource {
     pub color : RGB,
-    pub range: i32
+    pub range: i32,
+    #[serde(default)]
+    pub falloff : LightFalloff,
+    /// How much the light's brightness wavers from turn to turn, from 0 (steady) to 1.
+    #[serde(default)]
+    pub flicker : f32,
+    /// Whether it's giving off light; the player can cover theirs to stay out of sight.
+    #[serde(default = "light_is_lit")]
+    pub lit : bool
+}
+
+fn light_is_lit() -> bool { true }
+
+/// How a light dims towards the edge of its range.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum LightFalloff { Linear, Quadratic, Flat }
+
+impl Default for LightFalloff {
+    fn default() -> Self { LightFalloff::Linear }
+}
+
+impl LightFalloff {
+    /// How bright a light of `range` is at `distance` from its source, from 0 to 1.
+    pub fn intensity(&self, distance : f32, range : f32) -> f32 {
+        let fraction = f32::max(0.0, (range - distance) / range);
+        match self {
+            LightFalloff::Linear => fraction,
+            LightFalloff::Quadratic => fraction * fraction,
+            LightFalloff::Flat => if distance <= range { 1.0 } else { 0.0 }
+        }
+    }
+}
+
+/// The tiles a light reaches and how brightly, worked out from where it was and what
+/// blocked sight at the time. Not saved; it's rebuilt when needed.
+#[derive(Component, Clone)]
+pub struct LightCache {
+    pub x : i32,
+    pub y : i32,
+    pub view_revision : u32,
+    pub tiles : Vec<(usize, f32)>
+}
+
+/// Can make out things standing in the dark, up to `range` tiles away.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Darkvision {
+    pub range : i32
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -116,7 +162,7 @@ pub struct ApplyMove {
 pub struct ApplyTeleport {
     pub dest_x : i32,
     pub dest_y : i32,
-    pub dest_depth : i32
+    pub dest_level : crate::map::LevelKey
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -215,6 +261,13 @@ pub struct Chasing {
     pub target : Entity
 }
 
+/// Heard something at `idx` and is going to take a look, for up to `turns` more turns.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToInvestigate {
+    pub idx : i32,
+    pub turns : i32
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct LootTable {
     pub table : String
@@ -289,6 +342,39 @@ pub struct DamageOverTime {
     pub damage : i32
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ignites {}
+
+/// Catches fire, and is destroyed when the fire under it burns out.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Flammable {}
+
+/// Lets off a cloud of gas where it's used.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ReleasesGas {
+    pub gas : crate::map::GasType,
+    pub amount : f32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MakesNoise {
+    pub volume : i32
+}
+
+/// Can dig through rock: a tool, a spell or a burrowing creature. `turns` is how long it
+/// takes to get through one tile.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Digger {
+    pub turns : i32
+}
+
+/// The entity spends its turns digging at `tile_idx` until it's through, or interrupted.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Digging {
+    pub tile_idx : i32,
+    pub turns : i32
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Duration {
     pub turns : i32
@@ -455,7 +541,7 @@ pub struct TownPortal {}
 pub struct TeleportTo {
     pub x: i32,
     pub y: i32,
-    pub depth: i32,
+    pub level: crate::map::LevelKey,
     pub player_only : bool
 }
 
@@ -490,5 +576,7 @@ pub struct SerializationHelper {
 pub struct DMSerializationHelper {
     pub map : super::map::MasterDungeonMap,
     pub log : Vec<Vec<crate::gamelog::LogFragment>>,
-    pub events : HashMap<String, i32>
+    pub events : HashMap<String, i32>,
+    #[serde(default)]
+    pub clock : crate::GameClock
 }
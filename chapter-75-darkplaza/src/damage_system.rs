@@ -1,6 +1,8 @@
 use specs::prelude::*;
 use super::{Pools, Player, Name, RunState, Position,
     InBackpack, Equipped, LootTable};
+use crate::gamelog::GameLog;
+use rltk::RandomNumberGenerator;
 
 pub fn delete_the_dead(ecs : &mut World) {
     let mut dead : Vec<Entity> = Vec::new();
@@ -21,7 +23,7 @@ pub fn delete_the_dead(ecs : &mut World) {
                                 .color(rltk::RED)
                                 .append(&victim_name.name)
                                 .append("is dead!")
-                                .log();
+                                .log(&mut ecs.write_resource::<GameLog>());
                         }
                         dead.push(entity)
                     }
@@ -35,6 +37,7 @@ pub fn delete_the_dead(ecs : &mut World) {
     }
 
     // Drop everything held by dead people
+    let raws = crate::raws::get_raws(ecs);
     let mut to_spawn : Vec<(String, Position)> = Vec::new();
     { // To avoid keeping hold of borrowed entries, use a scope
         let mut to_drop : Vec<(Entity, Position)> = Vec::new();
@@ -64,7 +67,8 @@ pub fn delete_the_dead(ecs : &mut World) {
 
             if let Some(table) = loot_tables.get(*victim) {
                 let drop_finder = crate::raws::get_item_drop(
-                    &crate::raws::RAWS.lock().unwrap(),
+                    &raws,
+                    &mut ecs.write_resource::<RandomNumberGenerator>(),
                     &table.table
                 );
                 if let Some(tag) = drop_finder {
@@ -85,7 +89,7 @@ pub fn delete_the_dead(ecs : &mut World) {
     {
         for drop in to_spawn.iter() {
             crate::raws::spawn_named_item(
-                &crate::raws::RAWS.lock().unwrap(),
+                &raws,
                 ecs,
                 &drop.0,
                 crate::raws::SpawnType::AtPosition{x : drop.1.x, y: drop.1.y}
@@ -101,7 +105,7 @@ pub fn delete_the_dead(ecs : &mut World) {
         let death_effects = ecs.read_storage::<OnDeath>();
         if let Some(death_effect) = death_effects.get(*victim) {
             for effect in death_effect.abilities.iter() {
-                if crate::rng::roll_dice(1,100) <= (effect.chance * 100.0) as i32 {
+                if ecs.write_resource::<RandomNumberGenerator>().roll_dice(1,100) <= (effect.chance * 100.0) as i32 {
                     let map = ecs.fetch::<Map>();
                     if let Some(pos) = ecs.read_storage::<Position>().get(*victim) {
                         let spell_entity = crate::raws::find_spell_entity(ecs, &effect.spell).unwrap();
@@ -113,6 +117,7 @@ pub fn delete_the_dead(ecs : &mut World) {
                                 Targets::Tile{ tile_idx : tile_idx as i32 }
                             };
                         add_effect(
+                            ecs,
                             None,
                             EffectType::SpellUse{ spell: crate::raws::find_spell_entity( ecs, &effect.spell ).unwrap() },
                             target
@@ -1,8 +1,9 @@
 use specs::prelude::*;
 use super::*;
 use crate::components::{Pools, Player, Attributes, Confusion, SerializeMe, Duration, StatusEffect, 
-    Name, EquipmentChanged, Slow, DamageOverTime, Skills };
+    Name, EquipmentChanged, Slow, DamageOverTime, Skills, Digging };
 use crate::map::Map;
+use crate::gamelog::{GameLog, GameEvents};
 use crate::gamesystem::{player_hp_at_level, mana_at_level};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
@@ -18,8 +19,8 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
             }
             if let EffectType::Damage{amount} = damage.effect_type {
                 pool.hit_points.current -= amount;
-                add_effect(None, EffectType::Bloodstain, Targets::Single{target});
-                add_effect(None, 
+                add_effect(ecs, None, EffectType::Bloodstain, Targets::Single{target});
+                add_effect(ecs, None, 
                     EffectType::Particle{ 
                         glyph: rltk::to_cp437('‼'),
                         fg : rltk::RGB::named(rltk::ORANGE),
@@ -29,16 +30,20 @@ pub fn inflict_damage(ecs: &mut World, damage: &EffectSpawner, target: Entity) {
                     Targets::Single{target}
                 );
                 if target == *player_entity {
-                    crate::gamelog::record_event("Damage Taken", amount);
+                    ecs.write_resource::<GameEvents>().record("Damage Taken", amount);
+                }
+                // Getting hurt puts a stop to any digging
+                if ecs.write_storage::<Digging>().remove(target).is_some() && target == *player_entity {
+                    crate::gamelog::Logger::new().append("You stop digging.").log(&mut ecs.write_resource::<GameLog>());
                 }
                 if let Some(creator) = damage.creator {
                     if creator == *player_entity {
-                        crate::gamelog::record_event("Damage Inflicted", amount);
+                        ecs.write_resource::<GameEvents>().record("Damage Inflicted", amount);
                     }
                 }
 
                 if pool.hit_points.current < 1 {
-                    add_effect(damage.creator, EffectType::EntityDeath, Targets::Single{target});
+                    add_effect(ecs, damage.creator, EffectType::EntityDeath, Targets::Single{target});
                 }
             }
         }
@@ -58,7 +63,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
     let mut attributes = ecs.write_storage::<Attributes>();
 
     if let Some(pos) = entity_position(ecs, target) {
-        crate::spatial::remove_entity(target, pos as usize);
+        ecs.fetch_mut::<Map>().spatial.remove_entity(target, pos as usize);
     }
 
     if let Some(source) = effect.creator {
@@ -80,26 +85,26 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
                         .color(rltk::MAGENTA)
                         .append("Congratulations, you are now level")
                         .append(format!("{}", player_stats.level))
-                        .log();
+                        .log(&mut ecs.write_resource::<GameLog>());
 
                     // Improve a random attribute
-                    let attr_to_boost = crate::rng::roll_dice(1, 4);
+                    let attr_to_boost = ecs.write_resource::<rltk::RandomNumberGenerator>().roll_dice(1, 4);
                     match attr_to_boost {
                         1 => {
                             player_attributes.might.base += 1;
-                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel stronger!").log();
+                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel stronger!").log(&mut ecs.write_resource::<GameLog>());
                         }
                         2 => {
                             player_attributes.fitness.base += 1;
-                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel healthier!").log();
+                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel healthier!").log(&mut ecs.write_resource::<GameLog>());
                         }
                         3 => {
                             player_attributes.quickness.base += 1;
-                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel quicker!").log();
+                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel quicker!").log(&mut ecs.write_resource::<GameLog>());
                         }
                         _ => {
                             player_attributes.intelligence.base += 1;
-                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel smarter!").log();
+                            crate::gamelog::Logger::new().color(rltk::GREEN).append("You feel smarter!").log(&mut ecs.write_resource::<GameLog>());
                         }
                     }
 
@@ -131,7 +136,7 @@ pub fn death(ecs: &mut World, effect: &EffectSpawner, target : Entity) {
                     let map = ecs.fetch::<Map>();
                     for i in 0..10 {
                         if player_pos.y - i > 1 {
-                            add_effect(None, 
+                            add_effect(ecs, None, 
                                 EffectType::Particle{ 
                                     glyph: rltk::to_cp437('░'),
                                     fg : rltk::RGB::named(rltk::GOLD),
@@ -153,7 +158,7 @@ pub fn heal_damage(ecs: &mut World, heal: &EffectSpawner, target: Entity) {
     if let Some(pool) = pools.get_mut(target) {
         if let EffectType::Healing{amount} = heal.effect_type {
             pool.hit_points.current = i32::min(pool.hit_points.max, pool.hit_points.current + amount);
-            add_effect(None, 
+            add_effect(ecs, None, 
                 EffectType::Particle{ 
                     glyph: rltk::to_cp437('‼'),
                     fg : rltk::RGB::named(rltk::GREEN),
@@ -171,7 +176,7 @@ pub fn restore_mana(ecs: &mut World, mana: &EffectSpawner, target: Entity) {
     if let Some(pool) = pools.get_mut(target) {
         if let EffectType::Mana{amount} = mana.effect_type {
             pool.mana.current = i32::min(pool.mana.max, pool.mana.current + amount);
-            add_effect(None, 
+            add_effect(ecs, None, 
                 EffectType::Particle{ 
                     glyph: rltk::to_cp437('‼'),
                     fg : rltk::RGB::named(rltk::BLUE),
@@ -1,4 +1,3 @@
-use std::sync::Mutex;
 use specs::prelude::*;
 use std::collections::{HashSet, VecDeque};
 use crate::map::Map;
@@ -9,13 +8,12 @@ mod particles;
 mod triggers;
 mod hunger;
 mod movement;
+mod terrain;
+mod noise;
+pub use noise::*;
 use crate::components::AttributeBonus;
 use rltk::Point;
 
-lazy_static! {
-    pub static ref EFFECT_QUEUE : Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
-}
-
 #[derive(Debug)]
 pub enum EffectType { 
     Damage { amount : i32 },
@@ -30,10 +28,15 @@ pub enum EffectType {
     Mana { amount : i32 },
     Confusion { turns : i32 },
     TriggerFire { trigger: Entity },
-    TeleportTo { x:i32, y:i32, depth: i32, player_only : bool },
+    TeleportTo { x:i32, y:i32, level: crate::map::LevelKey, player_only : bool },
     AttributeEffect { bonus : AttributeBonus, name : String, duration : i32 },
     Slow { initiative_penalty : f32 },
-    DamageOverTime { damage : i32 }
+    DamageOverTime { damage : i32 },
+    Dig { turns : i32 },
+    Ignite,
+    BurnOut,
+    Gas { gas : crate::map::GasType, amount : f32 },
+    Noise { volume : i32 }
 }
 
 #[derive(Clone, Debug)]
@@ -52,21 +55,32 @@ pub struct EffectSpawner {
     dedupe : HashSet<Entity>
 }
 
-pub fn add_effect(creator : Option<Entity>, effect_type: EffectType, targets : Targets) {
-    EFFECT_QUEUE
-        .lock()
-        .unwrap()
-        .push_back(EffectSpawner{
+/// Effects waiting to be applied, oldest first. Systems queue them up as they run, and
+/// `run_effects_queue` applies them once the systems are done.
+#[derive(Default)]
+pub struct EffectQueue {
+    queue : VecDeque<EffectSpawner>
+}
+
+impl EffectQueue {
+    pub fn add(&mut self, creator : Option<Entity>, effect_type: EffectType, targets : Targets) {
+        self.queue.push_back(EffectSpawner{
             creator,
             effect_type,
             targets,
             dedupe : HashSet::new()
         });
+    }
+}
+
+/// Queues an effect from outside a system, where there's only the world to hand.
+pub fn add_effect(ecs : &World, creator : Option<Entity>, effect_type: EffectType, targets : Targets) {
+    ecs.fetch_mut::<EffectQueue>().add(creator, effect_type, targets);
 }
 
 pub fn run_effects_queue(ecs : &mut World) {
     loop {
-        let effect : Option<EffectSpawner> = EFFECT_QUEUE.lock().unwrap().pop_front();
+        let effect : Option<EffectSpawner> = ecs.fetch_mut::<EffectQueue>().queue.pop_front();
         if let Some(mut effect) = effect {
             target_applicator(ecs, &mut effect);
         } else {
@@ -82,6 +96,9 @@ fn target_applicator(ecs : &mut World, effect : &mut EffectSpawner) {
         triggers::spell_trigger(effect.creator, spell, &effect.targets, ecs);
     } else if let EffectType::TriggerFire{trigger} = effect.effect_type {
         triggers::trigger(effect.creator, trigger, &effect.targets, ecs);
+    } else if let EffectType::Noise{volume} = effect.effect_type {
+        // One noise, however many tiles it comes from
+        noise::make_noise(ecs, effect.creator, volume, &effect.targets);
     } else {
         match &effect.targets.clone() {
             Targets::Tile{tile_idx} => affect_tile(ecs, effect, *tile_idx),
@@ -109,7 +126,7 @@ fn tile_effect_hits_entities(effect: &EffectType) -> bool {
 
 fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx : i32) {
     if tile_effect_hits_entities(&effect.effect_type) {
-        let content = crate::spatial::get_tile_content_clone(tile_idx as usize);
+        let content = ecs.fetch::<Map>().spatial.get_tile_content_clone(tile_idx as usize);
         content.iter().for_each(|entity| affect_entity(ecs, effect, *entity));
     }
 
@@ -117,6 +134,10 @@ fn affect_tile(ecs: &mut World, effect: &mut EffectSpawner, tile_idx : i32) {
         EffectType::Bloodstain => damage::bloodstain(ecs, tile_idx),
         EffectType::Particle{..} => particles::particle_to_tile(ecs, tile_idx, &effect),
         EffectType::ParticleProjectile{..} => particles::projectile(ecs, tile_idx, &effect),
+        EffectType::Dig{..} => terrain::dig(ecs, effect, tile_idx),
+        EffectType::Ignite => terrain::ignite(ecs, tile_idx),
+        EffectType::BurnOut => terrain::burn_out(ecs, tile_idx),
+        EffectType::Gas{..} => terrain::release_gas(ecs, effect, tile_idx),
         _ => {}
     }
 }
@@ -131,6 +152,8 @@ fn affect_entity(ecs: &mut World, effect: &mut EffectSpawner, target: Entity) {
         EffectType::EntityDeath => damage::death(ecs, effect, target),
         EffectType::Bloodstain{..} => if let Some(pos) = entity_position(ecs, target) { damage::bloodstain(ecs, pos) },
         EffectType::Particle{..} => if let Some(pos) = entity_position(ecs, target) { particles::particle_to_tile(ecs, pos, &effect) },
+        EffectType::Ignite => if let Some(pos) = entity_position(ecs, target) { terrain::ignite(ecs, pos) },
+        EffectType::Gas{..} => if let Some(pos) = entity_position(ecs, target) { terrain::release_gas(ecs, effect, pos) },
         EffectType::WellFed => hunger::well_fed(ecs, effect, target),
         EffectType::Healing{..} => damage::heal_damage(ecs, effect, target),
         EffectType::Mana{..} => damage::restore_mana(ecs, effect, target),
@@ -0,0 +1,80 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Position, Faction, MoveMode, Movement, WantsToInvestigate};
+use crate::map::Map;
+use crate::raws::Reaction;
+use rltk::{BaseMap, Algorithm2D, Point};
+
+/// How far (in steps around walls) some common noises carry.
+pub const COMBAT_NOISE : i32 = 8;
+pub const DOOR_NOISE : i32 = 5;
+pub const SHOUT_NOISE : i32 = 16;
+
+/// How many turns a monster spends looking for the source of a noise before giving up.
+const INVESTIGATE_TURNS : i32 = 20;
+
+/// Sound goes anywhere that isn't solid rock; creatures don't stop it.
+struct SoundMap<'a> {
+    map : &'a Map
+}
+
+impl<'a> BaseMap for SoundMap<'a> {
+    fn get_available_exits(&self, idx:usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        let mut exits = rltk::SmallVec::new();
+        let x = idx as i32 % self.map.width;
+        let y = idx as i32 / self.map.width;
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || nx > self.map.width-1 || ny < 0 || ny > self.map.height-1 { continue; }
+            let exit = self.map.xy_idx(nx, ny);
+            if !self.map.tile_set.opaque(self.map.tiles[exit]) {
+                exits.push((exit, if *dx != 0 && *dy != 0 { 1.45 } else { 1.0 }));
+            }
+        }
+        exits
+    }
+}
+
+impl<'a> Algorithm2D for SoundMap<'a> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
+    }
+}
+
+/// Makes a noise of `volume` at the targets. Monsters that hear it, and would fight
+/// whoever made it, go to take a look.
+pub fn make_noise(ecs: &mut World, source: Option<Entity>, volume: i32, targets: &Targets) {
+    let starts : Vec<usize> = match targets {
+        Targets::Tile{tile_idx} => vec![*tile_idx as usize],
+        Targets::Tiles{tiles} => tiles.iter().map(|t| *t as usize).collect(),
+        Targets::Single{target} => entity_position(ecs, *target).iter().map(|t| *t as usize).collect(),
+        Targets::TargetList{targets} => targets.iter().filter_map(|t| entity_position(ecs, *t)).map(|t| t as usize).collect()
+    };
+    if starts.is_empty() { return; }
+
+    let map = ecs.fetch::<Map>();
+    let heard = rltk::DijkstraMap::new(map.width as usize, map.height as usize, &starts, &SoundMap{ map : &*map }, volume as f32);
+
+    let player = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let factions = ecs.read_storage::<Faction>();
+    let move_modes = ecs.read_storage::<MoveMode>();
+    let mut investigate = ecs.write_storage::<WantsToInvestigate>();
+    let source_faction = source.and_then(|s| factions.get(s)).map(|f| f.name.clone());
+    let raws = crate::raws::get_raws(ecs);
+
+    for (entity, pos, faction, mode) in (&entities, &positions, &factions, &move_modes).join() {
+        if entity == player || Some(entity) == source { continue; }
+        if let Movement::Static = mode.mode { continue; }
+        let idx = map.xy_idx(pos.x, pos.y);
+        if heard.map[idx] > volume as f32 { continue; }
+        if let Some(source_faction) = &source_faction {
+            if crate::raws::faction_reaction(&faction.name, source_faction, &raws) != Reaction::Attack {
+                continue;
+            }
+        }
+        investigate.insert(entity, WantsToInvestigate{ idx : starts[0] as i32, turns : INVESTIGATE_TURNS })
+            .expect("Unable to insert");
+    }
+}
@@ -0,0 +1,145 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Position, Viewshed, BlocksVisibility, Digging, Flammable};
+use crate::map::{Map, TileType};
+use crate::gamelog::GameLog;
+
+pub fn dig(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
+    if let EffectType::Dig{turns} = effect.effect_type {
+        let idx = tile_idx as usize;
+        let dug_through = {
+            let mut map = ecs.fetch_mut::<Map>();
+            let x = tile_idx % map.width;
+            let y = tile_idx / map.width;
+            let on_edge = x < 1 || x > map.width-2 || y < 1 || y > map.height-2;
+            if on_edge || !map.tile_set.has_flag(map.tiles[idx], "diggable") {
+                std::mem::drop(map);
+                stop_digging(ecs, effect.creator);
+                return;
+            }
+            let progress = map.dig_progress.entry(idx).or_insert(0);
+            *progress += 1;
+            *progress >= turns
+        };
+
+        if dug_through {
+            ecs.fetch_mut::<Map>().dig_progress.remove(&idx);
+            change_tile(ecs, idx, TileType::Floor);
+            stop_digging(ecs, effect.creator);
+            if effect.creator == Some(*ecs.fetch::<Entity>()) {
+                crate::gamelog::Logger::new().append("You break through the rock.").log(&mut ecs.write_resource::<GameLog>());
+            }
+        } else if let Some(digger) = effect.creator {
+            // Keep at it on the following turns
+            if ecs.entities().is_alive(digger) {
+                ecs.write_storage::<Digging>()
+                    .insert(digger, Digging{ tile_idx, turns })
+                    .expect("Unable to insert");
+            }
+        }
+
+        add_effect(ecs, None,
+            EffectType::Particle{
+                glyph : rltk::to_cp437('*'),
+                fg : rltk::RGB::named(rltk::BURLYWOOD),
+                bg : rltk::RGB::named(rltk::BLACK),
+                lifespan : 150.0
+            },
+            Targets::Tile{ tile_idx }
+        );
+    }
+}
+
+fn stop_digging(ecs: &mut World, digger: Option<Entity>) {
+    if let Some(digger) = digger {
+        ecs.write_storage::<Digging>().remove(digger);
+    }
+}
+
+/// How many turns flammable ground burns for.
+const GROUND_FUEL : i32 = 3;
+/// How many more turns a wooden prop keeps a fire going.
+const PROP_FUEL : i32 = 6;
+
+fn flammable_content(ecs: &World, idx: usize) -> Vec<Entity> {
+    let flammable = ecs.read_storage::<Flammable>();
+    ecs.fetch::<Map>().spatial.get_tile_content_clone(idx)
+        .into_iter()
+        .filter(|entity| flammable.get(*entity).is_some())
+        .collect()
+}
+
+pub fn ignite(ecs: &mut World, tile_idx : i32) {
+    let idx = tile_idx as usize;
+    let mut fuel = 0;
+    let flammable_ground = {
+        let map = ecs.fetch::<Map>();
+        map.tile_set.has_flag(map.tiles[idx], "flammable")
+    };
+    if flammable_ground {
+        fuel += GROUND_FUEL;
+    }
+    fuel += PROP_FUEL * flammable_content(ecs, idx).len() as i32;
+
+    if fuel > 0 {
+        ecs.fetch_mut::<Map>().fires.entry(idx).or_insert(fuel);
+    }
+}
+
+/// A fire has gone out: whatever was burning there is now ash.
+pub fn burn_out(ecs: &mut World, tile_idx : i32) {
+    let idx = tile_idx as usize;
+    for entity in flammable_content(ecs, idx) {
+        ecs.fetch_mut::<Map>().spatial.remove_entity(entity, idx);
+        ecs.delete_entity(entity).expect("Unable to delete");
+    }
+
+    let new_tile = {
+        let map = ecs.fetch::<Map>();
+        let tile = map.tiles[idx];
+        if map.tile_set.has_flag(tile, "flammable") { TileType::Ash } else { tile }
+    };
+    change_tile(ecs, idx, new_tile);
+}
+
+pub fn release_gas(ecs: &mut World, effect: &EffectSpawner, tile_idx : i32) {
+    if let EffectType::Gas{gas, amount} = effect.effect_type {
+        let mut map = ecs.fetch_mut::<Map>();
+        if !map.tile_set.opaque(map.tiles[tile_idx as usize]) {
+            map.add_gas(tile_idx as usize, gas, amount);
+        }
+    }
+}
+
+/// Turns the tile at `idx` into `new_tile`, and brings everything that depends on the
+/// terrain up to date: what blocks sight and movement there, and the viewsheds that can
+/// reach it. The dungeon master picks up the change with the rest of the level when the
+/// player leaves it.
+pub fn change_tile(ecs: &mut World, idx: usize, new_tile: TileType) {
+    let mut map = ecs.fetch_mut::<Map>();
+    let map = &mut *map;
+    map.tiles[idx] = new_tile;
+
+    let blockers = ecs.read_storage::<BlocksVisibility>();
+    let blocks_view = map.spatial.get_tile_content_clone(idx)
+        .iter()
+        .any(|entity| blockers.get(*entity).is_some());
+    if blocks_view {
+        map.view_blocked.insert(idx);
+    } else {
+        map.view_blocked.remove(&idx);
+    }
+    map.view_changed();
+    map.terrain_changed();
+    map.spatial.refresh_tile(&map.tiles, &map.tile_set, idx);
+
+    let tile_pt = rltk::Point::new(idx as i32 % map.width, idx as i32 / map.width);
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
+    let positions = ecs.read_storage::<Position>();
+    for (viewshed, pos) in (&mut viewsheds, &positions).join() {
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(tile_pt, rltk::Point::new(pos.x, pos.y));
+        if distance <= viewshed.range as f32 {
+            viewshed.dirty = true;
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use super::*;
 use crate::components::*;
 use crate::RunState;
+use crate::gamelog::GameLog;
 
 pub fn item_trigger(creator : Option<Entity>, item: Entity, targets : &Targets, ecs: &mut World) {
     // Check charges
@@ -10,7 +11,7 @@ pub fn item_trigger(creator : Option<Entity>, item: Entity, targets : &Targets,
             crate::gamelog::Logger::new()
                 .item_name(&ecs.read_storage::<Name>().get(item).unwrap().name)
                 .append("is out of charges!")
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
             return;
         } else {
             c.charges -= 1;
@@ -84,8 +85,7 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
 
     // Simple particle spawn
     if let Some(part) = ecs.read_storage::<SpawnParticleBurst>().get(entity) {
-        add_effect(
-            creator, 
+        add_effect(ecs, creator, 
             EffectType::Particle{
                 glyph : part.glyph,
                 fg : part.color,
@@ -120,19 +120,19 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
 
     // Providing food
     if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
-        add_effect(creator, EffectType::WellFed, targets.clone());
+        add_effect(ecs, creator, EffectType::WellFed, targets.clone());
         let names = ecs.read_storage::<Name>();
         crate::gamelog::Logger::new()
             .append("You eat the")
             .item_name(&names.get(entity).unwrap().name)
-            .log();
+            .log(&mut ecs.write_resource::<GameLog>());
         did_something = true;
     }
 
     // Magic mapper
     if ecs.read_storage::<MagicMapper>().get(entity).is_some() {
         let mut runstate = ecs.fetch_mut::<RunState>();
-        crate::gamelog::Logger::new().append("The map is revealed to you!").log();
+        crate::gamelog::Logger::new().append("The map is revealed to you!").log(&mut ecs.write_resource::<GameLog>());
         *runstate = RunState::MagicMapReveal{ row : 0};
         did_something = true;
     }
@@ -155,9 +155,9 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
     if ecs.read_storage::<TownPortal>().get(entity).is_some() {
         let map = ecs.fetch::<Map>();
         if map.depth == 1 {
-            crate::gamelog::Logger::new().append("You are already in town, so the scroll does nothing.").log();
+            crate::gamelog::Logger::new().append("You are already in town, so the scroll does nothing.").log(&mut ecs.write_resource::<GameLog>());
         } else {
-            crate::gamelog::Logger::new().append("You are telported back to town!").log();
+            crate::gamelog::Logger::new().append("You are telported back to town!").log(&mut ecs.write_resource::<GameLog>());
             let mut runstate = ecs.fetch_mut::<RunState>();
             *runstate = RunState::TownPortal;
             did_something = true;
@@ -166,38 +166,37 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
 
     // Healing
     if let Some(heal) = ecs.read_storage::<ProvidesHealing>().get(entity) {
-        add_effect(creator, EffectType::Healing{amount: heal.heal_amount}, targets.clone());
+        add_effect(ecs, creator, EffectType::Healing{amount: heal.heal_amount}, targets.clone());
         did_something = true;
     }
 
     // Mana
     if let Some(mana) = ecs.read_storage::<ProvidesMana>().get(entity) {
-        add_effect(creator, EffectType::Mana{amount: mana.mana_amount}, targets.clone());
+        add_effect(ecs, creator, EffectType::Mana{amount: mana.mana_amount}, targets.clone());
         did_something = true;
     }
 
     // Damage
     if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
-        add_effect(creator, EffectType::Damage{ amount: damage.damage }, targets.clone());
+        add_effect(ecs, creator, EffectType::Damage{ amount: damage.damage }, targets.clone());
         did_something = true;
     }
 
     // Confusion
     if let Some(_confusion) = ecs.read_storage::<Confusion>().get(entity) {
         if let Some(duration) = ecs.read_storage::<Duration>().get(entity) {
-            add_effect(creator, EffectType::Confusion{ turns : duration.turns }, targets.clone());
+            add_effect(ecs, creator, EffectType::Confusion{ turns : duration.turns }, targets.clone());
             did_something = true;
         }
     }
 
     // Teleport
     if let Some(teleport) = ecs.read_storage::<TeleportTo>().get(entity) {
-        add_effect(
-            creator, 
+        add_effect(ecs, creator, 
             EffectType::TeleportTo{ 
                 x : teleport.x, 
                 y : teleport.y, 
-                depth: teleport.depth, 
+                level: teleport.level.clone(),
                 player_only: teleport.player_only 
             }, 
             targets.clone()
@@ -207,8 +206,7 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
 
     // Attribute Modifiers
     if let Some(attr) = ecs.read_storage::<AttributeBonus>().get(entity) {
-        add_effect(
-            creator,
+        add_effect(ecs, creator,
             EffectType::AttributeEffect{
                 bonus : attr.clone(),
                 duration : 10,
@@ -237,13 +235,36 @@ fn event_trigger(creator : Option<Entity>, entity: Entity, targets : &Targets, e
 
     // Slow
     if let Some(slow) = ecs.read_storage::<Slow>().get(entity) {
-        add_effect(creator, EffectType::Slow{ initiative_penalty : slow.initiative_penalty }, targets.clone());
+        add_effect(ecs, creator, EffectType::Slow{ initiative_penalty : slow.initiative_penalty }, targets.clone());
         did_something = true;
     }
 
     // Damage Over Time
     if let Some(damage) = ecs.read_storage::<DamageOverTime>().get(entity) {
-        add_effect(creator, EffectType::DamageOverTime{ damage : damage.damage }, targets.clone());
+        add_effect(ecs, creator, EffectType::DamageOverTime{ damage : damage.damage }, targets.clone());
+        did_something = true;
+    }
+
+    // Setting things alight
+    if ecs.read_storage::<Ignites>().get(entity).is_some() {
+        add_effect(ecs, creator, EffectType::Ignite, targets.clone());
+        did_something = true;
+    }
+
+    // Gas clouds
+    if let Some(gas) = ecs.read_storage::<ReleasesGas>().get(entity) {
+        add_effect(ecs, creator, EffectType::Gas{ gas : gas.gas, amount : gas.amount }, targets.clone());
+        did_something = true;
+    }
+
+    // Noise
+    if let Some(noise) = ecs.read_storage::<MakesNoise>().get(entity) {
+        add_effect(ecs, creator, EffectType::Noise{ volume : noise.volume }, targets.clone());
+    }
+
+    // Digging
+    if let Some(digger) = ecs.read_storage::<Digger>().get(entity) {
+        add_effect(ecs, creator, EffectType::Dig{ turns : digger.turns }, targets.clone());
         did_something = true;
     }
 
@@ -256,8 +277,7 @@ fn spawn_line_particles(ecs:&World, start: i32, end: i32, part: &SpawnParticleLi
     let end_pt = rltk::Point::new(end % map.width, end / map.width);
     let line = rltk::line2d(rltk::LineAlg::Bresenham, start_pt, end_pt);
     for pt in line.iter() {
-        add_effect(
-            None, 
+        add_effect(ecs, None, 
             EffectType::Particle{
                 glyph : part.glyph,
                 fg : part.color,
@@ -0,0 +1,73 @@
+use rltk::RGB;
+use serde::{Serialize, Deserialize};
+
+/// How much time passes with each of the player's turns.
+const MINUTES_PER_TURN : i32 = 1;
+const MINUTES_PER_DAY : i32 = 24 * 60;
+/// A new game starts in the morning.
+const START_TIME : i32 = 8 * 60;
+
+/// The time of day, for anything that cares (outdoor light, who's about). Advances one
+/// step per player turn.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct GameClock {
+    /// Minutes since midnight on the first day.
+    pub minutes : i32
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock{ minutes : START_TIME }
+    }
+}
+
+impl GameClock {
+    pub fn advance(&mut self) {
+        self.minutes += MINUTES_PER_TURN;
+    }
+
+    pub fn day(&self) -> i32 { self.minutes / MINUTES_PER_DAY + 1 }
+    pub fn hour(&self) -> i32 { (self.minutes % MINUTES_PER_DAY) / 60 }
+    pub fn minute(&self) -> i32 { self.minutes % 60 }
+
+    /// How much daylight there is, from 0 (dead of night) to 1 (broad day). Dawn runs from
+    /// 5 to 7, and dusk from 18 to 20.
+    pub fn daylight(&self) -> f32 {
+        let hour = (self.minutes % MINUTES_PER_DAY) as f32 / 60.0;
+        match hour {
+            h if h < 5.0 => 0.0,
+            h if h < 7.0 => (h - 5.0) / 2.0,
+            h if h < 18.0 => 1.0,
+            h if h < 20.0 => (20.0 - h) / 2.0,
+            _ => 0.0
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.daylight() < 0.5
+    }
+
+    /// The light falling on outdoor tiles from the sky: white by day, a warm glow at dawn
+    /// and dusk, and a faint blue at night.
+    pub fn ambient_light(&self) -> RGB {
+        let night = RGB::from_f32(0.03, 0.04, 0.09);
+        let twilight = RGB::from_f32(0.9, 0.55, 0.4);
+        let day = RGB::from_f32(1.0, 1.0, 1.0);
+        let daylight = self.daylight();
+        if daylight < 0.5 {
+            night.lerp(twilight, daylight * 2.0)
+        } else {
+            twilight.lerp(day, (daylight - 0.5) * 2.0)
+        }
+    }
+
+    /// How far someone who can see `range` tiles in daylight can see outdoors right now.
+    pub fn outdoor_vision(&self, range : i32) -> i32 {
+        let scale = 0.5 + self.daylight() * 0.5;
+        i32::max(2, (range as f32 * scale).round() as i32)
+    }
+
+    pub fn time_string(&self) -> String {
+        format!("Day {}, {:02}:{:02}", self.day(), self.hour(), self.minute())
+    }
+}
@@ -1,5 +1,5 @@
 use rltk::prelude::*;
-use super::{LogFragment, append_entry};
+use super::{LogFragment, GameLog};
 
 pub struct Logger {
     current_color : RGB,
@@ -29,8 +29,8 @@ impl Logger {
         self
     }
 
-    pub fn log(self) {
-        append_entry(self.fragments)
+    pub fn log(self, log : &mut GameLog) {
+        log.append_entry(self.fragments)
     }
 
     pub fn npc_name<T: ToString>(mut self, text : T) -> Self {
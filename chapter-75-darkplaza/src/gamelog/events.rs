@@ -1,43 +1,29 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
 
-lazy_static! {
-    static ref EVENTS : Mutex<HashMap<String, i32>> = Mutex::new(HashMap::new());
+/// Running totals of things that happened during the run, for the game over screen.
+#[derive(Default)]
+pub struct GameEvents {
+    counts : HashMap<String, i32>
 }
 
-pub fn clear_events() {
-    EVENTS.lock().unwrap().clear();
-}
+impl GameEvents {
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
 
-pub fn record_event<T: ToString>(event: T, n : i32) {
-    let event_name = event.to_string();
-    let mut events_lock = EVENTS.lock();
-    let events = events_lock.as_mut().unwrap();
-    if let Some(e) = events.get_mut(&event_name) {
-        *e += n;
-    } else {
-        events.insert(event_name, n);
+    pub fn record<T: ToString>(&mut self, event: T, n : i32) {
+        *self.counts.entry(event.to_string()).or_insert(0) += n;
     }
-}
 
-pub fn get_event_count<T: ToString>(event: T) -> i32 {
-    let event_name = event.to_string();
-    let events_lock = EVENTS.lock();
-    let events = events_lock.unwrap();
-    if let Some(e) = events.get(&event_name) {
-        *e
-    } else {
-        0
+    pub fn count<T: ToString>(&self, event: T) -> i32 {
+        self.counts.get(&event.to_string()).copied().unwrap_or(0)
     }
-}
 
-pub fn clone_events() -> HashMap<String, i32> {
-    EVENTS.lock().unwrap().clone()
-}
+    pub fn counts(&self) -> &HashMap<String, i32> {
+        &self.counts
+    }
 
-pub fn load_events(events : HashMap<String, i32>) {
-    EVENTS.lock().unwrap().clear();
-    events.iter().for_each(|(k,v)| {
-        EVENTS.lock().unwrap().insert(k.to_string(), *v);
-    });
-}
\ No newline at end of file
+    pub fn restore(&mut self, events : HashMap<String, i32>) {
+        self.counts = events;
+    }
+}
@@ -1,38 +1,40 @@
-use std::sync::Mutex;
 use super::LogFragment;
 use rltk::prelude::*;
 
-lazy_static! {
-    static ref LOG : Mutex<Vec<Vec<LogFragment>>> = Mutex::new(Vec::new());
+/// The messages shown to the player, oldest first.
+#[derive(Default)]
+pub struct GameLog {
+    entries : Vec<Vec<LogFragment>>
 }
 
-pub fn append_entry(fragments : Vec<LogFragment>) {
-    LOG.lock().unwrap().push(fragments);
-}
+impl GameLog {
+    pub fn append_entry(&mut self, fragments : Vec<LogFragment>) {
+        self.entries.push(fragments);
+    }
 
-pub fn clear_log() {
-    LOG.lock().unwrap().clear();
-}
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
 
-pub fn print_log(console: &mut Box<dyn Console>, pos: Point) {
-    let mut y = pos.y;
-    let mut x = pos.x;
-    LOG.lock().unwrap().iter().rev().take(6).for_each(|log| {
-        log.iter().for_each(|frag| {
-            console.print_color(x, y, frag.color.to_rgba(1.0), RGBA::named(rltk::BLACK), &frag.text);
-            x += frag.text.len() as i32;
-            x += 1;
+    pub fn print(&self, console: &mut Box<dyn Console>, pos: Point) {
+        let mut y = pos.y;
+        let mut x = pos.x;
+        self.entries.iter().rev().take(6).for_each(|log| {
+            log.iter().for_each(|frag| {
+                console.print_color(x, y, frag.color.to_rgba(1.0), RGBA::named(rltk::BLACK), &frag.text);
+                x += frag.text.len() as i32;
+                x += 1;
+            });
+            y += 1;
+            x = pos.x;
         });
-        y += 1;
-        x = pos.x;
-    });
-}
+    }
 
-pub fn clone_log() -> Vec<Vec<crate::gamelog::LogFragment>> {
-    LOG.lock().unwrap().clone()
-}
+    pub fn entries(&self) -> &[Vec<LogFragment>] {
+        &self.entries
+    }
 
-pub fn restore_log(log : &mut Vec<Vec<crate::gamelog::LogFragment>>) {
-    LOG.lock().unwrap().clear();
-    LOG.lock().unwrap().append(log);
-}
\ No newline at end of file
+    pub fn restore(&mut self, log : Vec<Vec<LogFragment>>) {
+        self.entries = log;
+    }
+}
@@ -2,8 +2,7 @@ use rltk::prelude::*;
 mod builder;
 pub use builder::*;
 mod logstore;
-use logstore::*;
-pub use logstore::{clear_log, clone_log, restore_log, print_log};
+pub use logstore::GameLog;
 use serde::{Serialize, Deserialize};
 mod events;
 pub use events::*;
@@ -1,11 +1,11 @@
 use rltk::prelude::*;
-use crate::State;
+use crate::input::PlayerCommand;
 use super::{menu_option, menu_box};
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum CheatMenuResult { NoResponse, Cancel, TeleportToExit, Heal, Reveal, GodMode }
 
-pub fn show_cheat_mode(_gs : &mut State, ctx : &mut Rltk) -> CheatMenuResult {
+pub fn show_cheat_mode() {
     let mut draw_batch = DrawBatch::new();
     let count = 4;
     let mut y = (25 - (count / 2)) as i32;
@@ -25,18 +25,16 @@ pub fn show_cheat_mode(_gs : &mut State, ctx : &mut Rltk) -> CheatMenuResult {
     menu_option(&mut draw_batch, 17, y, rltk::to_cp437('G'), "God Mode (No Death)");
 
     draw_batch.submit(6000);
+}
 
-    match ctx.key {
-        None => CheatMenuResult::NoResponse,
-        Some(key) => {
-            match key {
-                VirtualKeyCode::T => CheatMenuResult::TeleportToExit,
-                VirtualKeyCode::H => CheatMenuResult::Heal,
-                VirtualKeyCode::R => CheatMenuResult::Reveal,
-                VirtualKeyCode::G => CheatMenuResult::GodMode,
-                VirtualKeyCode::Escape => CheatMenuResult::Cancel,
-                _ => CheatMenuResult::NoResponse
-            }
-        }
+/// The options are picked in the order they're listed above.
+pub fn cheat_menu_choice(command : Option<PlayerCommand>) -> CheatMenuResult {
+    match command {
+        Some(PlayerCommand::Select(0)) => CheatMenuResult::TeleportToExit,
+        Some(PlayerCommand::Select(1)) => CheatMenuResult::Heal,
+        Some(PlayerCommand::Select(2)) => CheatMenuResult::Reveal,
+        Some(PlayerCommand::Select(3)) => CheatMenuResult::GodMode,
+        Some(PlayerCommand::Cancel) => CheatMenuResult::Cancel,
+        _ => CheatMenuResult::NoResponse
     }
 }
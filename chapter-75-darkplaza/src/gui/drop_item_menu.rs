@@ -1,29 +1,14 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{State, InBackpack};
-use super::{get_item_display_name, ItemMenuResult, item_result_menu};
+use super::{inventory_items, item_result_menu};
 
-pub fn drop_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+/// Anything in the backpack can be dropped, so this lists the same items as the inventory.
+pub fn drop_item_menu(ecs : &World) {
     let mut draw_batch = DrawBatch::new();
-
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
-
-    let mut items : Vec<(Entity, String)> = Vec::new();
-    (&entities, &backpack).join()
-        .filter(|item| item.1.owner == *player_entity )
-        .for_each(|item| {
-            items.push((item.0, get_item_display_name(&gs.ecs, item.0)))
-        });
-
-    let result = item_result_menu(
+    item_result_menu(
         &mut draw_batch,
         "Drop which item?",
-        items.len(),
-        &items,
-        ctx.key
+        &inventory_items(ecs)
     );
     draw_batch.submit(6000);
-    result
-}
\ No newline at end of file
+}
@@ -1,9 +1,12 @@
 use rltk::prelude::*;
+use specs::prelude::*;
+use crate::{gamelog::GameEvents, input::PlayerCommand};
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult { NoSelection, QuitToMenu }
 
-pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
+pub fn game_over(ecs : &World) {
+    let events = ecs.fetch::<GameEvents>();
     let mut draw_batch = DrawBatch::new();
     draw_batch.print_color_centered(
         15, 
@@ -23,17 +26,17 @@ pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
 
     draw_batch.print_color_centered(
         19,
-        &format!("You lived for {} turns.", crate::gamelog::get_event_count("Turn")),
+        &format!("You lived for {} turns.", events.count("Turn")),
         ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
     );
     draw_batch.print_color_centered(
         20,
-        &format!("You suffered {} points of damage.", crate::gamelog::get_event_count("Damage Taken")),
+        &format!("You suffered {} points of damage.", events.count("Damage Taken")),
         ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK))
     );
     draw_batch.print_color_centered(
         21,
-        &format!("You inflicted {} points of damage.", crate::gamelog::get_event_count("Damage Inflicted")),
+        &format!("You inflicted {} points of damage.", events.count("Damage Inflicted")),
         ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::BLACK)));
 
     draw_batch.print_color_centered(
@@ -43,8 +46,10 @@ pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
     );
 
     draw_batch.submit(6000);
+}
 
-    match ctx.key {
+pub fn game_over_choice(command : Option<PlayerCommand>) -> GameOverResult {
+    match command {
         None => GameOverResult::NoSelection,
         Some(_) => GameOverResult::QuitToMenu
     }
@@ -2,7 +2,7 @@ use rltk::prelude::*;
 use specs::prelude::*;
 use crate::{Pools, Map, Name, InBackpack,
     Equipped, HungerClock, HungerState, Attributes, Attribute, Consumable,
-    StatusEffect, Duration, KnownSpells, Weapon, gamelog };
+    StatusEffect, Duration, KnownSpells, Weapon, GameClock, gamelog };
 use super::{draw_tooltips, get_item_display_name, get_item_color};
 
 fn draw_attribute(name : &str, attribute : &Attribute, y : i32, draw_batch: &mut DrawBatch) {
@@ -51,6 +51,19 @@ pub fn map_label(ecs: &World, draw_batch: &mut DrawBatch) {
     draw_batch.print_color(Point::new(x_pos+1, 0), &map.name, ColorPair::new(white, black));
 }
 
+/// Shows the time of day on the bottom edge of the map frame.
+fn clock_label(ecs: &World, draw_batch: &mut DrawBatch) {
+    let box_gray : RGB = RGB::from_hex("#999999").expect("Oops");
+    let black = RGB::named(rltk::BLACK);
+    let white = RGB::named(rltk::WHITE);
+
+    let time = ecs.fetch::<GameClock>().time_string();
+    let x_pos = 47 - time.len() as i32;
+    draw_batch.set(Point::new(x_pos - 1, 45), ColorPair::new(box_gray, black), to_cp437('┤'));
+    draw_batch.set(Point::new(x_pos + time.len() as i32, 45), ColorPair::new(box_gray, black), to_cp437('├'));
+    draw_batch.print_color(Point::new(x_pos, 45), &time, ColorPair::new(white, black));
+}
+
 fn draw_stats(ecs: &World, draw_batch: &mut DrawBatch, player_entity: &Entity) {
     let black = RGB::named(rltk::BLACK);
     let white = RGB::named(rltk::WHITE);
@@ -264,6 +277,7 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
 
     box_framework(&mut draw_batch);
     map_label(ecs, &mut draw_batch);
+    clock_label(ecs, &mut draw_batch);
     draw_stats(ecs, &mut draw_batch, &player_entity);
     draw_attributes(ecs, &mut draw_batch, &player_entity);
     initiative_weight(ecs, &mut draw_batch, &player_entity);
@@ -271,7 +285,7 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
     y += consumables(ecs, &mut draw_batch, &player_entity, y);
     spells(ecs, &mut draw_batch, &player_entity, y);
     status(ecs, &mut draw_batch, &player_entity);
-    gamelog::print_log(&mut rltk::BACKEND_INTERNAL.lock().consoles[1].console, Point::new(1, 23));
+    ecs.fetch::<gamelog::GameLog>().print(&mut rltk::BACKEND_INTERNAL.lock().consoles[1].console, Point::new(1, 23));
     draw_tooltips(ecs, ctx);
 
     draw_batch.submit(5000);
@@ -1,19 +1,17 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{Name, State, InBackpack, Equipped, MasterDungeonMap, Item, ObfuscatedName };
-use super::{get_item_display_name, item_result_menu, ItemMenuResult};
+use crate::{Name, InBackpack, Equipped, MasterDungeonMap, Item, ObfuscatedName };
+use super::{get_item_display_name, item_result_menu};
 
-pub fn identify_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let mut draw_batch = DrawBatch::new();
-
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let equipped = gs.ecs.read_storage::<Equipped>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
-    let item_components = gs.ecs.read_storage::<Item>();
-    let names = gs.ecs.read_storage::<Name>();
-    let dm = gs.ecs.fetch::<MasterDungeonMap>();
-    let obfuscated = gs.ecs.read_storage::<ObfuscatedName>();
+pub fn unidentified_items(ecs : &World) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+    let item_components = ecs.read_storage::<Item>();
+    let names = ecs.read_storage::<Name>();
+    let dm = ecs.fetch::<MasterDungeonMap>();
+    let obfuscated = ecs.read_storage::<ObfuscatedName>();
 
     let mut items : Vec<(Entity, String)> = Vec::new();
     (&entities, &item_components).join()
@@ -41,16 +39,18 @@ pub fn identify_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Optio
             keep
         })
         .for_each(|item| {
-            items.push((item.0, get_item_display_name(&gs.ecs, item.0)))
+            items.push((item.0, get_item_display_name(ecs, item.0)))
         });
 
-    let result = item_result_menu(
+    items
+}
+
+pub fn identify_menu(ecs : &World) {
+    let mut draw_batch = DrawBatch::new();
+    item_result_menu(
         &mut draw_batch,
         "Inventory",
-        items.len(),
-        &items,
-        ctx.key
+        &unidentified_items(ecs)
     );
     draw_batch.submit(6000);
-    result
-}
\ No newline at end of file
+}
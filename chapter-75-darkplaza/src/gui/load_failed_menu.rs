@@ -0,0 +1,33 @@
+use rltk::prelude::*;
+
+/// Explains why the saved game couldn't be loaded. Any key goes back to the main menu.
+pub fn load_failed(reason : &str) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.print_color_centered(
+        15,
+        "Your saved game couldn't be loaded.",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+
+    // Long reasons are wrapped to fit the screen
+    let mut y = 17;
+    let mut line = String::new();
+    for word in reason.split_whitespace() {
+        if !line.is_empty() && line.len() + word.len() >= 70 {
+            draw_batch.print_color_centered(y, &line, ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
+            y += 1;
+            line.clear();
+        }
+        if !line.is_empty() { line.push(' '); }
+        line.push_str(word);
+    }
+    draw_batch.print_color_centered(y, &line, ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK)));
+
+    draw_batch.print_color_centered(
+        y + 2,
+        "Press any key to return to the menu.",
+        ColorPair::new(RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK))
+    );
+
+    draw_batch.submit(6000);
+}
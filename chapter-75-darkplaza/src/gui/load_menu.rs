@@ -0,0 +1,74 @@
+use rltk::prelude::*;
+use specs::prelude::*;
+use crate::{input::PlayerCommand, saveload_system::{SaveSlot, SaveSummary}};
+use super::menu_option;
+
+#[derive(PartialEq, Clone)]
+pub enum LoadMenuResult { NoResponse, Cancel, Selected{ slot : String } }
+
+/// Turns seconds since the Unix epoch into a UTC date and time.
+fn format_timestamp(seconds : u64) -> String {
+    let days = (seconds / 86400) as i64;
+    let minutes_today = (seconds % 86400) / 60;
+
+    // Howard Hinnant's days-to-civil-date algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes_today / 60, minutes_today % 60)
+}
+
+pub(super) fn describe(summary : &Option<SaveSummary>) -> String {
+    match summary {
+        Some(s) => format!("{}, level {}, {} (depth {}) - {}",
+            s.character, s.level, s.location, s.depth, format_timestamp(s.saved_at)),
+        None => "An old or damaged save".to_string()
+    }
+}
+
+/// Lists the save slots read when the menu was opened, newest first.
+pub fn load_menu(ecs : &World) {
+    let slots = ecs.fetch::<Vec<SaveSlot>>();
+    let mut draw_batch = DrawBatch::new();
+    let count = slots.len();
+    let mut y = (25 - (count / 2)) as i32;
+    draw_batch.draw_box(
+        Rect::with_size(5, y-2, 70, (count+3) as i32),
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
+    );
+    draw_batch.print_color(
+        Point::new(8, y-2),
+        "Load which game?",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+    draw_batch.print_color(
+        Point::new(8, y+count as i32+1),
+        "ESCAPE to cancel",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+
+    for (j, slot) in slots.iter().enumerate() {
+        menu_option(&mut draw_batch, 7, y, 97+j as rltk::FontCharType, describe(&slot.summary));
+        y += 1;
+    }
+
+    draw_batch.submit(6000);
+}
+
+pub fn load_menu_choice(ecs : &World, command : Option<PlayerCommand>) -> LoadMenuResult {
+    let slots = ecs.fetch::<Vec<SaveSlot>>();
+    match command {
+        Some(PlayerCommand::Cancel) => LoadMenuResult::Cancel,
+        Some(PlayerCommand::Select(selection)) if selection > -1 && selection < slots.len() as i32 => {
+            LoadMenuResult::Selected{ slot : slots[selection as usize].name.clone() }
+        }
+        _ => LoadMenuResult::NoResponse
+    }
+}
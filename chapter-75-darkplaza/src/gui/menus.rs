@@ -1,6 +1,9 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use super::ItemMenuResult;
+use crate::{RunState, input::PlayerCommand};
+use super::{ItemMenuResult, show_inventory, drop_item_menu, remove_item_menu, remove_curse_menu,
+    identify_menu, show_cheat_mode, ranged_target, show_vendor_menu, main_menu, load_menu, save_menu,
+    game_over, load_failed};
 
 pub fn menu_box<T: ToString>(draw_batch: &mut DrawBatch, x: i32, y: i32, width: i32, title: T) {
     draw_batch.draw_box(
@@ -40,11 +43,9 @@ pub fn menu_option<T:ToString>(draw_batch: &mut DrawBatch, x: i32, y: i32, hotke
 pub fn item_result_menu<S: ToString>(
     draw_batch: &mut DrawBatch,
     title: S,
-    count: usize,
-    items: &[(Entity, String)],
-    key: Option<VirtualKeyCode>
-) -> (ItemMenuResult, Option<Entity>) {
-
+    items: &[(Entity, String)]
+) {
+    let count = items.len();
     let mut y = (25 - (count / 2)) as i32;
     draw_batch.draw_box(
         Rect::with_size(15, y-2, 31, (count+3) as i32), 
@@ -61,28 +62,40 @@ pub fn item_result_menu<S: ToString>(
         ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
     );
 
-    let mut item_list : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for item in items {
+    for (j, item) in items.iter().enumerate() {
         menu_option(draw_batch, 17, y, 97+j as rltk::FontCharType, &item.1);
-        item_list.push(item.0);
         y += 1;
-        j += 1;
     }
+}
 
-    match key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => {
-            match key {
-                VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
-                _ => {
-                    let selection = rltk::letter_to_option(key);
-                    if selection > -1 && selection < count as i32 {
-                        return (ItemMenuResult::Selected, Some(item_list[selection as usize]));
-                    }
-                    (ItemMenuResult::NoResponse, None)
-                }
-            }
+/// What a command does to a lettered list of items.
+pub fn item_result_choice(items: &[(Entity, String)], command: Option<PlayerCommand>) -> (ItemMenuResult, Option<Entity>) {
+    match command {
+        Some(PlayerCommand::Cancel) => (ItemMenuResult::Cancel, None),
+        Some(PlayerCommand::Select(selection)) if selection > -1 && selection < items.len() as i32 => {
+            (ItemMenuResult::Selected, Some(items[selection as usize].0))
         }
+        _ => (ItemMenuResult::NoResponse, None)
+    }
+}
+
+/// Draws whichever menu the game is showing, if any.
+pub fn draw_menu(ecs: &World, ctx: &mut Rltk) {
+    let runstate = (*ecs.fetch::<RunState>()).clone();
+    match runstate {
+        RunState::ShowInventory => show_inventory(ecs),
+        RunState::ShowDropItem => drop_item_menu(ecs),
+        RunState::ShowRemoveItem => remove_item_menu(ecs),
+        RunState::ShowRemoveCurse => remove_curse_menu(ecs),
+        RunState::ShowIdentify => identify_menu(ecs),
+        RunState::ShowCheatMenu => show_cheat_mode(),
+        RunState::ShowTargeting{ range, .. } => ranged_target(ecs, ctx, range),
+        RunState::ShowVendor{ vendor, mode } => show_vendor_menu(ecs, vendor, mode),
+        RunState::MainMenu{ menu_selection } => main_menu(ecs, ctx, menu_selection),
+        RunState::ShowLoadMenu => load_menu(ecs),
+        RunState::ShowSaveMenu{ name } => save_menu(ecs, &name),
+        RunState::GameOver => game_over(ecs),
+        RunState::LoadFailed{ reason } => load_failed(&reason),
+        _ => {}
     }
-}
\ No newline at end of file
+}
@@ -18,8 +18,14 @@ mod ranged_target;
 pub use ranged_target::*;
 mod main_menu;
 pub use main_menu::*;
+mod load_menu;
+pub use load_menu::*;
+mod save_menu;
+pub use save_menu::*;
 mod game_over_menu;
 pub use game_over_menu::*;
+mod load_failed_menu;
+pub use load_failed_menu::*;
 mod cheat_menu;
 pub use cheat_menu::*;
 mod vendor_menu;
@@ -1,14 +1,32 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{State, camera, Viewshed };
+use crate::{camera, Viewshed, input::PlayerCommand };
 use super::ItemMenuResult;
 
-pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuResult, Option<Point>) {
-    let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let player_pos = gs.ecs.fetch::<Point>();
-    let viewsheds = gs.ecs.read_storage::<Viewshed>();
+/// The tiles in view, in range and on screen, or `None` if the player can't see at all.
+fn target_cells(ecs : &World, range : i32) -> Option<Vec<Point>> {
+    let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(ecs);
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
 
+    let visible = viewsheds.get(*player_entity)?;
+    let mut available_cells = Vec::new();
+    for idx in visible.visible_tiles.iter() {
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
+        if distance <= range as f32 {
+            let screen_x = idx.x - min_x;
+            let screen_y = idx.y - min_y;
+            if screen_x > 1 && screen_x < (max_x - min_x)-1 && screen_y > 1 && screen_y < (max_y - min_y)-1 {
+                available_cells.push(*idx);
+            }
+        }
+    }
+    Some(available_cells)
+}
+
+pub fn ranged_target(ecs : &World, ctx : &mut Rltk, range : i32) {
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs);
     let mut draw_batch = DrawBatch::new();
 
     draw_batch.print_color(
@@ -18,45 +36,40 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
     );
 
     // Highlight available target cells
-    let mut available_cells = Vec::new();
-    let visible = viewsheds.get(*player_entity);
-    if let Some(visible) = visible {
-        // We have a viewshed
-        for idx in visible.visible_tiles.iter() {
-            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
-            if distance <= range as f32 {
-                let screen_x = idx.x - min_x;
-                let screen_y = idx.y - min_y;
-                if screen_x > 1 && screen_x < (max_x - min_x)-1 && screen_y > 1 && screen_y < (max_y - min_y)-1 {
-                    draw_batch.set_bg(Point::new(screen_x, screen_y), RGB::named(rltk::BLUE));
-                    available_cells.push(idx);
-                }
-            }
-        }
-    } else {
-        return (ItemMenuResult::Cancel, None);
+    let available_cells = target_cells(ecs, range).unwrap_or_default();
+    for idx in available_cells.iter() {
+        draw_batch.set_bg(Point::new(idx.x - min_x, idx.y - min_y), RGB::named(rltk::BLUE));
     }
 
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
-    let mut mouse_map_pos = mouse_pos;
-    mouse_map_pos.0 += min_x - 1;
-    mouse_map_pos.1 += min_y - 1;
-    let mut valid_target = false;
-    for idx in available_cells.iter() { if idx.x == mouse_map_pos.0 && idx.y == mouse_map_pos.1 { valid_target = true; } }
+    let mouse_map_pos = (mouse_pos.0 + min_x - 1, mouse_pos.1 + min_y - 1);
+    let valid_target = available_cells.iter().any(|idx| idx.x == mouse_map_pos.0 && idx.y == mouse_map_pos.1);
     if valid_target {
         draw_batch.set_bg(Point::new(mouse_pos.0, mouse_pos.1), RGB::named(rltk::CYAN));
-        if ctx.left_click {
-            return (ItemMenuResult::Selected, Some(Point::new(mouse_map_pos.0, mouse_map_pos.1)));
-        }
     } else {
         draw_batch.set_bg(Point::new(mouse_pos.0, mouse_pos.1), RGB::named(rltk::RED));
-        if ctx.left_click {
-            return (ItemMenuResult::Cancel, None);
-        }
     }
 
     draw_batch.submit(5000);
+}
+
+/// Picking a tile that can't be targeted gives up on the whole thing.
+pub fn ranged_target_choice(ecs : &World, range : i32, command : Option<PlayerCommand>) -> (ItemMenuResult, Option<Point>) {
+    let available_cells = match target_cells(ecs, range) {
+        Some(cells) => cells,
+        None => return (ItemMenuResult::Cancel, None)
+    };
 
-    (ItemMenuResult::NoResponse, None)
-}
\ No newline at end of file
+    match command {
+        Some(PlayerCommand::Target{ x, y }) => {
+            if available_cells.iter().any(|idx| idx.x == x && idx.y == y) {
+                (ItemMenuResult::Selected, Some(Point::new(x, y)))
+            } else {
+                (ItemMenuResult::Cancel, None)
+            }
+        }
+        Some(PlayerCommand::Cancel) => (ItemMenuResult::Cancel, None),
+        _ => (ItemMenuResult::NoResponse, None)
+    }
+}
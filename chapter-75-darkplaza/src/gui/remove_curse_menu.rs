@@ -1,19 +1,17 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{Name, State, InBackpack, Equipped, MasterDungeonMap, CursedItem, Item };
-use super::{get_item_display_name, item_result_menu, ItemMenuResult};
+use crate::{Name, InBackpack, Equipped, MasterDungeonMap, CursedItem, Item };
+use super::{get_item_display_name, item_result_menu};
 
-pub fn remove_curse_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let equipped = gs.ecs.read_storage::<Equipped>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
-    let item_components = gs.ecs.read_storage::<Item>();
-    let cursed = gs.ecs.read_storage::<CursedItem>();
-    let names = gs.ecs.read_storage::<Name>();
-    let dm = gs.ecs.fetch::<MasterDungeonMap>();
-
-    let mut draw_batch = DrawBatch::new();
+pub fn cursed_items(ecs : &World) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+    let item_components = ecs.read_storage::<Item>();
+    let cursed = ecs.read_storage::<CursedItem>();
+    let names = ecs.read_storage::<Name>();
+    let dm = ecs.fetch::<MasterDungeonMap>();
 
     let mut items : Vec<(Entity, String)> = Vec::new();
     (&entities, &item_components, &cursed).join()
@@ -37,16 +35,18 @@ pub fn remove_curse_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, O
             keep
         })
         .for_each(|item| {
-            items.push((item.0, get_item_display_name(&gs.ecs, item.0)))
+            items.push((item.0, get_item_display_name(ecs, item.0)))
         });
 
-    let result = item_result_menu(
+    items
+}
+
+pub fn remove_curse_menu(ecs : &World) {
+    let mut draw_batch = DrawBatch::new();
+    item_result_menu(
         &mut draw_batch,
         "Inventory",
-        items.len(),
-        &items,
-        ctx.key
+        &cursed_items(ecs)
     );
     draw_batch.submit(6000);
-    result
 }
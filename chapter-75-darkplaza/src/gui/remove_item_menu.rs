@@ -1,29 +1,28 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{State, Equipped };
-use super::{get_item_display_name, ItemMenuResult, item_result_menu};
+use crate::Equipped;
+use super::{get_item_display_name, item_result_menu};
 
-pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let mut draw_batch = DrawBatch::new();
-
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let backpack = gs.ecs.read_storage::<Equipped>();
-    let entities = gs.ecs.entities();
+pub fn equipped_items(ecs : &World) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let backpack = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
 
     let mut items : Vec<(Entity, String)> = Vec::new();
     (&entities, &backpack).join()
         .filter(|item| item.1.owner == *player_entity )
         .for_each(|item| {
-            items.push((item.0, get_item_display_name(&gs.ecs, item.0)))
+            items.push((item.0, get_item_display_name(ecs, item.0)))
         });
+    items
+}
 
-    let result = item_result_menu(
+pub fn remove_item_menu(ecs : &World) {
+    let mut draw_batch = DrawBatch::new();
+    item_result_menu(
         &mut draw_batch,
         "Remove which item?",
-        items.len(),
-        &items,
-        ctx.key
+        &equipped_items(ecs)
     );
     draw_batch.submit(6000);
-    result
-}
\ No newline at end of file
+}
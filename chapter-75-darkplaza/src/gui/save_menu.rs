@@ -0,0 +1,75 @@
+use rltk::prelude::*;
+use specs::prelude::*;
+use crate::{input::PlayerCommand, saveload_system::SaveSlot};
+use super::describe;
+
+/// Slot names become file names, so they're kept short and plain.
+const MAX_SLOT_NAME : usize = 24;
+
+#[derive(PartialEq, Clone)]
+pub enum SaveMenuResult { NoResponse, Cancel, Editing{ name : String }, Selected{ slot : String } }
+
+/// Asks which slot to save in: the name typed so far, with the saves already made listed
+/// underneath. The one that would be saved over is highlighted.
+pub fn save_menu(ecs : &World, name : &str) {
+    let slots = ecs.fetch::<Vec<SaveSlot>>();
+    let mut draw_batch = DrawBatch::new();
+    let count = slots.len();
+    let y = (25 - (count / 2)) as i32;
+    draw_batch.draw_box(
+        Rect::with_size(5, y-4, 70, (count+5) as i32),
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
+    );
+    draw_batch.print_color(
+        Point::new(8, y-4),
+        "Save as which game?",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+    draw_batch.print_color(
+        Point::new(7, y-2),
+        format!("Name: {}_", name),
+        ColorPair::new(RGB::named(rltk::WHITE), RGB::named(rltk::BLACK))
+    );
+    draw_batch.print_color(
+        Point::new(8, y+count as i32+1),
+        "UP/DOWN to save over a game, ENTER to save, ESCAPE to cancel",
+        ColorPair::new(RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK))
+    );
+
+    for (j, slot) in slots.iter().enumerate() {
+        let color = if slot.name == name { rltk::MAGENTA } else { rltk::GRAY };
+        draw_batch.print_color(
+            Point::new(7, y + j as i32),
+            format!("{}: {}", slot.name, describe(&slot.summary)),
+            ColorPair::new(RGB::named(color), RGB::named(rltk::BLACK))
+        );
+    }
+
+    draw_batch.submit(6000);
+}
+
+pub fn save_menu_choice(ecs : &World, name : &str, command : Option<PlayerCommand>) -> SaveMenuResult {
+    let slots = ecs.fetch::<Vec<SaveSlot>>();
+    let current = slots.iter().position(|s| s.name == name);
+    match command {
+        Some(PlayerCommand::Cancel) => SaveMenuResult::Cancel,
+        Some(PlayerCommand::Confirm) if !name.is_empty() => SaveMenuResult::Selected{ slot : name.to_string() },
+        Some(PlayerCommand::TypeChar(ch)) if name.len() < MAX_SLOT_NAME && (ch.is_ascii_alphanumeric() || ch == '-' || ch == '_') => {
+            SaveMenuResult::Editing{ name : format!("{}{}", name, ch) }
+        }
+        Some(PlayerCommand::Backspace) => {
+            let mut name = name.to_string();
+            name.pop();
+            SaveMenuResult::Editing{ name }
+        }
+        Some(PlayerCommand::MenuDown) if !slots.is_empty() => {
+            let next = current.map_or(0, |i| (i + 1) % slots.len());
+            SaveMenuResult::Editing{ name : slots[next].name.clone() }
+        }
+        Some(PlayerCommand::MenuUp) if !slots.is_empty() => {
+            let previous = current.map_or(slots.len() - 1, |i| (i + slots.len() - 1) % slots.len());
+            SaveMenuResult::Editing{ name : slots[previous].name.clone() }
+        }
+        _ => SaveMenuResult::NoResponse
+    }
+}
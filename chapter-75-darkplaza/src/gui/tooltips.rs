@@ -1,6 +1,6 @@
 use rltk::prelude::*;
 use specs::prelude::*;
-use crate::{Pools, Map, Name, Hidden, camera, Attributes, StatusEffect, Duration };
+use crate::{Pools, Map, Name, Hidden, camera, Attributes, StatusEffect, Duration, Darkvision };
 use super::get_item_display_name;
 
 struct Tooltip {
@@ -44,11 +44,13 @@ impl Tooltip {
 pub fn draw_tooltips(ecs: &World, ctx : &mut Rltk) {
     let mut draw_batch = DrawBatch::new();
 
-    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs);
     let map = ecs.fetch::<Map>();
     let hidden = ecs.read_storage::<Hidden>();
     let attributes = ecs.read_storage::<Attributes>();
     let pools = ecs.read_storage::<Pools>();
+    let darkvision = ecs.read_storage::<Darkvision>();
+    let player_darkvision = darkvision.get(*ecs.fetch::<Entity>());
 
     let mouse_pos = ctx.mouse_pos();
     let mut mouse_map_pos = mouse_pos;
@@ -63,50 +65,58 @@ pub fn draw_tooltips(ecs: &World, ctx : &mut Rltk) {
     }
     if !map.in_bounds(rltk::Point::new(mouse_map_pos.0, mouse_map_pos.1)) { return; }
     let mouse_idx = map.xy_idx(mouse_map_pos.0, mouse_map_pos.1);
-    if !map.visible_tiles[mouse_idx] { return; }
+    if !map.revealed_tiles[mouse_idx] { return; }
 
     let mut tip_boxes : Vec<Tooltip> = Vec::new();
-    crate::spatial::for_each_tile_content(mouse_idx, |entity| {
-        if hidden.get(entity).is_some() { return; }
-        let mut tip = Tooltip::new();
-        tip.add(get_item_display_name(ecs, entity));
-
-        // Comment on attributes
-        let attr = attributes.get(entity);
-        if let Some(attr) = attr {
-            let mut s = "".to_string();
-            if attr.might.bonus < 0 { s += "Weak. " };
-            if attr.might.bonus > 0 { s += "Strong. " };
-            if attr.quickness.bonus < 0 { s += "Clumsy. " };
-            if attr.quickness.bonus > 0 { s += "Agile. " };
-            if attr.fitness.bonus < 0 { s += "Unheathy. " };
-            if attr.fitness.bonus > 0 { s += "Healthy." };
-            if attr.intelligence.bonus < 0 { s += "Unintelligent. "};
-            if attr.intelligence.bonus > 0 { s += "Smart. "};
-            if s.is_empty() {
-                s = "Quite Average".to_string();
+    if map.visible_tiles[mouse_idx] && map.can_make_out(*ecs.fetch::<Point>(), player_darkvision, mouse_idx) {
+        map.spatial.for_each_tile_content(mouse_idx, |entity| {
+            if hidden.get(entity).is_some() { return; }
+            let mut tip = Tooltip::new();
+            tip.add(get_item_display_name(ecs, entity));
+
+            // Comment on attributes
+            let attr = attributes.get(entity);
+            if let Some(attr) = attr {
+                let mut s = "".to_string();
+                if attr.might.bonus < 0 { s += "Weak. " };
+                if attr.might.bonus > 0 { s += "Strong. " };
+                if attr.quickness.bonus < 0 { s += "Clumsy. " };
+                if attr.quickness.bonus > 0 { s += "Agile. " };
+                if attr.fitness.bonus < 0 { s += "Unheathy. " };
+                if attr.fitness.bonus > 0 { s += "Healthy." };
+                if attr.intelligence.bonus < 0 { s += "Unintelligent. "};
+                if attr.intelligence.bonus > 0 { s += "Smart. "};
+                if s.is_empty() {
+                    s = "Quite Average".to_string();
+                }
+                tip.add(s);
             }
-            tip.add(s);
-        }
 
-        // Comment on pools
-        let stat = pools.get(entity);
-        if let Some(stat) = stat {
-            tip.add(format!("Level: {}", stat.level));
-        }
+            // Comment on pools
+            let stat = pools.get(entity);
+            if let Some(stat) = stat {
+                tip.add(format!("Level: {}", stat.level));
+            }
 
-        // Status effects
-        let statuses = ecs.read_storage::<StatusEffect>();
-        let durations = ecs.read_storage::<Duration>();
-        let names = ecs.read_storage::<Name>();
-        for (status, duration, name) in (&statuses, &durations, &names).join() {
-            if status.target == entity {
-                tip.add(format!("{} ({})", name.name, duration.turns));
+            // Status effects
+            let statuses = ecs.read_storage::<StatusEffect>();
+            let durations = ecs.read_storage::<Duration>();
+            let names = ecs.read_storage::<Name>();
+            for (status, duration, name) in (&statuses, &durations, &names).join() {
+                if status.target == entity {
+                    tip.add(format!("{} ({})", name.name, duration.turns));
+                }
             }
-        }
 
-        tip_boxes.push(tip);
-    });
+            tip_boxes.push(tip);
+        });
+    } else {
+        for seen in map.memories(mouse_idx).iter() {
+            let mut tip = Tooltip::new();
+            tip.add(format!("You remember seeing {} here", seen.name));
+            tip_boxes.push(tip);
+        }
+    }
 
     if tip_boxes.is_empty() { return; }
 
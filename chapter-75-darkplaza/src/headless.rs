@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+use crate::{State, RunState, PlayerCommand, input, replay::{Replay, Playback}};
+
+/// Plays the game without a window. The player's commands come from a script rather than
+/// the keyboard, and nothing is drawn: no camera, no UI and no particles. Everything else
+/// (the systems, the menus, changing level) runs exactly as it does on screen.
+pub struct Headless {
+    pub state : State,
+    commands : VecDeque<PlayerCommand>
+}
+
+impl Headless {
+    /// Starts a new run, going straight past the main menu.
+    pub fn new(run_seed : u64) -> Headless {
+        let mut state = State::new(run_seed);
+        state.skip_main_menu();
+        Headless{ state, commands : VecDeque::new() }
+    }
+
+    /// Plays a recorded run through, and hands back the game as it ends up. Fails if the game
+    /// stops matching the recording.
+    pub fn replay(replay : Replay) -> Result<Headless, String> {
+        let mut game = Headless::new(replay.seed);
+        game.state.options = replay.options;
+        let mut playback = Playback::new(replay)?;
+        loop {
+            if input::wants_command(&game.runstate()) {
+                match playback.next_command(&game.state.ecs)? {
+                    Some(command) => game.state.step(Some(command)),
+                    None => return Ok(game)
+                }
+            } else {
+                game.state.step(None);
+            }
+        }
+    }
+
+    /// Adds commands to the end of the script.
+    pub fn queue<I : IntoIterator<Item = PlayerCommand>>(&mut self, commands : I) {
+        self.commands.extend(commands);
+    }
+
+    /// Steps the game until it's waiting on the player and the script has run out.
+    pub fn run(&mut self) {
+        loop {
+            if input::wants_command(&self.runstate()) {
+                match self.commands.pop_front() {
+                    Some(command) => self.state.step(Some(command)),
+                    None => return
+                }
+            } else {
+                self.state.step(None);
+            }
+        }
+    }
+
+    /// Queues the commands and plays them all.
+    pub fn play<I : IntoIterator<Item = PlayerCommand>>(&mut self, commands : I) {
+        self.queue(commands);
+        self.run();
+    }
+
+    pub fn runstate(&self) -> RunState {
+        (*self.state.ecs.fetch::<RunState>()).clone()
+    }
+}
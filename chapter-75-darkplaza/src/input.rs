@@ -0,0 +1,247 @@
+use rltk::{Rltk, VirtualKeyCode};
+use specs::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{RunState, camera};
+
+/// Something the player asked for. The window turns key presses and clicks into these, and
+/// the headless driver takes them from a script, so the game itself never looks at a
+/// keyboard.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PlayerCommand {
+    Move { dx : i32, dy : i32 },
+    Wait,
+    Shout,
+    /// Covers or uncovers the player's light.
+    ToggleLight,
+    Descend,
+    Ascend,
+    PickUp,
+    OpenInventory,
+    OpenDropMenu,
+    OpenRemoveMenu,
+    OpenCheatMenu,
+    CycleTarget,
+    Fire,
+    SaveAndQuit,
+    /// Uses the n-th consumable in the backpack, counting from zero.
+    UseConsumable(i32),
+    /// Casts the n-th known spell, counting from zero.
+    CastSpell(i32),
+    /// Picks the n-th option of whatever menu is open, counting from zero.
+    Select(i32),
+    /// Flips a vendor between buying and selling.
+    SwitchVendorMode,
+    /// Picks a map tile while targeting.
+    Target { x : i32, y : i32 },
+    Cancel,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    /// Types a letter into whatever name is being entered.
+    TypeChar(char),
+    /// Rubs out the last letter typed.
+    Backspace,
+    /// Puts another turn into the rock the player is digging through. The window sends it
+    /// for them while they're digging and no key is down.
+    KeepDigging
+}
+
+fn number_key(key : VirtualKeyCode) -> Option<i32> {
+    match key {
+        VirtualKeyCode::Key1 => Some(0),
+        VirtualKeyCode::Key2 => Some(1),
+        VirtualKeyCode::Key3 => Some(2),
+        VirtualKeyCode::Key4 => Some(3),
+        VirtualKeyCode::Key5 => Some(4),
+        VirtualKeyCode::Key6 => Some(5),
+        VirtualKeyCode::Key7 => Some(6),
+        VirtualKeyCode::Key8 => Some(7),
+        VirtualKeyCode::Key9 => Some(8),
+        _ => None
+    }
+}
+
+fn playing_command(ctx : &Rltk, key : VirtualKeyCode) -> Option<PlayerCommand> {
+    // Hotkeys
+    if ctx.shift {
+        if let Some(slot) = number_key(key) {
+            return Some(PlayerCommand::UseConsumable(slot));
+        }
+    }
+    if ctx.control {
+        if let Some(slot) = number_key(key) {
+            return Some(PlayerCommand::CastSpell(slot));
+        }
+    }
+
+    match key {
+        VirtualKeyCode::Left |
+        VirtualKeyCode::Numpad4 |
+        VirtualKeyCode::H => Some(PlayerCommand::Move{ dx: -1, dy: 0 }),
+
+        VirtualKeyCode::Right |
+        VirtualKeyCode::Numpad6 |
+        VirtualKeyCode::L => Some(PlayerCommand::Move{ dx: 1, dy: 0 }),
+
+        VirtualKeyCode::Up |
+        VirtualKeyCode::Numpad8 |
+        VirtualKeyCode::K => Some(PlayerCommand::Move{ dx: 0, dy: -1 }),
+
+        VirtualKeyCode::Down |
+        VirtualKeyCode::Numpad2 |
+        VirtualKeyCode::J => Some(PlayerCommand::Move{ dx: 0, dy: 1 }),
+
+        // Diagonals
+        VirtualKeyCode::Numpad9 |
+        VirtualKeyCode::U => Some(PlayerCommand::Move{ dx: 1, dy: -1 }),
+
+        VirtualKeyCode::Numpad7 |
+        VirtualKeyCode::Y => Some(PlayerCommand::Move{ dx: -1, dy: -1 }),
+
+        VirtualKeyCode::Numpad3 |
+        VirtualKeyCode::N => Some(PlayerCommand::Move{ dx: 1, dy: 1 }),
+
+        VirtualKeyCode::Numpad1 |
+        VirtualKeyCode::B => Some(PlayerCommand::Move{ dx: -1, dy: 1 }),
+
+        // Skip Turn
+        VirtualKeyCode::Numpad5 |
+        VirtualKeyCode::Space => Some(PlayerCommand::Wait),
+
+        VirtualKeyCode::S => Some(PlayerCommand::Shout),
+        VirtualKeyCode::T => Some(PlayerCommand::ToggleLight),
+        VirtualKeyCode::Period => Some(PlayerCommand::Descend),
+        VirtualKeyCode::Comma => Some(PlayerCommand::Ascend),
+        VirtualKeyCode::G => Some(PlayerCommand::PickUp),
+        VirtualKeyCode::I => Some(PlayerCommand::OpenInventory),
+        VirtualKeyCode::D => Some(PlayerCommand::OpenDropMenu),
+        VirtualKeyCode::R => Some(PlayerCommand::OpenRemoveMenu),
+        VirtualKeyCode::V => Some(PlayerCommand::CycleTarget),
+        VirtualKeyCode::F => Some(PlayerCommand::Fire),
+        VirtualKeyCode::Escape => Some(PlayerCommand::SaveAndQuit),
+        VirtualKeyCode::Backslash => Some(PlayerCommand::OpenCheatMenu),
+        _ => None
+    }
+}
+
+/// Lettered menus: `a` is the first option, `b` the second and so on.
+fn lettered_menu_command(key : VirtualKeyCode) -> Option<PlayerCommand> {
+    match key {
+        VirtualKeyCode::Escape => Some(PlayerCommand::Cancel),
+        _ => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 { Some(PlayerCommand::Select(selection)) } else { None }
+        }
+    }
+}
+
+/// The cheat menu's options, in the order `show_cheat_mode` lists them.
+fn cheat_menu_command(key : VirtualKeyCode) -> Option<PlayerCommand> {
+    match key {
+        VirtualKeyCode::T => Some(PlayerCommand::Select(0)),
+        VirtualKeyCode::H => Some(PlayerCommand::Select(1)),
+        VirtualKeyCode::R => Some(PlayerCommand::Select(2)),
+        VirtualKeyCode::G => Some(PlayerCommand::Select(3)),
+        VirtualKeyCode::Escape => Some(PlayerCommand::Cancel),
+        _ => None
+    }
+}
+
+fn main_menu_command(key : VirtualKeyCode) -> Option<PlayerCommand> {
+    match key {
+        VirtualKeyCode::Escape => Some(PlayerCommand::Cancel),
+        VirtualKeyCode::Up => Some(PlayerCommand::MenuUp),
+        VirtualKeyCode::Down => Some(PlayerCommand::MenuDown),
+        VirtualKeyCode::Return => Some(PlayerCommand::Confirm),
+        _ => None
+    }
+}
+
+/// Naming a save: letters, numbers and dashes type, and the arrows pick an existing save.
+fn save_menu_command(ctx : &Rltk, key : VirtualKeyCode) -> Option<PlayerCommand> {
+    match key {
+        VirtualKeyCode::Escape => Some(PlayerCommand::Cancel),
+        VirtualKeyCode::Return => Some(PlayerCommand::Confirm),
+        VirtualKeyCode::Back => Some(PlayerCommand::Backspace),
+        VirtualKeyCode::Up => Some(PlayerCommand::MenuUp),
+        VirtualKeyCode::Down => Some(PlayerCommand::MenuDown),
+        VirtualKeyCode::Minus => Some(PlayerCommand::TypeChar(if ctx.shift { '_' } else { '-' })),
+        VirtualKeyCode::Key0 => Some(PlayerCommand::TypeChar('0')),
+        _ => {
+            if let Some(n) = number_key(key) {
+                return Some(PlayerCommand::TypeChar(char::from(b'1' + n as u8)));
+            }
+            let letter = rltk::letter_to_option(key);
+            if letter < 0 { return None; }
+            let ch = char::from(b'a' + letter as u8);
+            Some(PlayerCommand::TypeChar(if ctx.shift { ch.to_ascii_uppercase() } else { ch }))
+        }
+    }
+}
+
+/// Clicking picks the tile under the mouse; nothing else does anything.
+fn targeting_command(ecs : &World, ctx : &Rltk) -> Option<PlayerCommand> {
+    if !ctx.left_click { return None; }
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs);
+    let mouse_pos = ctx.mouse_pos();
+    Some(PlayerCommand::Target{ x: mouse_pos.0 + min_x - 1, y: mouse_pos.1 + min_y - 1 })
+}
+
+/// Works out what the player asked for this frame, given what's on screen.
+pub fn read_command(ecs : &World, ctx : &Rltk) -> Option<PlayerCommand> {
+    let runstate = (*ecs.fetch::<RunState>()).clone();
+    if let RunState::ShowTargeting{ .. } = runstate {
+        return targeting_command(ecs, ctx);
+    }
+
+    // Digging carries on by itself until a key is pressed
+    if ctx.key.is_none() && runstate == RunState::AwaitingInput && crate::player::is_digging(ecs) {
+        return Some(PlayerCommand::KeepDigging);
+    }
+
+    let key = ctx.key?;
+    match runstate {
+        RunState::AwaitingInput => playing_command(ctx, key),
+        RunState::ShowInventory |
+        RunState::ShowDropItem |
+        RunState::ShowRemoveItem |
+        RunState::ShowRemoveCurse |
+        RunState::ShowIdentify |
+        RunState::ShowLoadMenu => lettered_menu_command(key),
+        RunState::ShowVendor{ .. } => {
+            if key == VirtualKeyCode::Space {
+                Some(PlayerCommand::SwitchVendorMode)
+            } else {
+                lettered_menu_command(key)
+            }
+        }
+        RunState::ShowCheatMenu => cheat_menu_command(key),
+        RunState::MainMenu{ .. } => main_menu_command(key),
+        RunState::ShowSaveMenu{ .. } => save_menu_command(ctx, key),
+        // Any key will do
+        RunState::GameOver |
+        RunState::LoadFailed{ .. } => Some(PlayerCommand::Confirm),
+        _ => None
+    }
+}
+
+/// Whether the game is waiting on the player in this state, rather than getting on with
+/// things by itself.
+pub fn wants_command(runstate : &RunState) -> bool {
+    matches!(runstate,
+        RunState::AwaitingInput |
+        RunState::ShowInventory |
+        RunState::ShowDropItem |
+        RunState::ShowRemoveItem |
+        RunState::ShowRemoveCurse |
+        RunState::ShowIdentify |
+        RunState::ShowTargeting{ .. } |
+        RunState::ShowVendor{ .. } |
+        RunState::ShowCheatMenu |
+        RunState::MainMenu{ .. } |
+        RunState::ShowLoadMenu |
+        RunState::ShowSaveMenu{ .. } |
+        RunState::GameOver |
+        RunState::LoadFailed{ .. }
+    )
+}
@@ -0,0 +1,702 @@
+extern crate serde;
+use serde::{Serialize, Deserialize};
+use rltk::{GameState, Rltk, Point, RandomNumberGenerator};
+use std::sync::Arc;
+use specs::prelude::*;
+use specs::saveload::SimpleMarkerAllocator;
+
+mod components;
+pub use components::*;
+mod map;
+pub use map::*;
+mod player;
+use player::*;
+mod rect;
+pub use rect::Rect;
+mod damage_system;
+mod gui;
+mod gamelog;
+mod spawner;
+pub mod saveload_system;
+pub mod random_table;
+pub mod rex_assets;
+pub mod map_builders;
+pub mod raws;
+mod gamesystem;
+pub use gamesystem::*;
+mod gameclock;
+pub use gameclock::GameClock;
+pub mod effects;
+#[macro_use]
+extern crate lazy_static;
+mod systems;
+pub mod rng;
+pub mod spatial;
+mod input;
+pub use input::PlayerCommand;
+mod headless;
+pub use headless::Headless;
+pub mod replay;
+pub mod registry;
+
+/// Writes a diagnostic from map generation or raw loading. Native builds send it to
+/// stderr, so it never ends up mixed into a map that `mapgen` prints to stdout.
+pub(crate) fn console_log<S : ToString>(message : S) {
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", message.to_string());
+    #[cfg(target_arch = "wasm32")]
+    rltk::console::log(message);
+}
+
+const SHOW_MAPGEN_VISUALIZER : bool = false;
+const SHOW_FPS : bool = true;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorMode { Buy, Sell }
+
+/// How the player chose to play, from the command line.
+#[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct GameOptions {
+    /// Save the run every time the player changes level.
+    pub autosave : bool,
+    /// A save can only be loaded once, and dying deletes it.
+    pub permadeath : bool
+}
+
+#[derive(PartialEq, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    Ticking,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting { range : i32, item : Entity},
+    MainMenu { menu_selection : gui::MainMenuSelection },
+    /// Asking which slot to save in; `name` is what's been typed so far.
+    ShowSaveMenu { name : String },
+    SaveGame { slot : String },
+    NextLevel,
+    PreviousLevel,
+    EnterBranch,
+    TownPortal,
+    ShowRemoveItem,
+    GameOver,
+    MagicMapReveal { row : i32 },
+    MapGeneration,
+    ShowCheatMenu,
+    ShowVendor { vendor: Entity, mode : VendorMode },
+    TeleportingToOtherLevel { x: i32, y: i32, level: LevelKey },
+    ShowRemoveCurse,
+    ShowIdentify,
+    ShowLoadMenu,
+    LoadFailed { reason : String }
+}
+
+pub struct State {
+    pub ecs: World,
+    mapgen_next_state : Option<RunState>,
+    mapgen_history : Vec<Map>,
+    mapgen_index : usize,
+    mapgen_timer : f32,
+    dispatcher : Box<dyn systems::UnifiedDispatcher + 'static>,
+    recorder : Option<replay::Recorder>,
+    playback : Option<replay::Playback>,
+    pub options : GameOptions
+}
+
+impl State {
+    fn run_systems(&mut self) {
+        self.dispatcher.run_now(&mut self.ecs);
+        self.ecs.maintain();
+        map::remember_visible_entities(&self.ecs);
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx : &mut Rltk) {
+        let runstate = (*self.ecs.fetch::<RunState>()).clone();
+
+        ctx.set_active_console(1);
+        ctx.cls();
+        ctx.set_active_console(0);
+        ctx.cls();
+        systems::particle_system::update_particles(&mut self.ecs, ctx.frame_time_ms);
+
+        match runstate {
+            RunState::MainMenu{..} => {}
+            RunState::GameOver{..} => {}
+            RunState::ShowLoadMenu => {}
+            RunState::LoadFailed{..} => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+
+        if runstate == RunState::MapGeneration && SHOW_MAPGEN_VISUALIZER {
+            self.show_mapgen_visualizer(ctx);
+        } else {
+            gui::draw_menu(&self.ecs, ctx);
+            let command = if self.playback.is_some() && input::wants_command(&runstate) {
+                self.replay_command()
+            } else {
+                input::read_command(&self.ecs, ctx)
+            };
+            self.step(command);
+        }
+
+        rltk::render_draw_buffer(ctx);
+        if SHOW_FPS {
+            ctx.print(1, 59, &format!("FPS: {}", ctx.fps));
+        }
+    }
+}
+
+impl State {
+    /// Plays back the map builder's snapshots, one every quarter of a second.
+    fn show_mapgen_visualizer(&mut self, ctx : &mut Rltk) {
+        ctx.cls();
+        if self.mapgen_index < self.mapgen_history.len() { camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx); }
+
+        self.mapgen_timer += ctx.frame_time_ms;
+        if self.mapgen_timer > 250.0 {
+            self.mapgen_timer = 0.0;
+            self.mapgen_index += 1;
+            if self.mapgen_index >= self.mapgen_history.len() {
+                let mut runwriter = self.ecs.write_resource::<RunState>();
+                *runwriter = self.mapgen_next_state.clone().unwrap();
+            }
+        }
+    }
+
+    /// Advances the game by one frame, acting on the player's command if there is one. This
+    /// is the whole game bar the drawing, so it runs the same with or without a window.
+    #[allow(clippy::cognitive_complexity)]
+    pub fn step(&mut self, command : Option<PlayerCommand>) {
+        let mut newrunstate = (*self.ecs.fetch::<RunState>()).clone();
+
+        if let (Some(command), Some(recorder)) = (command, self.recorder.as_mut()) {
+            // A replay starts from the beginning of the run, so what was picked in the main
+            // menu (which depends on whether there's a save lying around) stays out of it
+            if !matches!(newrunstate, RunState::MainMenu{ .. }) {
+                recorder.record(&self.ecs, command);
+            }
+        }
+
+        match newrunstate {
+            RunState::MapGeneration => {
+                newrunstate = self.mapgen_next_state.clone().unwrap();
+            }
+            RunState::PreRun => {
+                self.run_systems();
+                self.ecs.maintain();
+                newrunstate = RunState::AwaitingInput;
+            }
+            RunState::AwaitingInput => {
+                if let Some(command) = command {
+                    newrunstate = match player::keep_digging(&mut self.ecs, command) {
+                        Some(digging) => digging,
+                        None => player_input(self, command)
+                    };
+                }
+                if newrunstate != RunState::AwaitingInput {
+                    self.ecs.write_resource::<gamelog::GameEvents>().record("Turn", 1);
+                }
+            }
+            RunState::Ticking => {
+                systems::particle_system::clear_particles(&mut self.ecs);
+                let mut should_change_target = false;
+                while newrunstate == RunState::Ticking {
+                    self.run_systems();
+                    self.ecs.maintain();
+                    match *self.ecs.fetch::<RunState>() {
+                        RunState::AwaitingInput => {
+                            newrunstate = RunState::AwaitingInput;
+                            should_change_target = true;
+                        }
+                        RunState::MagicMapReveal{ .. } => newrunstate = RunState::MagicMapReveal{ row: 0 },
+                        RunState::TownPortal => newrunstate = RunState::TownPortal,
+                        RunState::TeleportingToOtherLevel{ x, y, ref level } => newrunstate = RunState::TeleportingToOtherLevel{ x, y, level: level.clone() },
+                        RunState::ShowRemoveCurse => newrunstate = RunState::ShowRemoveCurse,
+                        RunState::ShowIdentify => newrunstate = RunState::ShowIdentify,
+                        _ => newrunstate = RunState::Ticking
+                    }
+                }
+                if should_change_target {
+                    player::end_turn_targeting(&mut self.ecs);
+                }
+            }
+            RunState::ShowInventory => {
+                let result = gui::item_result_choice(&gui::inventory_items(&self.ecs), command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item_entity);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting{ range: is_item_ranged.range, item: item_entity };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item: item_entity, target: None }).expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                }
+            }
+            RunState::ShowCheatMenu => {
+                let result = gui::cheat_menu_choice(command);
+                match result {
+                    gui::CheatMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::CheatMenuResult::NoResponse => {}
+                    gui::CheatMenuResult::TeleportToExit => {
+                        let current = self.ecs.fetch::<Map>().key();
+                        self.goto_level(map::level_below(&current));
+                        self.mapgen_next_state = Some(RunState::PreRun);
+                        newrunstate = RunState::MapGeneration;
+                    }
+                    gui::CheatMenuResult::Heal => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let mut player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.hit_points.current = player_pools.hit_points.max;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::Reveal => {
+                        let mut map = self.ecs.fetch_mut::<Map>();
+                        for v in map.revealed_tiles.iter_mut() {
+                            *v = true;
+                        }
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::CheatMenuResult::GodMode => {
+                        let player = self.ecs.fetch::<Entity>();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let mut player_pools = pools.get_mut(*player).unwrap();
+                        player_pools.god_mode = true;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = gui::item_result_choice(&gui::inventory_items(&self.ecs), command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToDropItem{ item: item_entity }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let result = gui::item_result_choice(&gui::equipped_items(&self.ecs), command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem{ item: item_entity }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowRemoveCurse => {
+                let result = gui::item_result_choice(&gui::cursed_items(&self.ecs), command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        self.ecs.write_storage::<CursedItem>().remove(item_entity);
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowIdentify => {
+                let result = gui::item_result_choice(&gui::unidentified_items(&self.ecs), command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        if let Some(name) = self.ecs.read_storage::<Name>().get(item_entity) {
+                            let mut dm = self.ecs.fetch_mut::<MasterDungeonMap>();
+                            dm.identified_items.insert(name.name.clone());
+                        }
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowTargeting{range, item} => {
+                let result = gui::ranged_target_choice(&self.ecs, range, command);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        if self.ecs.read_storage::<SpellTemplate>().get(item).is_some() {
+                            let mut intent = self.ecs.write_storage::<WantsToCastSpell>();
+                            intent.insert(*self.ecs.fetch::<Entity>(), WantsToCastSpell{ spell: item, target: result.1 }).expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                            intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item, target: result.1 }).expect("Unable to insert intent");
+                            newrunstate = RunState::Ticking;
+                        }
+                    }
+                }
+            }
+            RunState::ShowVendor{vendor, mode} => {
+                use crate::raws::*;
+                let result = gui::vendor_choice(&self.ecs, vendor, mode, command);
+                match result.0 {
+                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::VendorResult::NoResponse => {}
+                    gui::VendorResult::Sell => {
+                        let price = self.ecs.read_storage::<Item>().get(result.1.unwrap()).unwrap().base_value * 0.8;
+                        self.ecs.write_storage::<Pools>().get_mut(*self.ecs.fetch::<Entity>()).unwrap().gold += price;
+                        self.ecs.delete_entity(result.1.unwrap()).expect("Unable to delete");
+                    }
+                    gui::VendorResult::Buy => {
+                        let tag = result.2.unwrap();
+                        let price = result.3.unwrap();
+                        let mut pools = self.ecs.write_storage::<Pools>();
+                        let player_entity = self.ecs.fetch::<Entity>();
+                        let mut identified = self.ecs.write_storage::<IdentifiedItem>();
+                        identified.insert(*player_entity, IdentifiedItem{ name : tag.clone() }).expect("Unable to insert");
+                        std::mem::drop(identified);
+                        let player_pools = pools.get_mut(*player_entity).unwrap();
+                        std::mem::drop(player_entity);
+                        if player_pools.gold >= price {
+                            player_pools.gold -= price;
+                            std::mem::drop(pools);
+                            let player_entity = *self.ecs.fetch::<Entity>();
+                            let raws = get_raws(&self.ecs);
+                            crate::raws::spawn_named_item(&raws, &mut self.ecs, &tag, SpawnType::Carried{ by: player_entity });
+                        }
+                    }
+                    gui::VendorResult::BuyMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Buy },
+                    gui::VendorResult::SellMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Sell }
+                }
+            }
+            RunState::MainMenu{ menu_selection } => {
+                let result = gui::main_menu_choice(&self.ecs, menu_selection, command);
+                match result {
+                    gui::MainMenuResult::NoSelection{ selected } => newrunstate = RunState::MainMenu{ menu_selection: selected },
+                    gui::MainMenuResult::Selected{ selected } => {
+                        match selected {
+                            gui::MainMenuSelection::NewGame => newrunstate = RunState::PreRun,
+                            gui::MainMenuSelection::LoadGame => newrunstate = RunState::ShowLoadMenu,
+                            gui::MainMenuSelection::Quit => {
+                                self.discard_recording();
+                                ::std::process::exit(0);
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::GameOver => {
+                let result = gui::game_over_choice(command);
+                match result {
+                    gui::GameOverResult::NoSelection => {}
+                    gui::GameOverResult::QuitToMenu => {
+                        if self.options.permadeath {
+                            // No going back to an autosave
+                            saveload_system::delete_save(&self.ecs, &saveload_system::run_slot(&self.ecs));
+                        }
+                        // The replay of this run ends on the command that got here, with
+                        // nothing more to check
+                        let recording = self.recorder.take().is_some();
+                        self.reset_world(recording);
+                        newrunstate = RunState::MapGeneration;
+                        self.mapgen_next_state = Some(RunState::MainMenu{ menu_selection: gui::MainMenuSelection::NewGame });
+                    }
+                }
+            }
+            RunState::ShowLoadMenu => {
+                match gui::load_menu_choice(&self.ecs, command) {
+                    gui::LoadMenuResult::NoResponse => {}
+                    gui::LoadMenuResult::Cancel => newrunstate = RunState::MainMenu{ menu_selection: gui::MainMenuSelection::LoadGame },
+                    gui::LoadMenuResult::Selected{ slot } => {
+                        match saveload_system::load_game(&mut self.ecs, &slot) {
+                            Ok(()) => {
+                                // The replay can't follow the game into a save file
+                                self.discard_recording();
+                                newrunstate = RunState::AwaitingInput;
+                                if self.options.permadeath {
+                                    saveload_system::delete_save(&self.ecs, &slot);
+                                }
+                            }
+                            Err(reason) => {
+                                // Whatever got loaded before it went wrong can't be trusted,
+                                // so start over with a fresh world. Nothing has been
+                                // recorded yet, so its replay can go too.
+                                rltk::console::log(&reason);
+                                let recording = self.recorder.is_some();
+                                self.discard_recording();
+                                self.reset_world(recording);
+                                newrunstate = RunState::LoadFailed{ reason };
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::LoadFailed{ .. } => {
+                if command.is_some() {
+                    newrunstate = RunState::MainMenu{ menu_selection: gui::MainMenuSelection::NewGame };
+                }
+            }
+            RunState::ShowSaveMenu{ ref name } => {
+                match gui::save_menu_choice(&self.ecs, name, command) {
+                    gui::SaveMenuResult::NoResponse => {}
+                    gui::SaveMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::SaveMenuResult::Editing{ name } => newrunstate = RunState::ShowSaveMenu{ name },
+                    gui::SaveMenuResult::Selected{ slot } => newrunstate = RunState::SaveGame{ slot }
+                }
+            }
+            RunState::SaveGame{ slot } => {
+                self.stop_recording();
+                saveload_system::save_game(&self.ecs, &slot);
+                self.find_saves();
+                newrunstate = RunState::MainMenu{ menu_selection : gui::MainMenuSelection::LoadGame };
+            }
+            RunState::NextLevel => {
+                let current = self.ecs.fetch::<Map>().key();
+                self.goto_level(map::level_below(&current));
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::PreviousLevel => {
+                let current = self.ecs.fetch::<Map>().key();
+                self.goto_level(map::level_above(&current, &raws::get_raws(&self.ecs)));
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::EnterBranch => {
+                let destination = {
+                    let map = self.ecs.fetch::<Map>();
+                    let player_pos = self.ecs.fetch::<rltk::Point>();
+                    map::branch_below(&map, map.xy_idx(player_pos.x, player_pos.y), &raws::get_raws(&self.ecs))
+                };
+                if let Some(destination) = destination {
+                    self.goto_level(destination);
+                    self.mapgen_next_state = Some(RunState::PreRun);
+                    newrunstate = RunState::MapGeneration;
+                } else {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::TownPortal => {
+                // Spawn the portal
+                spawner::spawn_town_portal(&mut self.ecs);
+
+                // Transition
+                self.goto_level(LevelKey::main(1));
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::TeleportingToOtherLevel{x, y, level} => {
+                self.goto_level(level);
+                let player_entity = self.ecs.fetch::<Entity>();
+                if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(*player_entity) {
+                    pos.x = x;
+                    pos.y = y;
+                }
+                let mut ppos = self.ecs.fetch_mut::<rltk::Point>();
+                ppos.x = x;
+                ppos.y = y;
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
+            RunState::MagicMapReveal{row} => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x as i32,row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row == map.height-1 {
+                    newrunstate = RunState::Ticking;
+                } else {
+                    newrunstate = RunState::MagicMapReveal{ row: row+1 };
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+        damage_system::delete_the_dead(&mut self.ecs);
+    }
+}
+
+impl State {
+    fn goto_level(&mut self, destination: LevelKey) {
+        freeze_level_entities(&mut self.ecs);
+
+        // Keep what the player has seen of this level for when they come back
+        {
+            let map = self.ecs.fetch::<Map>();
+            self.ecs.fetch_mut::<map::MasterDungeonMap>().store_map(&*map);
+        }
+
+        // Build a new map and place the player
+        let current = self.ecs.fetch::<Map>().key();
+        self.generate_world_map(&destination, &current);
+
+        // Notify the player
+        gamelog::Logger::new().append("You change level.").log(&mut self.ecs.write_resource::<gamelog::GameLog>());
+
+        // Watching a replay shouldn't write over anybody's save
+        if self.options.autosave && self.playback.is_none() {
+            let slot = saveload_system::run_slot(&self.ecs);
+            saveload_system::save_game(&self.ecs, &slot);
+        }
+    }
+
+    /// Throws the world away for a brand new one, so that the next run plays out just as it
+    /// would have if the game had been started with its seed.
+    fn reset_world(&mut self, record : bool) {
+        let run_seed = rng::random_seed();
+        let options = self.options;
+        let save_dir = (*self.ecs.fetch::<saveload_system::SaveDir>()).clone();
+        *self = State::new(run_seed);
+        self.options = options;
+        self.ecs.insert(save_dir);
+        self.find_saves();
+        if record {
+            self.start_recording(&replay::replay_path(run_seed));
+        }
+    }
+
+    fn generate_world_map(&mut self, level : &LevelKey, from : &LevelKey) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_history.clear();
+        let map_building_info = map::level_transition(&mut self.ecs, level, from);
+        if let Some(history) = map_building_info {
+            self.mapgen_history = history;
+        } else {
+            map::thaw_level_entities(&mut self.ecs);
+        }
+
+        let mut log = self.ecs.write_resource::<gamelog::GameLog>();
+        log.clear();
+        gamelog::Logger::new()
+            .append("Welcome to")
+            .color(rltk::CYAN)
+            .append("Rusty Roguelike")
+            .log(&mut log);
+
+        self.ecs.write_resource::<gamelog::GameEvents>().clear();
+    }
+}
+
+impl State {
+    /// Reads the list of save slots again, after something may have changed them. The main
+    /// and load menus show the list as it was last read.
+    pub fn find_saves(&mut self) {
+        let slots = saveload_system::list_saves(&self.ecs);
+        self.ecs.insert(slots);
+    }
+
+    /// Records everything the player does from here on, along with the run's seed.
+    pub fn start_recording(&mut self, path : &str) {
+        let run_seed = self.ecs.fetch::<MasterDungeonMap>().seed;
+        match replay::Recorder::start(path, run_seed, raws::raws_hash(), self.options) {
+            Ok(recorder) => self.recorder = Some(recorder),
+            Err(e) => rltk::console::log(format!("Unable to record a replay to {}: {}", path, e))
+        }
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            recorder.finish(&self.ecs);
+        }
+    }
+
+    fn discard_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            recorder.discard();
+        }
+    }
+
+    /// Plays a recorded run back in place of the keyboard. The world needs to be fresh from
+    /// `State::new`, with the replay's seed.
+    pub fn play_replay(&mut self, replay : replay::Replay) -> Result<(), String> {
+        self.options = replay.options;
+        self.playback = Some(replay::Playback::new(replay)?);
+        self.skip_main_menu();
+        Ok(())
+    }
+
+    /// Goes straight into the run once the first level is built, instead of to the main menu.
+    fn skip_main_menu(&mut self) {
+        self.mapgen_next_state = Some(RunState::PreRun);
+    }
+
+    /// The replay's next command. When it runs out, or the game stops matching it, the
+    /// player is told and the keyboard takes over.
+    fn replay_command(&mut self) -> Option<PlayerCommand> {
+        let playback = self.playback.as_mut()?;
+        let message = match playback.next_command(&self.ecs) {
+            Ok(Some(command)) => return Some(command),
+            Ok(None) => "The replay is over.".to_string(),
+            Err(e) => e
+        };
+        self.playback = None;
+        rltk::console::log(&message);
+        gamelog::Logger::new().append(message).log(&mut self.ecs.write_resource::<gamelog::GameLog>());
+        None
+    }
+}
+
+impl State {
+    /// Builds a fresh world with every component registered, the raws loaded and the
+    /// first level of the run generated.
+    pub fn new(run_seed : u64) -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            mapgen_next_state : Some(RunState::MainMenu{ menu_selection: gui::MainMenuSelection::NewGame }),
+            mapgen_index : 0,
+            mapgen_history: Vec::new(),
+            mapgen_timer: 0.0,
+            dispatcher: systems::build(),
+            recorder : None,
+            playback : None,
+            options : GameOptions::default()
+        };
+        registry::register_components(&mut gs.ecs);
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+        let raws = Arc::new(raws::load_raws());
+        gs.ecs.insert(raws.clone());
+        gs.ecs.insert(RandomNumberGenerator::seeded(run_seed));
+        gs.ecs.insert(rng::VisualRng::new(run_seed));
+        gs.ecs.insert(effects::EffectQueue::default());
+        gs.ecs.insert(gamelog::GameLog::default());
+        gs.ecs.insert(gamelog::GameEvents::default());
+
+        gs.ecs.insert(map::MasterDungeonMap::new(run_seed, &raws));
+        gs.ecs.insert(GameClock::default());
+        gs.ecs.insert(Map::new(1, 64, 64, "New Map", &raws.tiles));
+        gs.ecs.insert(map::FlowFields::default());
+        gs.ecs.insert(Point::new(0, 0));
+        let player_entity = spawner::player(&mut gs.ecs, 0, 0);
+        gs.ecs.insert(player_entity);
+        gs.ecs.insert(RunState::MapGeneration{} );
+        gs.ecs.insert(systems::particle_system::ParticleBuilder::new());
+        gs.ecs.insert(rex_assets::RexAssets::new());
+        // No saves are listed until `find_saves` goes looking
+        gs.ecs.insert(saveload_system::SaveDir::default());
+        gs.ecs.insert(Vec::<saveload_system::SaveSlot>::new());
+
+        gs.generate_world_map(&LevelKey::main(1), &LevelKey::main(1));
+
+        gs
+    }
+}
@@ -1,30 +1,44 @@
 use specs::prelude::*;
-use crate::{Map,Position,Renderable,Hidden,TileSize,Target};
+use crate::{Map,Position,Renderable,Hidden,TileSize,Target,Darkvision};
 use rltk::prelude::*;
 use crate::map::tile_glyph;
 
-pub fn get_screen_bounds(ecs: &World, _ctx:  &mut Rltk) -> (i32, i32, i32, i32) {
-    let player_pos = ecs.fetch::<Point>();
-    //let (x_chars, y_chars) = ctx.get_char_size();
-    let (x_chars, y_chars) = (48, 44);
+/// The part of the screen given over to the map, inside the HUD frame.
+pub const VIEWPORT_WIDTH : i32 = 48;
+pub const VIEWPORT_HEIGHT : i32 = 44;
 
-    let center_x = (x_chars / 2) as i32;
-    let center_y = (y_chars / 2) as i32;
+/// The range of map tiles on screen, as `(min_x, max_x, min_y, max_y)`. The camera follows
+/// the player but stops at the map edges, and maps smaller than the viewport are centred.
+pub fn get_screen_bounds(ecs: &World) -> (i32, i32, i32, i32) {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
 
-    let min_x = player_pos.x - center_x;
-    let max_x = min_x + x_chars as i32;
-    let min_y = player_pos.y - center_y;
-    let max_y = min_y + y_chars as i32;
+    let min_x = camera_origin(player_pos.x, map.width, VIEWPORT_WIDTH);
+    let max_x = min_x + VIEWPORT_WIDTH;
+    let min_y = camera_origin(player_pos.y, map.height, VIEWPORT_HEIGHT);
+    let max_y = min_y + VIEWPORT_HEIGHT;
 
     (min_x, max_x, min_y, max_y)
 }
 
+fn camera_origin(player : i32, map_size : i32, view_size : i32) -> i32 {
+    if map_size <= view_size {
+        (map_size - view_size) / 2
+    } else {
+        i32::max(0, i32::min(player - view_size / 2, map_size - view_size))
+    }
+}
+
+fn on_screen(screen_x : i32, screen_y : i32) -> bool {
+    screen_x >= 0 && screen_x < VIEWPORT_WIDTH && screen_y >= 0 && screen_y < VIEWPORT_HEIGHT
+}
+
 const SHOW_BOUNDARIES : bool = false;
 
-pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
+pub fn render_camera(ecs: &World, _ctx : &mut Rltk) {
     let mut draw_batch = DrawBatch::new();
     let map = ecs.fetch::<Map>();
-    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs, ctx);
+    let (min_x, max_x, min_y, max_y) = get_screen_bounds(ecs);
 
     // Render the Map
 
@@ -42,6 +56,15 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
                         ColorPair::new(fg, bg),
                         glyph
                     );
+
+                    // Things the player saw here, greyed out like the tile they're on
+                    if let Some(seen) = map.memories(idx).first() {
+                        draw_batch.set(
+                            Point::new(x+1, y+1),
+                            ColorPair::new(seen.fg.to_greyscale(), bg),
+                            seen.glyph
+                        );
+                    }
                 }
             } else if SHOW_BOUNDARIES {
                 draw_batch.set(
@@ -61,6 +84,9 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
     let sizes = ecs.read_storage::<TileSize>();
     let entities = ecs.entities();
     let targets = ecs.read_storage::<Target>();
+    let player_pos = *ecs.fetch::<Point>();
+    let darkvision = ecs.read_storage::<Darkvision>();
+    let player_darkvision = darkvision.get(*ecs.fetch::<Entity>());
 
     let mut data = (&positions, &renderables, &entities, !&hidden).join().collect::<Vec<_>>();
     data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order) );
@@ -70,11 +96,12 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
                 for cx in 0 .. size.x {
                     let tile_x = cx + pos.x;
                     let tile_y = cy + pos.y;
+                    if tile_x < 0 || tile_x >= map.width || tile_y < 0 || tile_y >= map.height { continue; }
                     let idx = map.xy_idx(tile_x, tile_y);
-                    if map.visible_tiles[idx] {
+                    if map.visible_tiles[idx] && map.can_make_out(player_pos, player_darkvision, idx) {
                         let entity_screen_x = (cx + pos.x) - min_x;
                         let entity_screen_y = (cy + pos.y) - min_y;
-                        if entity_screen_x > 0 && entity_screen_x < map_width && entity_screen_y > 0 && entity_screen_y < map_height {
+                        if on_screen(entity_screen_x, entity_screen_y) {
                             draw_batch.set(
                                 Point::new(entity_screen_x + 1, entity_screen_y + 1),
                                 ColorPair::new(render.fg, render.bg),
@@ -86,10 +113,10 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
             }
         } else {
             let idx = map.xy_idx(pos.x, pos.y);
-            if map.visible_tiles[idx] {
+            if map.visible_tiles[idx] && map.can_make_out(player_pos, player_darkvision, idx) {
                 let entity_screen_x = pos.x - min_x;
                 let entity_screen_y = pos.y - min_y;
-                if entity_screen_x > 0 && entity_screen_x < map_width && entity_screen_y > 0 && entity_screen_y < map_height {
+                if on_screen(entity_screen_x, entity_screen_y) {
                     draw_batch.set(
                         Point::new(entity_screen_x + 1, entity_screen_y + 1),
                         ColorPair::new(render.fg, render.bg),
@@ -99,9 +126,9 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
             }
         }
 
-        if targets.get(*entity).is_some() {
-            let entity_screen_x = pos.x - min_x;
-            let entity_screen_y = pos.y - min_y;
+        let entity_screen_x = pos.x - min_x;
+        let entity_screen_y = pos.y - min_y;
+        if targets.get(*entity).is_some() && on_screen(entity_screen_x, entity_screen_y) {
             draw_batch.set(
                 Point::new(entity_screen_x , entity_screen_y + 1),
                 ColorPair::new(RGB::named(rltk::RED), RGB::named(rltk::YELLOW)),
@@ -118,31 +145,34 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
     draw_batch.submit(0);
 }
 
+/// Draws a whole map for the mapgen visualizer. Maps too big for the screen are scaled
+/// down, one character per block of tiles, so the full layout is always visible.
 pub fn render_debug_map(map : &Map, ctx : &mut Rltk) {
-    let player_pos = Point::new(map.width / 2, map.height / 2);
     let (x_chars, y_chars) = ctx.get_char_size();
+    let (x_chars, y_chars) = (x_chars as i32, y_chars as i32);
 
-    let center_x = (x_chars / 2) as i32;
-    let center_y = (y_chars / 2) as i32;
-
-    let min_x = player_pos.x - center_x;
-    let max_x = min_x + x_chars as i32;
-    let min_y = player_pos.y - center_y;
-    let max_y = min_y + y_chars as i32;
+    let scale = i32::max(1, i32::max(
+        (map.width + x_chars - 1) / x_chars,
+        (map.height + y_chars - 1) / y_chars
+    ));
+    let offset_x = (x_chars - map.width / scale) / 2;
+    let offset_y = (y_chars - map.height / scale) / 2;
 
     let map_width = map.width-1;
     let map_height = map.height-1;
 
-    for (y,ty) in (min_y .. max_y).enumerate() {
-        for (x,tx) in (min_x .. max_x).enumerate() {
+    for y in 0 .. y_chars {
+        for x in 0 .. x_chars {
+            let tx = (x - offset_x) * scale;
+            let ty = (y - offset_y) * scale;
             if tx > 0 && tx < map_width && ty > 0 && ty < map_height {
                 let idx = map.xy_idx(tx, ty);
                 if map.revealed_tiles[idx] {
                     let (glyph, fg, bg) = tile_glyph(idx, &*map);
-                    ctx.set(x as i32, y as i32, fg, bg, glyph);
+                    ctx.set(x, y, fg, bg, glyph);
                 }
             } else if SHOW_BOUNDARIES {
-                ctx.set(x as i32, y as i32, RGB::named(rltk::GRAY), RGB::named(rltk::BLACK), rltk::to_cp437('·'));
+                ctx.set(x, y, RGB::named(rltk::GRAY), RGB::named(rltk::BLACK), rltk::to_cp437('·'));
             }
         }
     }
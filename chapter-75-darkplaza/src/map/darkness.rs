@@ -0,0 +1,24 @@
+use super::Map;
+use rltk::Point;
+use crate::components::Darkvision;
+
+/// Tiles lit more dimly than this (on average across red, green and blue) are dark enough
+/// to hide in.
+const DARKNESS_THRESHOLD : f32 = 0.1;
+
+impl Map {
+    /// Whether there's enough light on a tile to see what's standing there.
+    pub fn is_lit(&self, idx : usize) -> bool {
+        let light = self.light[idx];
+        (light.r + light.g + light.b) / 3.0 >= DARKNESS_THRESHOLD
+    }
+
+    /// Whether someone at `from`, who can see tile `idx`, can make out what's on it. Things
+    /// in the dark can only be seen right next to you, or with darkvision.
+    pub fn can_make_out(&self, from : Point, darkvision : Option<&Darkvision>, idx : usize) -> bool {
+        if self.is_lit(idx) { return true; }
+        let target = Point::new(idx as i32 % self.width, idx as i32 / self.width);
+        let distance = rltk::DistanceAlg::Pythagoras.distance2d(from, target);
+        distance < 1.5 || darkvision.map_or(false, |dv| distance <= dv.range as f32)
+    }
+}
@@ -1,36 +1,46 @@
 use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
-use super::{Map, TileType};
+use super::{Map, TileType, TileSet, LevelKey};
+use std::sync::Arc;
 use crate::components::{Position, Viewshed, OtherLevelPosition};
-use crate::map_builders::level_builder;
+use crate::map_builders::{build_level, DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT};
+use crate::raws::RawMaster;
 use specs::prelude::*;
-use rltk::Point;
+use rltk::{Point, RandomNumberGenerator};
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct MasterDungeonMap {
     maps : HashMap<i32, Map>,
+    #[serde(default)]
+    branch_maps : HashMap<String, HashMap<i32, Map>>,
+    pub seed : u64,
     pub identified_items : HashSet<String>,
     pub scroll_mappings : HashMap<String, String>,
     pub potion_mappings : HashMap<String, String>
 }
 
 impl MasterDungeonMap {
-    pub fn new() -> MasterDungeonMap {
+    /// Starts a new run. Item name obfuscation and every generated level are worked out
+    /// from `seed`, so they're reproducible from it.
+    pub fn new(seed : u64, raws : &RawMaster) -> MasterDungeonMap {
+        let mut rng = RandomNumberGenerator::seeded(seed);
         let mut dm = MasterDungeonMap{
             maps: HashMap::new() ,
+            branch_maps: HashMap::new(),
+            seed,
             identified_items : HashSet::new(),
             scroll_mappings : HashMap::new(),
             potion_mappings : HashMap::new()
         };
 
-        for scroll_tag in crate::raws::get_scroll_tags().iter() {
-            let masked_name = make_scroll_name();
+        for scroll_tag in crate::raws::get_scroll_tags(raws).iter() {
+            let masked_name = make_scroll_name(&mut rng);
             dm.scroll_mappings.insert(scroll_tag.to_string(), masked_name);
         }
 
         let mut used_potion_names : HashSet<String> = HashSet::new();
-        for potion_tag in crate::raws::get_potion_tags().iter() {
-            let masked_name = make_potion_name(&mut used_potion_names);
+        for potion_tag in crate::raws::get_potion_tags(raws).iter() {
+            let masked_name = make_potion_name(&mut rng, &mut used_potion_names);
             dm.potion_mappings.insert(potion_tag.to_string(), masked_name);
         }
 
@@ -38,26 +48,63 @@ impl MasterDungeonMap {
     }
 
     pub fn store_map(&mut self, map : &Map) {
-        self.maps.insert(map.depth, map.clone());
+        if map.key().is_main() {
+            self.maps.insert(map.depth, map.clone());
+        } else {
+            self.branch_maps.entry(map.branch.clone()).or_insert_with(HashMap::new).insert(map.depth, map.clone());
+        }
     }
 
-    pub fn get_map(&self, depth : i32) -> Option<Map> {
-        if self.maps.contains_key(&depth) {
-            let mut result = self.maps[&depth].clone();
-            Some(result)
+    pub fn get_map(&self, level : &LevelKey) -> Option<Map> {
+        if level.is_main() {
+            self.maps.get(&level.depth).cloned()
         } else {
-            None
+            self.branch_maps.get(&level.branch).and_then(|maps| maps.get(&level.depth)).cloned()
+        }
+    }
+
+    /// Gives every level read from a save the tile set it's played with.
+    pub fn attach_tiles(&mut self, tile_set : &Arc<TileSet>) -> Result<(), String> {
+        let branch_maps = self.branch_maps.values_mut().flat_map(|maps| maps.values_mut());
+        for map in self.maps.values_mut().chain(branch_maps) {
+            map.attach_tiles(tile_set)?;
+        }
+        Ok(())
+    }
+}
+
+/// The level reached by going up from `current`. The top of a branch leads back to the
+/// level holding its entrance.
+pub fn level_above(current : &LevelKey, raws : &RawMaster) -> LevelKey {
+    if !current.is_main() {
+        let branch = crate::raws::get_branch(raws, &current.branch);
+        if let Some(branch) = branch {
+            if current.depth <= branch.first_depth {
+                return LevelKey::new(&branch.parent, branch.entrance_depth);
+            }
         }
     }
+    LevelKey::new(&current.branch, current.depth - 1)
+}
+
+pub fn level_below(current : &LevelKey) -> LevelKey {
+    LevelKey::new(&current.branch, current.depth + 1)
 }
 
-fn make_scroll_name() -> String {
-    let length = 4 + crate::rng::roll_dice(1, 4);
+/// The first level of the branch whose entrance is at `idx`, if there is one.
+pub fn branch_below(map : &Map, idx : usize, raws : &RawMaster) -> Option<LevelKey> {
+    let name = map.branch_entrances.get(&idx)?;
+    let branch = crate::raws::get_branch(raws, name)?;
+    Some(LevelKey::new(&branch.name, branch.first_depth))
+}
+
+fn make_scroll_name(rng : &mut RandomNumberGenerator) -> String {
+    let length = 4 + rng.roll_dice(1, 4);
     let mut name = "Scroll of ".to_string();
 
     for i in 0..length {
         if i % 2 == 0 {
-            name += match crate::rng::roll_dice(1, 5) {
+            name += match rng.roll_dice(1, 5) {
                 1 => "a",
                 2 => "e",
                 3 => "i",
@@ -65,7 +112,7 @@ fn make_scroll_name() -> String {
                 _ => "u"
             }
         } else {
-            name += match crate::rng::roll_dice(1, 21) {
+            name += match rng.roll_dice(1, 21) {
                 1 => "b",
                 2 => "c",
                 3 => "d",
@@ -97,11 +144,11 @@ fn make_scroll_name() -> String {
 const POTION_COLORS: &[&str] = &["Red", "Orange", "Yellow", "Green", "Brown", "Indigo", "Violet"];
 const POTION_ADJECTIVES : &[&str] = &["Swirling", "Effervescent", "Slimey", "Oiley", "Viscous", "Smelly", "Glowing"];
 
-fn make_potion_name(used_names : &mut HashSet<String>) -> String {
+fn make_potion_name(rng : &mut RandomNumberGenerator, used_names : &mut HashSet<String>) -> String {
     loop {
-        let mut name : String = POTION_ADJECTIVES[crate::rng::roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize -1].to_string();
+        let mut name : String = POTION_ADJECTIVES[rng.roll_dice(1, POTION_ADJECTIVES.len() as i32) as usize -1].to_string();
         name += " ";
-        name += POTION_COLORS[crate::rng::roll_dice(1, POTION_COLORS.len() as i32) as usize -1];
+        name += POTION_COLORS[rng.roll_dice(1, POTION_COLORS.len() as i32) as usize -1];
         name += " Potion";
 
         if !used_names.contains(&name) {
@@ -111,15 +158,10 @@ fn make_potion_name(used_names : &mut HashSet<String>) -> String {
     }
 }
 
-fn transition_to_new_map(ecs : &mut World, new_depth: i32) -> Vec<Map> {
-    let mut builder = level_builder(new_depth, 80, 50);
-    builder.build_map();
-    if new_depth > 1 {
-        if let Some(pos) = &builder.build_data.starting_position {
-            let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
-            builder.build_data.map.tiles[up_idx] = TileType::UpStairs;
-        }
-    }
+fn transition_to_new_map(ecs : &mut World, level: &LevelKey) -> Vec<Map> {
+    let run_seed = ecs.fetch::<MasterDungeonMap>().seed;
+    let raws = crate::raws::get_raws(ecs);
+    let mut builder = build_level(&raws, run_seed, level, DEFAULT_MAP_WIDTH, DEFAULT_MAP_HEIGHT);
     let mapgen_history = builder.build_data.history.clone();
     let player_start;
     {
@@ -128,8 +170,10 @@ fn transition_to_new_map(ecs : &mut World, new_depth: i32) -> Vec<Map> {
         player_start = builder.build_data.starting_position.as_mut().unwrap().clone();
     }
 
-    // Spawn bad guys
+    // Spawn bad guys, then whatever is about at this hour
     builder.spawn_entities(ecs);
+    let start_idx = builder.build_data.map.xy_idx(player_start.x, player_start.y);
+    crate::spawner::spawn_for_time_of_day(ecs, &builder.build_data.spawn_list, start_idx);
 
     // Place the player and update resources
     let (player_x, player_y) = (player_start.x, player_start.y);
@@ -157,31 +201,36 @@ fn transition_to_new_map(ecs : &mut World, new_depth: i32) -> Vec<Map> {
     mapgen_history
 }
 
-fn transition_to_existing_map(ecs: &mut World, new_depth: i32, offset: i32) {
+fn transition_to_existing_map(ecs: &mut World, level: &LevelKey, from: &LevelKey) {
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
-    let map = dungeon_master.get_map(new_depth).unwrap();
+    let mut map = dungeon_master.get_map(level).unwrap();
     let mut worldmap_resource = ecs.write_resource::<Map>();
     let player_entity = ecs.fetch::<Entity>();
 
-    // Find the down stairs and place the player
+    // Coming back out of a branch puts you on its entrance; otherwise you arrive on the
+    // stairs leading back the way you came.
     let w = map.width;
-    let stair_type = if offset < 0 { TileType::DownStairs } else { TileType::UpStairs };
-    for (idx, tt) in map.tiles.iter().enumerate() {
-        if *tt == stair_type {
-            let mut player_position = ecs.write_resource::<Point>();
-            *player_position = Point::new(idx as i32 % w, idx as i32 / w);
-            let mut position_components = ecs.write_storage::<Position>();
-            let player_pos_comp = position_components.get_mut(*player_entity);
-            if let Some(player_pos_comp) = player_pos_comp {
-                player_pos_comp.x = idx as i32 % w;
-                player_pos_comp.y = idx as i32 / w;
-                if new_depth == 1 {
-                    player_pos_comp.x -= 1;
-                }
+    let entrance = map.branch_entrances.iter()
+        .find(|(_, branch)| **branch == from.branch && from.branch != level.branch)
+        .map(|(idx, _)| *idx);
+    let stair_type = if from.depth > level.depth { TileType::DownStairs } else { TileType::UpStairs };
+    let arrival = entrance.or_else(|| map.tiles.iter().position(|tt| *tt == stair_type));
+    if let Some(idx) = arrival {
+        let mut player_position = ecs.write_resource::<Point>();
+        *player_position = Point::new(idx as i32 % w, idx as i32 / w);
+        let mut position_components = ecs.write_storage::<Position>();
+        let player_pos_comp = position_components.get_mut(*player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = idx as i32 % w;
+            player_pos_comp.y = idx as i32 / w;
+            if level.is_main() && level.depth == 1 {
+                player_pos_comp.x -= 1;
             }
         }
     }
 
+    // Levels can differ in size, so the spatial index has to follow
+    map.spatial.set_size(map.tiles.len());
     *worldmap_resource = map;
 
     // Mark the player's visibility as dirty
@@ -198,13 +247,13 @@ pub fn freeze_level_entities(ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_key = ecs.fetch::<Map>().key();
 
     // Find positions and make OtherLevelPosition
     let mut pos_to_delete : Vec<Entity> = Vec::new();
     for (entity, pos) in (&entities, &positions).join() {
         if entity != *player_entity {
-            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, depth: map_depth }).expect("Insert fail");
+            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, level: map_key.clone() }).expect("Insert fail");
             pos_to_delete.push(entity);
         }
     }
@@ -221,12 +270,12 @@ pub fn thaw_level_entities(ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
     let player_entity = ecs.fetch::<Entity>();
-    let map_depth = ecs.fetch::<Map>().depth;
+    let map_key = ecs.fetch::<Map>().key();
 
     // Find OtherLevelPosition
     let mut pos_to_delete : Vec<Entity> = Vec::new();
     for (entity, pos) in (&entities, &other_level_positions).join() {
-        if entity != *player_entity && pos.depth == map_depth {
+        if entity != *player_entity && pos.level == map_key {
             positions.insert(entity, Position{ x: pos.x, y: pos.y }).expect("Insert fail");
             pos_to_delete.push(entity);
         }
@@ -238,17 +287,17 @@ pub fn thaw_level_entities(ecs: &mut World) {
     }
 }
 
-pub fn level_transition(ecs : &mut World, new_depth: i32, offset: i32) -> Option<Vec<Map>> {
+pub fn level_transition(ecs : &mut World, level: &LevelKey, from: &LevelKey) -> Option<Vec<Map>> {
     // Obtain the master dungeon map
     let dungeon_master = ecs.read_resource::<MasterDungeonMap>();
 
     // Do we already have a map?
-    if dungeon_master.get_map(new_depth).is_some() {
+    if dungeon_master.get_map(level).is_some() {
         std::mem::drop(dungeon_master);
-        transition_to_existing_map(ecs, new_depth, offset);
+        transition_to_existing_map(ecs, level, from);
         None
     } else {
         std::mem::drop(dungeon_master);
-        Some(transition_to_new_map(ecs, new_depth))
+        Some(transition_to_new_map(ecs, level))
     }
 }
@@ -0,0 +1,113 @@
+use rltk::{ BaseMap, Algorithm2D, Point, DijkstraMap };
+use super::{Map, LevelKey};
+
+/// A view of the map that only cares about the terrain. Creatures come and go every turn,
+/// so the shared fields ignore them and monsters step around each other as they move.
+struct TerrainMap<'a> {
+    map : &'a Map
+}
+
+impl<'a> BaseMap for TerrainMap<'a> {
+    fn is_opaque(&self, idx:usize) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    fn get_available_exits(&self, idx:usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        self.map.exits_where(idx, |exit| self.map.walkable(exit))
+    }
+
+    fn get_pathing_distance(&self, idx1:usize, idx2:usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+impl<'a> Algorithm2D for TerrainMap<'a> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
+    }
+}
+
+/// A Dijkstra map, and the tiles it was built outward from.
+struct Field {
+    sources : Vec<usize>,
+    map : DijkstraMap
+}
+
+impl Field {
+    fn build(map : &Map, mut sources : Vec<usize>) -> Field {
+        sources.sort_unstable();
+        sources.dedup();
+        let depth = f32::max(3000.0, (map.width * map.height) as f32 * 2.0);
+        let dijkstra = DijkstraMap::new(map.width as usize, map.height as usize, &sources, &TerrainMap{ map }, depth);
+        Field{ sources, map : dijkstra }
+    }
+
+    fn is_from(&self, sources : &[usize]) -> bool {
+        let mut sorted = sources.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        self.sources == sorted
+    }
+}
+
+/// Dijkstra maps shared by the AI, so a level full of monsters isn't path-finding once per
+/// monster per turn. Each field is rebuilt only when the tiles it starts from move, or the
+/// level's terrain changes under it.
+#[derive(Default)]
+pub struct FlowFields {
+    /// The level, and its terrain revision, that the fields were built on.
+    terrain : Option<(LevelKey, u32)>,
+    player : Option<Field>,
+    others : Vec<Field>
+}
+
+impl FlowFields {
+    /// Throws away anything built on terrain that has since changed (or on another level),
+    /// along with the one-off fields from the last system that ran.
+    pub fn sync(&mut self, map : &Map) {
+        let terrain = Some((map.key(), map.terrain_revision));
+        if self.terrain != terrain {
+            self.terrain = terrain;
+            self.player = None;
+        }
+        self.others.clear();
+    }
+
+    /// Distances to the player.
+    pub fn toward_player(&mut self, map : &Map, player_idx : usize) -> &DijkstraMap {
+        if !self.player.as_ref().map_or(false, |field| field.is_from(&[player_idx])) {
+            self.player = Some(Field::build(map, vec![player_idx]));
+        }
+        &self.player.as_ref().unwrap().map
+    }
+
+    /// Distances to the player, for climbing with `step_away`.
+    pub fn away_from_player(&mut self, map : &Map, player_idx : usize) -> &DijkstraMap {
+        self.toward_player(map, player_idx)
+    }
+
+    /// Distances to an arbitrary set of tiles. These only last until the next `sync`, so
+    /// monsters reacting to the same things in one pass share the work.
+    pub fn toward(&mut self, map : &Map, sources : &[usize]) -> &DijkstraMap {
+        let index = match self.others.iter().position(|field| field.is_from(sources)) {
+            Some(index) => index,
+            None => {
+                self.others.push(Field::build(map, sources.to_vec()));
+                self.others.len() - 1
+            }
+        };
+        &self.others[index].map
+    }
+}
+
+/// The open tile next to `idx` that gets closest to the field's sources, if any gets closer.
+pub fn step_toward(field : &DijkstraMap, map : &Map, idx : usize) -> Option<usize> {
+    DijkstraMap::find_lowest_exit(field, idx, map)
+        .filter(|exit| field.map[*exit] < field.map[idx])
+}
+
+/// The open tile next to `idx` that gets furthest from the field's sources, if any does.
+pub fn step_away(field : &DijkstraMap, map : &Map, idx : usize) -> Option<usize> {
+    DijkstraMap::find_highest_exit(field, idx, map)
+        .filter(|exit| field.map[*exit] > field.map[idx])
+}
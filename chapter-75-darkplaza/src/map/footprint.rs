@@ -0,0 +1,70 @@
+use rltk::{ BaseMap, Algorithm2D, Point };
+use specs::prelude::*;
+use super::Map;
+
+/// A view of the map for pathing something bigger than one tile. A tile counts as open
+/// if the whole footprint fits there, with its top-left corner on that tile.
+pub struct FootprintMap<'a> {
+    map : &'a Map,
+    blocked : Vec<bool>
+}
+
+impl<'a> FootprintMap<'a> {
+    /// Builds the footprint for `entity`, which is `width` x `height` tiles. The entity
+    /// doesn't get in its own way.
+    pub fn new(map : &'a Map, entity : Entity, width : i32, height : i32) -> FootprintMap<'a> {
+        let blocked = map.spatial.blocked_tiles_except(entity);
+        let w = map.width as usize;
+        let h = map.height as usize;
+
+        // Summed-area table of blocked tiles, so each footprint is checked in constant time
+        let mut sums = vec![0u32; (w + 1) * (h + 1)];
+        for y in 0..h {
+            for x in 0..w {
+                let here = if blocked.get(y * w + x).copied().unwrap_or(true) { 1 } else { 0 };
+                sums[(y + 1) * (w + 1) + x + 1] = here
+                    + sums[y * (w + 1) + x + 1]
+                    + sums[(y + 1) * (w + 1) + x]
+                    - sums[y * (w + 1) + x];
+            }
+        }
+
+        let mut footprint = vec![true; w * h];
+        for y in 1 .. map.height - 1 {
+            for x in 1 .. map.width - 1 {
+                let (right, bottom) = (x + width, y + height);
+                if right > map.width - 1 || bottom > map.height - 1 { continue; }
+                let (x0, y0, x1, y1) = (x as usize, y as usize, right as usize, bottom as usize);
+                let count = sums[y1 * (w + 1) + x1] + sums[y0 * (w + 1) + x0]
+                    - sums[y0 * (w + 1) + x1] - sums[y1 * (w + 1) + x0];
+                footprint[y0 * w + x0] = count > 0;
+            }
+        }
+
+        FootprintMap{ map, blocked : footprint }
+    }
+
+    pub fn xy_idx(&self, x: i32, y: i32) -> usize {
+        self.map.xy_idx(x, y)
+    }
+}
+
+impl<'a> BaseMap for FootprintMap<'a> {
+    fn is_opaque(&self, idx:usize) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    fn get_available_exits(&self, idx:usize) -> rltk::SmallVec<[(usize, f32); 10]> {
+        self.map.exits_where(idx, |exit| !self.blocked[exit])
+    }
+
+    fn get_pathing_distance(&self, idx1:usize, idx2:usize) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+impl<'a> Algorithm2D for FootprintMap<'a> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
+    }
+}
@@ -0,0 +1,89 @@
+use super::Map;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// What a cloud hanging over a tile is made of.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GasType { Poison, Smoke, Spores }
+
+impl GasType {
+    pub fn named(name : &str) -> Option<GasType> {
+        match name {
+            "poison" => Some(GasType::Poison),
+            "smoke" => Some(GasType::Smoke),
+            "spores" => Some(GasType::Spores),
+            _ => None
+        }
+    }
+
+    pub fn color(&self) -> rltk::RGB {
+        match self {
+            GasType::Poison => rltk::RGB::named(rltk::GREEN),
+            GasType::Smoke => rltk::RGB::named(rltk::GRAY),
+            GasType::Spores => rltk::RGB::named(rltk::YELLOW)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct GasCloud {
+    pub gas : GasType,
+    pub concentration : f32
+}
+
+impl GasCloud {
+    /// Thick smoke is as good as a wall for seeing through.
+    pub fn blocks_view(&self) -> bool {
+        self.gas == GasType::Smoke && self.concentration >= SMOKE_BLOCKS_VIEW
+    }
+}
+
+const SMOKE_BLOCKS_VIEW : f32 = 0.3;
+/// The share of a tile's gas that drifts into its neighbours each turn.
+const DIFFUSION : f32 = 0.4;
+/// How much of the gas is left after each turn.
+const DECAY : f32 = 0.9;
+/// Clouds thinner than this are gone.
+const MIN_CONCENTRATION : f32 = 0.05;
+
+impl Map {
+    /// Adds gas to a tile. If it already holds a different gas, whichever there's more of
+    /// wins out.
+    pub fn add_gas(&mut self, idx : usize, gas : GasType, amount : f32) {
+        add_to(&mut self.gas, idx, gas, amount);
+    }
+
+    /// Spreads every cloud a little into the open tiles around it, and thins it out.
+    pub fn diffuse_gas(&mut self) {
+        let mut tiles : Vec<usize> = self.gas.keys().cloned().collect();
+        tiles.sort_unstable();
+
+        let mut next : HashMap<usize, GasCloud> = HashMap::new();
+        for idx in tiles.iter() {
+            let cloud = self.gas[idx];
+            let x = *idx as i32 % self.width;
+            let y = *idx as i32 / self.width;
+            let share = cloud.concentration * DIFFUSION / 4.0;
+            let mut kept = cloud.concentration;
+            for (nx, ny) in [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter() {
+                if *nx < 1 || *nx > self.width-2 || *ny < 1 || *ny > self.height-2 { continue; }
+                let neighbour = self.xy_idx(*nx, *ny);
+                if self.tile_set.opaque(self.tiles[neighbour]) { continue; }
+                add_to(&mut next, neighbour, cloud.gas, share * DECAY);
+                kept -= share;
+            }
+            add_to(&mut next, *idx, cloud.gas, kept * DECAY);
+        }
+
+        next.retain(|_, cloud| cloud.concentration >= MIN_CONCENTRATION);
+        self.gas = next;
+    }
+}
+
+fn add_to(clouds : &mut HashMap<usize, GasCloud>, idx : usize, gas : GasType, amount : f32) {
+    let cloud = clouds.entry(idx).or_insert(GasCloud{ gas, concentration : 0.0 });
+    if cloud.gas != gas && amount > cloud.concentration {
+        cloud.gas = gas;
+    }
+    cloud.concentration += amount;
+}
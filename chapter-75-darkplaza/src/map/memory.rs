@@ -0,0 +1,87 @@
+use super::Map;
+use rltk::RGB;
+use serde::{Serialize, Deserialize};
+use specs::prelude::*;
+use std::collections::HashMap;
+use crate::components::{Position, Renderable, Hidden, Name, TileSize, Darkvision};
+
+/// Something the player saw on a tile, and remembers being there once the tile is out of
+/// sight.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RememberedEntity {
+    pub name : String,
+    pub glyph : rltk::FontCharType,
+    pub fg : RGB,
+    pub render_order : i32,
+    /// Who it was, so their name needn't be worked out again while they stay put. Not
+    /// saved; after loading, names are looked up afresh.
+    #[serde(skip)]
+    pub entity : Option<Entity>
+}
+
+impl Map {
+    /// Notes that the player saw something on a tile.
+    pub fn remember(&mut self, idx : usize, seen : RememberedEntity) {
+        let memories = self.remembered.entry(idx).or_insert_with(Vec::new);
+        memories.push(seen);
+        memories.sort_by(|a, b| a.render_order.cmp(&b.render_order));
+    }
+
+    /// What's remembered on a tile that can't be seen right now, topmost first.
+    pub fn memories(&self, idx : usize) -> &[RememberedEntity] {
+        if self.visible_tiles[idx] { return &[]; }
+        self.remembered.get(&idx).map_or(&[], |memories| memories.as_slice())
+    }
+}
+
+/// Brings the player's memory up to date: whatever was remembered on a tile in view is
+/// replaced by what's really there now. Anyone still where they were keeps the name they
+/// were remembered by.
+pub fn remember_visible_entities(ecs : &World) {
+    let mut map = ecs.fetch_mut::<Map>();
+    let map = &mut *map;
+    let player = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let names = ecs.read_storage::<Name>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let sizes = ecs.read_storage::<TileSize>();
+    let player_pos = *ecs.fetch::<rltk::Point>();
+    let darkvision = ecs.read_storage::<Darkvision>();
+    let player_darkvision = darkvision.get(player);
+
+    let visible = &map.visible_tiles;
+    let mut previous : HashMap<usize, Vec<RememberedEntity>> = HashMap::new();
+    map.remembered.retain(|idx, memories| {
+        if visible[*idx] {
+            previous.insert(*idx, std::mem::take(memories));
+        }
+        !visible[*idx]
+    });
+
+    for (entity, pos, render, _name, _hidden) in (&entities, &positions, &renderables, &names, !&hidden).join() {
+        if entity == player { continue; }
+        let (w, h) = sizes.get(entity).map_or((1, 1), |size| (size.x, size.y));
+        for y in pos.y .. pos.y + h {
+            for x in pos.x .. pos.x + w {
+                if x < 0 || x >= map.width || y < 0 || y >= map.height { continue; }
+                let idx = map.xy_idx(x, y);
+                if !map.visible_tiles[idx] || !map.can_make_out(player_pos, player_darkvision, idx) { continue; }
+                let known = previous.get_mut(&idx)
+                    .and_then(|old| old.iter().position(|seen| seen.entity == Some(entity)).map(|i| old.swap_remove(i)));
+                let name = match known {
+                    Some(seen) => seen.name,
+                    None => crate::gui::get_item_display_name(ecs, entity)
+                };
+                map.remember(idx, RememberedEntity{
+                    name,
+                    glyph : render.glyph,
+                    fg : render.fg,
+                    render_order : render.render_order,
+                    entity : Some(entity)
+                });
+            }
+        }
+    }
+}
@@ -1,90 +1,231 @@
 use rltk::{ BaseMap, Algorithm2D, Point };
 use specs::prelude::*;
 use serde::{Serialize, Deserialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::spatial::SpatialMap;
 mod tiletype;
-pub use tiletype::{TileType, tile_walkable, tile_opaque, tile_cost};
+pub use tiletype::{TileType, TileAppearance, TileSet};
 mod themes;
 pub use themes::*;
 mod dungeon;
-pub use dungeon::{MasterDungeonMap, level_transition, freeze_level_entities, thaw_level_entities};
+pub use dungeon::{MasterDungeonMap, level_transition, freeze_level_entities, thaw_level_entities,
+    level_above, level_below, branch_below};
 pub mod camera;
+mod footprint;
+pub use footprint::FootprintMap;
+mod gas;
+pub use gas::{GasType, GasCloud};
+mod darkness;
+mod memory;
+pub use memory::{RememberedEntity, remember_visible_entities};
+mod flow;
+pub use flow::{FlowFields, step_toward, step_away};
+
+/// The branch every run starts in, and the one save files from before branches belong to.
+pub const MAIN_BRANCH : &str = "main";
+
+pub fn main_branch() -> String {
+    MAIN_BRANCH.to_string()
+}
+
+/// Identifies a level: the branch of the dungeon it belongs to, and its depth.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+pub struct LevelKey {
+    pub branch : String,
+    pub depth : i32
+}
+
+impl LevelKey {
+    pub fn new<S : ToString>(branch : S, depth : i32) -> LevelKey {
+        LevelKey{ branch : branch.to_string(), depth }
+    }
+
+    pub fn main(depth : i32) -> LevelKey {
+        LevelKey::new(MAIN_BRANCH, depth)
+    }
+
+    pub fn is_main(&self) -> bool {
+        self.branch == MAIN_BRANCH
+    }
+}
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
     pub tiles : Vec<TileType>,
+    /// What each kind of tile is like; the raws' tile set, put back after loading.
+    #[serde(skip)]
+    pub tile_set : Arc<TileSet>,
+    /// The name of each tile ID as the map was made, so that a save still reads right if
+    /// the raws number their tiles differently.
+    #[serde(default)]
+    pub tile_names : Vec<String>,
     pub width : i32,
     pub height : i32,
     pub revealed_tiles : Vec<bool>,
     pub visible_tiles : Vec<bool>,
     pub depth : i32,
+    #[serde(default = "main_branch")]
+    pub branch : String,
+    /// Branch staircases on this level, and the branch each one leads into.
+    #[serde(default)]
+    pub branch_entrances : HashMap<usize, String>,
+    /// Which look the tiles take (see `raws/tiles.json`); empty for the plain look.
+    #[serde(default)]
+    pub theme : String,
+    #[serde(default)]
+    pub theme_regions : Vec<ThemeRegion>,
     pub bloodstains : HashSet<usize>,
+    /// Tiles someone has started digging through, and how many turns have gone into each.
+    #[serde(default)]
+    pub dig_progress : HashMap<usize, i32>,
+    /// Tiles that are on fire, and how many more turns each will burn for.
+    #[serde(default)]
+    pub fires : HashMap<usize, i32>,
+    /// Clouds of gas hanging over tiles; see `gas.rs`.
+    #[serde(default)]
+    pub gas : HashMap<usize, GasCloud>,
+    /// What the player last saw on tiles that are now out of sight; see `memory.rs`.
+    #[serde(default)]
+    pub remembered : HashMap<usize, Vec<RememberedEntity>>,
     pub view_blocked : HashSet<usize>,
+    /// Goes up whenever something that blocks sight changes, so anything worked out from
+    /// what can be seen where (like the area a light reaches) knows to do it again.
+    #[serde(skip)]
+    pub view_revision : u32,
+    /// Goes up by one whenever a tile changes, so anything worked out from the terrain (like
+    /// the AI's flow fields) knows to do it again.
+    #[serde(skip)]
+    pub terrain_revision : u32,
+    /// Who is standing where, and which tiles are blocked; rebuilt every turn, so not saved.
+    #[serde(skip)]
+    pub spatial : SpatialMap,
     pub name : String,
     pub outdoors : bool,
     pub light : Vec<rltk::RGB>,
 }
 
 impl Map {
+    pub fn key(&self) -> LevelKey {
+        LevelKey::new(&self.branch, self.depth)
+    }
+
+    /// The theme column `x` is drawn in.
+    pub fn theme_at(&self, x: i32) -> &str {
+        self.theme_regions.iter()
+            .find(|r| x >= r.min_x && x <= r.max_x)
+            .map(|r| r.theme.as_str())
+            .unwrap_or(&self.theme)
+    }
+
     pub fn xy_idx(&self, x: i32, y: i32) -> usize {
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn is_exit_valid(&self, x:i32, y:i32) -> bool {
-        if x < 1 || x > self.width-1 || y < 1 || y > self.height-1 { return false; }
-        let idx = self.xy_idx(x, y);
-        !crate::spatial::is_blocked(idx)
+    /// The tiles you can step to from `idx`, and what each step costs, where `open` says
+    /// whether a tile inside the map border can be entered.
+    fn exits_where<F : Fn(usize) -> bool>(&self, idx:usize, open: F) -> rltk::SmallVec<[(usize, f32); 10]> {
+        const DIAGONAL_COST : f32 = 1.5;
+        let mut exits = rltk::SmallVec::new();
+        let x = idx as i32 % self.width;
+        let y = idx as i32 / self.width;
+        let cost = self.tile_set.cost(self.tiles[idx as usize]);
+        let is_exit_valid = |x:i32, y:i32| {
+            if x < 1 || x > self.width-1 || y < 1 || y > self.height-1 { return false; }
+            open(self.xy_idx(x, y))
+        };
+
+        // Cardinal directions
+        if is_exit_valid(x-1, y) { exits.push((self.xy_idx(x-1, y), cost)) };
+        if is_exit_valid(x+1, y) { exits.push((self.xy_idx(x+1, y), cost)) };
+        if is_exit_valid(x, y-1) { exits.push((self.xy_idx(x, y-1), cost)) };
+        if is_exit_valid(x, y+1) { exits.push((self.xy_idx(x, y+1), cost)) };
+
+        // Diagonals
+        if is_exit_valid(x-1, y-1) { exits.push((self.xy_idx(x-1, y-1), cost * DIAGONAL_COST)); }
+        if is_exit_valid(x+1, y-1) { exits.push((self.xy_idx(x+1, y-1), cost * DIAGONAL_COST)); }
+        if is_exit_valid(x-1, y+1) { exits.push((self.xy_idx(x-1, y+1), cost * DIAGONAL_COST)); }
+        if is_exit_valid(x+1, y+1) { exits.push((self.xy_idx(x+1, y+1), cost * DIAGONAL_COST)); }
+
+        exits
+    }
+
+    /// Replaces the set of tiles entities or gas block sight through.
+    pub fn set_view_blocked(&mut self, blocked : HashSet<usize>) {
+        if blocked != self.view_blocked {
+            self.view_blocked = blocked;
+            self.view_changed();
+        }
+    }
+
+    /// Notes that what blocks sight has changed.
+    pub fn view_changed(&mut self) {
+        self.view_revision = self.view_revision.wrapping_add(1);
+    }
+
+    /// Notes that a tile has changed.
+    pub fn terrain_changed(&mut self) {
+        self.terrain_revision = self.terrain_revision.wrapping_add(1);
     }
 
     pub fn populate_blocked(&mut self) {
-        crate::spatial::populate_blocked_from_map(self);
-    }
-
-    pub fn populate_blocked_multi(&mut self, width : i32, height : i32) {
-        self.populate_blocked();
-        for y in 1 .. self.height-1 {
-            for x in 1 .. self.width - 1 {
-                let idx = self.xy_idx(x, y);
-                if !crate::spatial::is_blocked(idx) {
-                    for cy in 0..height {
-                        for cx in 0..width {
-                            let tx = x + cx;
-                            let ty = y + cy;
-                            if tx < self.width-1 && ty < self.height-1 {
-                                let tidx = self.xy_idx(tx, ty);
-                                if crate::spatial::is_blocked(tidx) {
-                                    crate::spatial::set_blocked(idx, true);
-                                }
-                            } else {
-                                crate::spatial::set_blocked(idx, true);
-                            }
-                        }
-                    }
-                }
+        self.spatial.populate_blocked(&self.tiles, &self.tile_set);
+    }
+
+    /// Whether the tile at `idx` can be walked on, whoever is standing there.
+    pub fn walkable(&self, idx : usize) -> bool {
+        self.tile_set.walkable(self.tiles[idx])
+    }
+
+    /// Gives a map read from a save the tile set it's played with, renumbering its tiles
+    /// if the raws' numbering has changed since it was saved.
+    pub fn attach_tiles(&mut self, tile_set : &Arc<TileSet>) -> Result<(), String> {
+        if self.tile_names != tile_set.names() {
+            let mut renumbered = Vec::with_capacity(self.tile_names.len());
+            for name in self.tile_names.iter() {
+                renumbered.push(tile_set.named(name).ok_or_else(|| format!("The save has a tile called {}, which the raws don't know about.", name))?);
             }
+            for tile in self.tiles.iter_mut() {
+                *tile = *renumbered.get(tile.0 as usize).ok_or_else(|| format!("The save has an unknown tile, number {}.", tile.0))?;
+            }
+            self.tile_names = tile_set.names();
         }
+        self.tile_set = tile_set.clone();
+        Ok(())
     }
 
     pub fn clear_content_index(&mut self) {
-        crate::spatial::clear();
+        self.spatial.clear();
     }
 
     /// Generates an empty map, consisting entirely of solid walls
-    pub fn new<S : ToString>(new_depth : i32, width: i32, height: i32, name: S) -> Map {
+    pub fn new<S : ToString>(new_depth : i32, width: i32, height: i32, name: S, tile_set : &Arc<TileSet>) -> Map {
         let map_tile_count = (width*height) as usize;
-        crate::spatial::set_size(map_tile_count);
         Map{
             tiles : vec![TileType::Wall; map_tile_count],
+            tile_set : tile_set.clone(),
+            tile_names : tile_set.names(),
             width,
             height,
             revealed_tiles : vec![false; map_tile_count],
             visible_tiles : vec![false; map_tile_count],
             depth: new_depth,
+            branch : main_branch(),
+            branch_entrances : HashMap::new(),
+            theme : String::new(),
+            theme_regions : Vec::new(),
             bloodstains: HashSet::new(),
+            dig_progress : HashMap::new(),
+            fires : HashMap::new(),
+            gas : HashMap::new(),
+            remembered : HashMap::new(),
             view_blocked : HashSet::new(),
+            view_revision : 0,
+            terrain_revision : 0,
+            spatial : SpatialMap::new(map_tile_count),
             name : name.to_string(),
             outdoors : true,
-            light: vec![rltk::RGB::from_f32(0.0, 0.0, 0.0); map_tile_count]
+            light: vec![rltk::RGB::from_f32(1.0, 1.0, 1.0); map_tile_count] // Until the lighting system works it out
         }
     }
 }
@@ -92,33 +233,14 @@ impl Map {
 impl BaseMap for Map {
     fn is_opaque(&self, idx:usize) -> bool {
         if idx > 0 && idx < self.tiles.len() {
-            tile_opaque(self.tiles[idx]) || self.view_blocked.contains(&idx)
+            self.tile_set.opaque(self.tiles[idx]) || self.view_blocked.contains(&idx)
         } else {
             true
         }
     }
 
     fn get_available_exits(&self, idx:usize) -> rltk::SmallVec<[(usize, f32); 10]> {
-        const DIAGONAL_COST : f32 = 1.5;
-        let mut exits = rltk::SmallVec::new();
-        let x = idx as i32 % self.width;
-        let y = idx as i32 / self.width;
-        let tt = self.tiles[idx as usize];
-        let w = self.width as usize;
-
-        // Cardinal directions
-        if self.is_exit_valid(x-1, y) { exits.push((idx-1, tile_cost(tt))) };
-        if self.is_exit_valid(x+1, y) { exits.push((idx+1, tile_cost(tt))) };
-        if self.is_exit_valid(x, y-1) { exits.push((idx-w, tile_cost(tt))) };
-        if self.is_exit_valid(x, y+1) { exits.push((idx+w, tile_cost(tt))) };
-
-        // Diagonals
-        if self.is_exit_valid(x-1, y-1) { exits.push(((idx-w)-1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x+1, y-1) { exits.push(((idx-w)+1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x-1, y+1) { exits.push(((idx+w)-1, tile_cost(tt) * DIAGONAL_COST)); }
-        if self.is_exit_valid(x+1, y+1) { exits.push(((idx+w)+1, tile_cost(tt) * DIAGONAL_COST)); }
-
-        exits
+        self.exits_where(idx, |exit| !self.spatial.is_blocked(exit))
     }
 
     fn get_pathing_distance(&self, idx1:usize, idx2:usize) -> f32 {
@@ -1,37 +1,40 @@
-use super::{Map, TileType};
+use super::Map;
 use rltk::RGB;
+use serde::{Serialize, Deserialize};
+
+/// A part of a level drawn in a different theme, e.g. the edge of a cavern where it
+/// opens out into dungeon. Covers columns `min_x` to `max_x` inclusive.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThemeRegion {
+    pub theme : String,
+    #[serde(default)]
+    pub min_x : i32,
+    #[serde(default = "region_max_x")]
+    pub max_x : i32
+}
+
+fn region_max_x() -> i32 { i32::MAX }
 
 pub fn tile_glyph(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let (glyph, mut fg, mut bg) = match map.depth {
-        9 => get_mushroom_glyph(idx, map),
-        8 => get_mushroom_glyph(idx, map),
-        7 => {
-            let x = idx as i32 % map.width;
-            if x > map.width-16 {
-                get_tile_glyph_default(idx, map)
-            } else {
-                get_mushroom_glyph(idx, map)
-            }
-        }
-        5 => {
-            let x = idx as i32 % map.width;
-            if x < map.width/2 {
-                get_limestone_cavern_glyph(idx, map)
-            } else {
-                get_tile_glyph_default(idx, map)
-            }
-        }
-        4 => get_limestone_cavern_glyph(idx, map),
-        3 => get_limestone_cavern_glyph(idx, map),
-        2 => get_forest_glyph(idx, map),
-        _ => get_tile_glyph_default(idx, map)
-    };
+    let x = idx as i32 % map.width;
+    let y = idx as i32 / map.width;
+    let look = map.tile_set.appearance(map.tiles[idx], map.theme_at(x));
+    let mut glyph = if look.connected { connected_glyph(map, x, y) } else { look.glyph };
+    let (mut fg, mut bg) = (look.fg, look.bg);
 
     if map.bloodstains.contains(&idx) { bg = RGB::from_f32(0.75, 0., 0.); }
+    if let Some(cloud) = map.gas.get(&idx) {
+        bg = bg.lerp(cloud.gas.color(), f32::min(cloud.concentration, 1.0) * 0.75);
+    }
+    if map.fires.contains_key(&idx) && map.visible_tiles[idx] {
+        glyph = rltk::to_cp437('^');
+        fg = RGB::named(rltk::YELLOW);
+        bg = RGB::named(rltk::ORANGE_RED);
+    }
     if !map.visible_tiles[idx] {
         fg = fg.to_greyscale();
         bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
-    } else if !map.outdoors {
+    } else {
         fg = fg * map.light[idx];
         bg = bg * map.light[idx];
     }
@@ -39,108 +42,16 @@ pub fn tile_glyph(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
     (glyph, fg, bg)
 }
 
-fn get_forest_glyph(idx:usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => { glyph = rltk::to_cp437('♣'); fg = RGB::from_f32(0.0, 0.6, 0.0); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::YELLOW); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::BLUE); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::UpStairs => { glyph = rltk::to_cp437('<'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        _ => { glyph = rltk::to_cp437('"'); fg = RGB::from_f32(0.0, 0.5, 0.0); }
-    }
-
-    (glyph, fg, bg)
-}
-
-fn get_mushroom_glyph(idx:usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => { glyph = rltk::to_cp437('♠'); fg = RGB::from_f32(1.0, 0.0, 1.0); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::GREEN); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::BLUE); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::UpStairs => { glyph = rltk::to_cp437('<'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        _ => { glyph = rltk::to_cp437('"'); fg = RGB::from_f32(0.0, 0.6, 0.0); }
-    }
-
-    (glyph, fg, bg)
-}
-
-fn get_limestone_cavern_glyph(idx:usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Wall => { glyph = rltk::to_cp437('▒'); fg = RGB::from_f32(0.7, 0.7, 0.7); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::YELLOW); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('░'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('▓'); fg = RGB::from_f32(0.2, 0.2, 1.0); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::UpStairs => { glyph = rltk::to_cp437('<'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::Stalactite => { glyph = rltk::to_cp437('╨'); fg = RGB::from_f32(0.7, 0.7, 0.7); }
-        TileType::Stalagmite => { glyph = rltk::to_cp437('╥'); fg = RGB::from_f32(0.7, 0.7, 0.7); }
-        _ => { glyph = rltk::to_cp437('\''); fg = RGB::from_f32(0.4, 0.4, 0.4); }
-    }
-
-    (glyph, fg, bg)
-}
-
-fn get_tile_glyph_default(idx: usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
-    let glyph;
-    let fg;
-    let bg = RGB::from_f32(0., 0., 0.);
-
-    match map.tiles[idx] {
-        TileType::Floor => { glyph = rltk::to_cp437('.'); fg = RGB::from_f32(0.0, 0.5, 0.5); }
-        TileType::WoodFloor => { glyph = rltk::to_cp437('░'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Wall => {
-            let x = idx as i32 % map.width;
-            let y = idx as i32 / map.width;
-            glyph = wall_glyph(&*map, x, y);
-            fg = RGB::from_f32(0., 1.0, 0.);
-        }
-        TileType::DownStairs => { glyph = rltk::to_cp437('>'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::UpStairs => { glyph = rltk::to_cp437('<'); fg = RGB::from_f32(0., 1.0, 1.0); }
-        TileType::Bridge => { glyph = rltk::to_cp437('.'); fg = RGB::named(rltk::CHOCOLATE); }
-        TileType::Road => { glyph = rltk::to_cp437('≡'); fg = RGB::named(rltk::GRAY); }
-        TileType::Grass => { glyph = rltk::to_cp437('"'); fg = RGB::named(rltk::GREEN); }
-        TileType::ShallowWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::CYAN); }
-        TileType::DeepWater => { glyph = rltk::to_cp437('~'); fg = RGB::named(rltk::BLUE); }
-        TileType::Gravel => { glyph = rltk::to_cp437(';'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::Stalactite => { glyph = rltk::to_cp437('╨'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-        TileType::Stalagmite => { glyph = rltk::to_cp437('╥'); fg = RGB::from_f32(0.5, 0.5, 0.5); }
-    }
-
-    (glyph, fg, bg)
-}
-
-fn wall_glyph(map : &Map, x: i32, y:i32) -> rltk::FontCharType {
+/// Joins a connected tile (like a wall) up to the revealed tiles of the same type around it.
+fn connected_glyph(map : &Map, x: i32, y:i32) -> rltk::FontCharType {
     if x < 1 || x > map.width-2 || y < 1 || y > map.height-2 as i32 { return 35; }
     let mut mask : u8 = 0;
+    let tt = map.tiles[map.xy_idx(x, y)];
 
-    if is_revealed_and_wall(map, x, y - 1) { mask +=1; }
-    if is_revealed_and_wall(map, x, y + 1) { mask +=2; }
-    if is_revealed_and_wall(map, x - 1, y) { mask +=4; }
-    if is_revealed_and_wall(map, x + 1, y) { mask +=8; }
+    if is_revealed_and_same(map, x, y - 1, tt) { mask +=1; }
+    if is_revealed_and_same(map, x, y + 1, tt) { mask +=2; }
+    if is_revealed_and_same(map, x - 1, y, tt) { mask +=4; }
+    if is_revealed_and_same(map, x + 1, y, tt) { mask +=8; }
 
     match mask {
         0 => { 9 } // Pillar because we can't see neighbors
@@ -163,7 +74,7 @@ fn wall_glyph(map : &Map, x: i32, y:i32) -> rltk::FontCharType {
     }
 }
 
-fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
+fn is_revealed_and_same(map: &Map, x: i32, y: i32, tt: super::TileType) -> bool {
     let idx = map.xy_idx(x, y);
-    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
+    map.tiles[idx] == tt && map.revealed_tiles[idx]
 }
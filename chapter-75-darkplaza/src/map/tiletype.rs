@@ -1,44 +1,195 @@
 use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use rltk::RGB;
+use crate::raws::{TileDef, TileLook};
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
-pub enum TileType {
-    Wall,
-    Stalactite,
-    Stalagmite,
-    Floor,
-    DownStairs,
-    Road,
-    Grass,
-    ShallowWater,
-    DeepWater,
-    WoodFloor,
-    Bridge,
-    Gravel,
-    UpStairs
-}
-
-pub fn tile_walkable(tt : TileType) -> bool {
-    match tt {
-        TileType::Floor | TileType::DownStairs | TileType::Road | TileType::Grass |
-        TileType::ShallowWater | TileType::WoodFloor | TileType::Bridge | TileType::Gravel |
-        TileType::UpStairs
-            => true,
-        _ => false
-    }
-}
-
-pub fn tile_opaque(tt : TileType) -> bool {
-    match tt {
-        TileType::Wall | TileType::Stalactite | TileType::Stalagmite => true,
-        _ => false
-    }
-}
-
-pub fn tile_cost(tt : TileType) -> f32 {
-    match tt {
-        TileType::Road => 0.8,
-        TileType::Grass => 1.1,
-        TileType::ShallowWater => 1.2,
-        _ => 1.0
+/// A kind of terrain, as an index into the tile definitions in `raws/tiles.json`.
+///
+/// The tiles the map builders place by hand have constants here, and always keep the same
+/// ID. Anything else in the raws (lava, ice...) is numbered after them as it's loaded.
+/// Saved maps keep the names their IDs stood for (`Map::tile_names`), so the numbering can
+/// change without breaking them.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct TileType(pub u16);
+
+#[allow(non_upper_case_globals)]
+impl TileType {
+    pub const Wall : TileType = TileType(0);
+    pub const Stalactite : TileType = TileType(1);
+    pub const Stalagmite : TileType = TileType(2);
+    pub const Floor : TileType = TileType(3);
+    pub const DownStairs : TileType = TileType(4);
+    pub const Road : TileType = TileType(5);
+    pub const Grass : TileType = TileType(6);
+    pub const ShallowWater : TileType = TileType(7);
+    pub const DeepWater : TileType = TileType(8);
+    pub const WoodFloor : TileType = TileType(9);
+    pub const Bridge : TileType = TileType(10);
+    pub const Gravel : TileType = TileType(11);
+    pub const UpStairs : TileType = TileType(12);
+    pub const BranchStairs : TileType = TileType(13);
+    pub const Ash : TileType = TileType(14);
+}
+
+const BUILT_IN_TILES : [&str; 15] = [
+    "Wall", "Stalactite", "Stalagmite", "Floor", "DownStairs", "Road", "Grass",
+    "ShallowWater", "DeepWater", "WoodFloor", "Bridge", "Gravel", "UpStairs", "BranchStairs", "Ash"
+];
+
+/// How a tile is drawn.
+#[derive(Clone)]
+pub struct TileAppearance {
+    pub glyph : rltk::FontCharType,
+    pub symbol : char,
+    pub fg : RGB,
+    pub bg : RGB,
+    pub connected : bool
+}
+
+impl TileAppearance {
+    fn from_raws(look : &TileLook) -> TileAppearance {
+        let symbol = look.glyph.chars().next().unwrap_or('?');
+        TileAppearance{
+            glyph : rltk::to_cp437(symbol),
+            symbol,
+            fg : parse_color(&look.fg),
+            bg : look.bg.as_ref().map(|bg| parse_color(bg)).unwrap_or_else(|| RGB::from_f32(0., 0., 0.)),
+            connected : look.connected
+        }
+    }
+}
+
+fn parse_color(hex : &str) -> RGB {
+    RGB::from_hex(hex).unwrap_or_else(|_| {
+        crate::console_log(format!("WARNING - bad tile colour {}", hex));
+        RGB::named(rltk::MAGENTA)
+    })
+}
+
+struct TileInfo {
+    name : String,
+    walkable : bool,
+    opaque : bool,
+    cost : f32,
+    flags : HashSet<String>,
+    appearance : TileAppearance,
+    themes : HashMap<String, TileAppearance>
+}
+
+impl TileInfo {
+    fn placeholder(name : &str) -> TileInfo {
+        TileInfo{
+            name : name.to_string(),
+            walkable : false,
+            opaque : false,
+            cost : 1.0,
+            flags : HashSet::new(),
+            appearance : TileAppearance{ glyph : rltk::to_cp437('?'), symbol : '?', fg : RGB::named(rltk::MAGENTA), bg : RGB::from_f32(0., 0., 0.), connected : false },
+            themes : HashMap::new()
+        }
+    }
+
+    fn from_raws(def : &TileDef) -> TileInfo {
+        TileInfo{
+            name : def.name.clone(),
+            walkable : def.walkable,
+            opaque : def.opaque,
+            cost : def.cost,
+            flags : def.flags.iter().cloned().collect(),
+            appearance : TileAppearance::from_raws(&def.appearance),
+            themes : def.themes.iter().map(|(theme, look)| (theme.clone(), TileAppearance::from_raws(look))).collect()
+        }
+    }
+}
+
+/// Everything the raws say about each kind of tile, indexed by `TileType`. Built once when
+/// the raws are loaded, and shared by the raws and every map made with them.
+pub struct TileSet {
+    tiles : Vec<TileInfo>,
+    index : HashMap<String, u16>
+}
+
+impl Default for TileSet {
+    fn default() -> TileSet {
+        TileSet::built_in()
+    }
+}
+
+impl TileSet {
+    /// Just the built-in tiles, as placeholders that can't be walked on.
+    fn built_in() -> TileSet {
+        let mut set = TileSet{ tiles : Vec::new(), index : HashMap::new() };
+        for name in BUILT_IN_TILES.iter() {
+            set.index.insert(name.to_string(), set.tiles.len() as u16);
+            set.tiles.push(TileInfo::placeholder(name));
+        }
+        set
+    }
+
+    /// The tile definitions from the raws. Built-in tiles keep their IDs; any the raws
+    /// leave out are logged and left as placeholders.
+    pub fn from_raws(defs : &[TileDef]) -> TileSet {
+        let mut set = TileSet::built_in();
+        let mut defined = vec![false; set.tiles.len()];
+        for def in defs.iter() {
+            match set.index.get(&def.name) {
+                Some(id) if (*id as usize) < defined.len() && !defined[*id as usize] => {
+                    defined[*id as usize] = true;
+                    set.tiles[*id as usize] = TileInfo::from_raws(def);
+                }
+                Some(_) => crate::console_log(format!("WARNING - duplicate tile [{}]", def.name)),
+                None => {
+                    set.index.insert(def.name.clone(), set.tiles.len() as u16);
+                    set.tiles.push(TileInfo::from_raws(def));
+                }
+            }
+        }
+        for (i, done) in defined.iter().enumerate() {
+            if !done {
+                crate::console_log(format!("WARNING - no tile definition for [{}]", BUILT_IN_TILES[i]));
+            }
+        }
+        set
+    }
+
+    fn get(&self, tt : TileType) -> &TileInfo {
+        &self.tiles[tt.0 as usize]
+    }
+
+    /// Looks a tile up by its name in the raws.
+    pub fn named(&self, name : &str) -> Option<TileType> {
+        self.index.get(name).map(|id| TileType(*id))
+    }
+
+    pub fn name(&self, tt : TileType) -> &str {
+        &self.get(tt).name
+    }
+
+    /// The name of every tile, in ID order.
+    pub fn names(&self) -> Vec<String> {
+        self.tiles.iter().map(|info| info.name.clone()).collect()
+    }
+
+    pub fn walkable(&self, tt : TileType) -> bool {
+        self.get(tt).walkable
+    }
+
+    pub fn opaque(&self, tt : TileType) -> bool {
+        self.get(tt).opaque
+    }
+
+    pub fn cost(&self, tt : TileType) -> f32 {
+        self.get(tt).cost
+    }
+
+    /// Whether the tile has a flag such as `liquid` or `flammable` in the raws.
+    pub fn has_flag(&self, tt : TileType, flag : &str) -> bool {
+        self.get(tt).flags.contains(flag)
+    }
+
+    /// How the tile looks in `theme`, falling back to its usual look.
+    pub fn appearance(&self, tt : TileType, theme : &str) -> &TileAppearance {
+        let info = self.get(tt);
+        info.themes.get(theme).unwrap_or(&info.appearance)
     }
 }
@@ -1,5 +1,5 @@
 use super::{MetaMapBuilder, BuilderMap, Position};
-use crate::map;
+use rltk::RandomNumberGenerator;
 
 #[allow(dead_code)]
 pub enum XStart { LEFT, CENTER, RIGHT }
@@ -13,7 +13,7 @@ pub struct AreaStartingPosition {
 }
 
 impl MetaMapBuilder for AreaStartingPosition {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
@@ -44,7 +44,7 @@ impl AreaStartingPosition {
 
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if build_data.map.tile_set.walkable(*tiletype) {
                 available_floors.push(
                     (
                         idx,
@@ -0,0 +1,59 @@
+use super::{MetaMapBuilder, BuilderMap, TileType, dijkstra_limit};
+use rltk::RandomNumberGenerator;
+
+/// Adds a staircase down into a side branch, part way between the start and the exit.
+pub struct BranchEntrance {
+    branch : String
+}
+
+impl MetaMapBuilder for BranchEntrance {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
+    }
+}
+
+impl BranchEntrance {
+    #[allow(dead_code)]
+    pub fn new(branch : &str) -> Box<BranchEntrance> {
+        Box::new(BranchEntrance{ branch : branch.to_string() })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let starting_pos = match &build_data.starting_position {
+            Some(pos) => pos.clone(),
+            None => {
+                crate::console_log(format!("BranchEntrance: no starting position, so no way into {}", self.branch));
+                return;
+            }
+        };
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+        build_data.map.populate_blocked();
+        let map_starts : Vec<usize> = vec![start_idx];
+        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts , &build_data.map, dijkstra_limit(&build_data.map));
+
+        // Candidates are reachable floor tiles that nothing spawns on
+        let mut candidates : Vec<(usize, f32)> = build_data.map.tiles
+            .iter()
+            .enumerate()
+            .filter(|(i, tile)| **tile == TileType::Floor && *i != start_idx)
+            .filter(|(i, _)| dijkstra_map.map[*i] != std::f32::MAX)
+            .filter(|(i, _)| !build_data.spawn_list.iter().any(|(idx, _)| idx == i))
+            .map(|(i, _)| (i, dijkstra_map.map[i]))
+            .collect();
+        if candidates.is_empty() {
+            crate::console_log(format!("BranchEntrance: nowhere to put the way into {}", self.branch));
+            return;
+        }
+
+        // Somewhere in the middle third by distance, so it's neither next to the start nor the exit
+        candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0)));
+        let third = candidates.len() / 3;
+        let middle = &candidates[third .. candidates.len() - third];
+        let roll = rng.roll_dice(1, middle.len() as i32) - 1;
+        let stairs_idx = middle[roll as usize].0;
+
+        build_data.map.tiles[stairs_idx] = TileType::BranchStairs;
+        build_data.map.branch_entrances.insert(stairs_idx, self.branch.clone());
+        build_data.take_snapshot();
+    }
+}
@@ -1,4 +1,5 @@
 use super::{InitialMapBuilder, BuilderMap, Rect, TileType, draw_corridor};
+use rltk::RandomNumberGenerator;
 
 const MIN_ROOM_SIZE : i32 = 8;
 
@@ -8,8 +9,8 @@ pub struct BspInteriorBuilder {
 
 impl InitialMapBuilder for BspInteriorBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
@@ -21,12 +22,12 @@ impl BspInteriorBuilder {
         })
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let mut rooms : Vec<Rect> = Vec::new();
         self.rects.clear();
         self.rects.push( Rect::new(1, 1, build_data.map.width-2, build_data.map.height-2) ); // Start with a single map-sized rectangle
         let first_room = self.rects[0];
-        self.add_subrects(first_room); // Divide the first room
+        self.add_subrects(rng, first_room); // Divide the first room
 
         let rooms_copy = self.rects.clone();
         for r in rooms_copy.iter() {
@@ -49,17 +50,17 @@ impl BspInteriorBuilder {
         for i in 0..rooms.len()-1 {
             let room = rooms[i];
             let next_room = rooms[i+1];
-            let start_x = room.x1 + (crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2))-1);
-            let start_y = room.y1 + (crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2))-1);
-            let end_x = next_room.x1 + (crate::rng::roll_dice(1, i32::abs(next_room.x1 - next_room.x2))-1);
-            let end_y = next_room.y1 + (crate::rng::roll_dice(1, i32::abs(next_room.y1 - next_room.y2))-1);
+            let start_x = room.x1 + (rng.roll_dice(1, i32::abs(room.x1 - room.x2))-1);
+            let start_y = room.y1 + (rng.roll_dice(1, i32::abs(room.y1 - room.y2))-1);
+            let end_x = next_room.x1 + (rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2))-1);
+            let end_y = next_room.y1 + (rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2))-1);
             draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             build_data.take_snapshot();
         }
         build_data.rooms = Some(rooms);
     }
 
-    fn add_subrects(&mut self, rect : Rect) {
+    fn add_subrects(&mut self, rng : &mut RandomNumberGenerator, rect : Rect) {
         // Remove the last rect from the list
         if !self.rects.is_empty() {
             self.rects.remove(self.rects.len() - 1);
@@ -71,24 +72,24 @@ impl BspInteriorBuilder {
         let half_width = width / 2;
         let half_height = height / 2;
 
-        let split = crate::rng::roll_dice(1, 4);
+        let split = rng.roll_dice(1, 4);
 
         if split <= 2 {
             // Horizontal split
             let h1 = Rect::new( rect.x1, rect.y1, half_width-1, height );
             self.rects.push( h1 );
-            if half_width > MIN_ROOM_SIZE { self.add_subrects(h1); }
+            if half_width > MIN_ROOM_SIZE { self.add_subrects(rng, h1); }
             let h2 = Rect::new( rect.x1 + half_width, rect.y1, half_width, height );
             self.rects.push( h2 );
-            if half_width > MIN_ROOM_SIZE { self.add_subrects(h2); }
+            if half_width > MIN_ROOM_SIZE { self.add_subrects(rng, h2); }
         } else {
             // Vertical split
             let v1 = Rect::new( rect.x1, rect.y1, width, half_height-1 );
             self.rects.push(v1);
-            if half_height > MIN_ROOM_SIZE { self.add_subrects(v1); }
+            if half_height > MIN_ROOM_SIZE { self.add_subrects(rng, v1); }
             let v2 = Rect::new( rect.x1, rect.y1 + half_height, width, half_height );
             self.rects.push(v2);
-            if half_height > MIN_ROOM_SIZE { self.add_subrects(v2); }
+            if half_height > MIN_ROOM_SIZE { self.add_subrects(rng, v2); }
         }
     }
 }
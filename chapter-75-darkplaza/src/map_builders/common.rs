@@ -1,4 +1,4 @@
-use super::{Map, TileType};
+use super::{Map, TileType, BuilderMap};
 use std::cmp::{max, min};
 
 #[derive(PartialEq, Copy, Clone)]
@@ -115,3 +115,25 @@ fn apply_paint(map: &mut Map, brush_size: i32, x: i32, y: i32) {
         }
     }
 }
+
+/// Turns the outermost ring of tiles into walls, and drops anything that was going to spawn there.
+pub fn wall_in_border(build_data : &mut BuilderMap) {
+    let width = build_data.map.width;
+    let height = build_data.map.height;
+    let on_border = |idx: usize| {
+        let x = idx as i32 % width;
+        let y = idx as i32 / width;
+        x == 0 || y == 0 || x == width-1 || y == height-1
+    };
+    for (idx, tt) in build_data.map.tiles.iter_mut().enumerate() {
+        if on_border(idx) {
+            *tt = TileType::Wall;
+        }
+    }
+    build_data.spawn_list.retain(|(idx, _)| !on_border(*idx));
+}
+
+/// A Dijkstra search limit that won't cut off any path on `map`, however large it is.
+pub fn dijkstra_limit(map : &Map) -> f32 {
+    f32::max(3000.0, (map.width * map.height) as f32 * 2.0)
+}
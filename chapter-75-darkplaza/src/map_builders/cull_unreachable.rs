@@ -1,9 +1,10 @@
-use super::{MetaMapBuilder, BuilderMap, TileType};
+use super::{MetaMapBuilder, BuilderMap, TileType, dijkstra_limit};
+use rltk::RandomNumberGenerator;
 
 pub struct CullUnreachable {}
 
 impl MetaMapBuilder for CullUnreachable {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
@@ -22,12 +23,13 @@ impl CullUnreachable {
         );
         build_data.map.populate_blocked();
         let map_starts : Vec<usize> = vec![start_idx];
-        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts , &build_data.map, 1000.0);
+        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts , &build_data.map, dijkstra_limit(&build_data.map));
         for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
             if *tile == TileType::Floor {
                 let distance_to_start = dijkstra_map.map[i];
-                // We can't get to this tile - so we'll make it a wall
-                if distance_to_start == std::f32::MAX {
+                // We can't get to this tile - so we'll make it a wall. The start itself only
+                // gets a distance if a neighbour leads back to it, so it is always kept.
+                if distance_to_start == std::f32::MAX && i != start_idx {
                     *tile = TileType::Wall;
                 }
             }
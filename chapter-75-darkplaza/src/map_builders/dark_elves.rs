@@ -1,38 +1,14 @@
-use super::{BuilderChain, XStart, YStart, AreaStartingPosition, 
-    CullUnreachable, VoronoiSpawning,
-    AreaEndingPosition, XEnd, YEnd, BspInteriorBuilder };
-
-pub fn dark_elf_city(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    println!("Dark elf builder");
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven City");
-    chain.start_with(BspInteriorBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::RIGHT, YStart::CENTER));
-    chain.with(AreaEndingPosition::new(XEnd::LEFT, YEnd::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain
-}
-
-pub fn dark_elf_plaza(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    println!("Dark elf plaza builder");
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dark Elven Plaza");
-    chain.start_with(PlazaMapBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain
-}
-
-// Plaza Builder
-use super::{InitialMapBuilder, BuilderMap, TileType };
+use super::{InitialMapBuilder, BuilderMap, TileType, wall_in_border };
+use rltk::RandomNumberGenerator;
 
 pub struct PlazaMapBuilder {}
 
 impl InitialMapBuilder for PlazaMapBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         self.empty_map(build_data);
-        self.spawn_zones(build_data);
+        self.spawn_zones(rng, build_data);
+        wall_in_border(build_data);
     }
 }
 
@@ -46,12 +22,12 @@ impl PlazaMapBuilder {
         build_data.map.tiles.iter_mut().for_each(|t| *t = TileType::Floor);
     }
 
-    fn spawn_zones(&mut self, build_data : &mut BuilderMap) {
+    fn spawn_zones(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let mut voronoi_seeds : Vec<(usize, rltk::Point)> = Vec::new();
 
         while voronoi_seeds.len() < 32 {
-            let vx = crate::rng::roll_dice(1, build_data.map.width-1);
-            let vy = crate::rng::roll_dice(1, build_data.map.height-1);
+            let vx = rng.roll_dice(1, build_data.map.width-1);
+            let vy = rng.roll_dice(1, build_data.map.height-1);
             let vidx = build_data.map.xy_idx(vx, vy);
             let candidate = (vidx, rltk::Point::new(vx, vy));
             if !voronoi_seeds.contains(&candidate) {
@@ -92,14 +68,14 @@ impl PlazaMapBuilder {
         zone_sizes.iter().enumerate().for_each(|(i, (zone, _))| {
             match i {
                 0 => self.portal_park(build_data, &voronoi_membership, *zone, &voronoi_seeds),
-                1 | 2 => self.park(build_data, &voronoi_membership, *zone, &voronoi_seeds),
+                1 | 2 => self.park(rng, build_data, &voronoi_membership, *zone, &voronoi_seeds),
                 i if i > 20 => self.fill_zone(build_data, &voronoi_membership, *zone, TileType::Wall),
                 _ => {
-                    let roll = crate::rng::roll_dice(1, 6);
+                    let roll = rng.roll_dice(1, 6);
                     match roll {
                         1 => self.fill_zone(build_data, &voronoi_membership, *zone, TileType::DeepWater),
                         2 => self.fill_zone(build_data, &voronoi_membership, *zone, TileType::ShallowWater),
-                        3 => self.stalactite_display(build_data, &voronoi_membership, *zone),
+                        3 => self.stalactite_display(rng, build_data, &voronoi_membership, *zone),
                         _ => {}
                     }
                 }
@@ -122,7 +98,7 @@ impl PlazaMapBuilder {
         zone_tiles.iter().for_each(|idx| build_data.map.tiles[*idx] = TileType::Gravel);
 
         // Add the exit
-        let center = seeds[zone as usize].1;
+        let center = zone_center(build_data, seeds[zone as usize].1);
         let idx = build_data.map.xy_idx(center.x, center.y);
         build_data.map.tiles[idx] = TileType::DownStairs;
 
@@ -147,13 +123,13 @@ impl PlazaMapBuilder {
             .for_each(|(idx, _)| build_data.map.tiles[idx] = tile_type);
     }
 
-    fn stalactite_display(&mut self, build_data : &mut BuilderMap, voronoi_membership: &[i32], zone: i32) {
+    fn stalactite_display(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, voronoi_membership: &[i32], zone: i32) {
         voronoi_membership
             .iter()
             .enumerate()
             .filter(|(_, tile_zone)| **tile_zone == zone)
             .for_each(|(idx, _)| {
-                build_data.map.tiles[idx] = match crate::rng::roll_dice(1,10) {
+                build_data.map.tiles[idx] = match rng.roll_dice(1,10) {
                     1 => TileType::Stalactite,
                     2 => TileType::Stalagmite,
                     _ => TileType::Grass,
@@ -161,7 +137,7 @@ impl PlazaMapBuilder {
             });
     }
 
-    fn park(&mut self, build_data : &mut BuilderMap, voronoi_membership: &[i32], zone: i32, seeds: &[(usize, rltk::Point)]) {
+    fn park(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, voronoi_membership: &[i32], zone: i32, seeds: &[(usize, rltk::Point)]) {
         let zone_tiles : Vec<usize> = voronoi_membership
             .iter()
             .enumerate()
@@ -173,12 +149,12 @@ impl PlazaMapBuilder {
         zone_tiles.iter().for_each(|idx| build_data.map.tiles[*idx] = TileType::Grass);
 
         // Add a stone area in the middle
-        let center = seeds[zone as usize].1;
+        let center = zone_center(build_data, seeds[zone as usize].1);
         for y in center.y-2 ..= center.y+2 {
             for x in center.x-2 ..= center.x+2 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Road;
-                if crate::rng::roll_dice(1,6) > 2 {
+                if rng.roll_dice(1,6) > 2 {
                     build_data.map.bloodstains.insert(idx);
                 }
             }
@@ -190,8 +166,13 @@ impl PlazaMapBuilder {
             "Altar".to_string()
         ));
 
+        // Lit by braziers at the corners
+        for (x, y) in [(center.x-2, center.y-2), (center.x+2, center.y-2), (center.x-2, center.y+2), (center.x+2, center.y+2)].iter() {
+            build_data.spawn_list.push((build_data.map.xy_idx(*x, *y), "Brazier".to_string()));
+        }
+
         // And chairs for spectators, and the spectators themselves
-        let available_enemies = match crate::rng::roll_dice(1, 3) {
+        let available_enemies = match rng.roll_dice(1, 3) {
             1 => vec![
                 "Arbat Dark Elf",
                 "Arbat Dark Elf Leader",
@@ -210,13 +191,13 @@ impl PlazaMapBuilder {
 
         zone_tiles.iter().for_each(|idx| {
             if build_data.map.tiles[*idx] == TileType::Grass {
-                    match crate::rng::roll_dice(1, 10) {
+                    match rng.roll_dice(1, 10) {
                     1 => build_data.spawn_list.push((
                             *idx,
                             "Chair".to_string()
                         )),
                     2 => {
-                        let to_spawn = crate::rng::range(0, available_enemies.len() as i32);
+                        let to_spawn = rng.range(0, available_enemies.len() as i32);
                         build_data.spawn_list.push((
                             *idx,
                             available_enemies[to_spawn as usize].to_string()
@@ -246,3 +227,12 @@ impl PlazaMapBuilder {
         }
     }
 }
+
+/// Where to put the middle of a zone's centrepiece: its seed, moved in far enough that
+/// everything within two tiles of it stays inside the map's border.
+fn zone_center(build_data : &BuilderMap, seed : rltk::Point) -> rltk::Point {
+    rltk::Point::new(
+        i32::max(3, i32::min(seed.x, build_data.map.width - 4)),
+        i32::max(3, i32::min(seed.y, build_data.map.height - 4))
+    )
+}
@@ -1,9 +1,10 @@
-use super::{MetaMapBuilder, BuilderMap, TileType};
+use super::{MetaMapBuilder, BuilderMap, TileType, dijkstra_limit};
+use rltk::RandomNumberGenerator;
 
 pub struct DistantExit {}
 
 impl MetaMapBuilder for DistantExit {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
@@ -22,7 +23,7 @@ impl DistantExit {
         );
         build_data.map.populate_blocked();
         let map_starts : Vec<usize> = vec![start_idx];
-        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts , &build_data.map, 3000.0);
+        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width as usize, build_data.map.height as usize, &map_starts , &build_data.map, dijkstra_limit(&build_data.map));
         let mut exit_tile = (0, 0.0f32);
         for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
             if *tile == TileType::Floor {
@@ -37,7 +38,17 @@ impl DistantExit {
             }
         }
 
-        // Place a staircase
+        if exit_tile.1 == 0.0 {
+            crate::console_log("DistantExit: nothing is reachable from the start, so no exit was placed");
+            return;
+        }
+
+        // Place a staircase, in place of any an earlier builder left behind
+        for tile in build_data.map.tiles.iter_mut() {
+            if *tile == TileType::DownStairs {
+                *tile = TileType::Floor;
+            }
+        }
         let stairs_idx = exit_tile.0;
         build_data.map.tiles[stairs_idx] = TileType::DownStairs;
         build_data.take_snapshot();
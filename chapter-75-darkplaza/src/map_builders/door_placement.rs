@@ -1,11 +1,12 @@
 use super::{MetaMapBuilder, BuilderMap, TileType };
+use rltk::RandomNumberGenerator;
 
 pub struct DoorPlacement {}
 
 impl MetaMapBuilder for DoorPlacement {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.doors(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.doors(rng, build_data);
     }
 }
 
@@ -48,7 +49,7 @@ impl DoorPlacement {
         false
     }
 
-    fn doors(&mut self, build_data : &mut BuilderMap) {
+    fn doors(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         if let Some(halls_original) = &build_data.corridors {
             let halls = halls_original.clone(); // To avoid nested borrowing
             for hall in halls.iter() {
@@ -62,7 +63,7 @@ impl DoorPlacement {
             // There are no corridors - scan for possible places
             let tiles = build_data.map.tiles.clone();
             for (i, tile) in tiles.iter().enumerate() {
-                if *tile == TileType::Floor && self.door_possible(build_data, i) && crate::rng::roll_dice(1,3)==1 {
+                if *tile == TileType::Floor && self.door_possible(build_data, i) && rng.roll_dice(1,3)==1 {
                     build_data.spawn_list.push((i, "Door".to_string()));
                 }
             }
@@ -1,4 +1,5 @@
 use super::{InitialMapBuilder, MetaMapBuilder, BuilderMap, TileType, Position, paint, Symmetry};
+use rltk::RandomNumberGenerator;
 
 #[derive(PartialEq, Copy, Clone)]
 #[allow(dead_code)]
@@ -18,15 +19,15 @@ pub struct DrunkardsWalkBuilder {
 
 impl InitialMapBuilder for DrunkardsWalkBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
 impl MetaMapBuilder for DrunkardsWalkBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
@@ -103,7 +104,7 @@ impl DrunkardsWalkBuilder {
         })
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         // Set a central starting point
         let starting_position = Position{ x: build_data.map.width / 2, y: build_data.map.height / 2 };
         let start_idx = build_data.map.xy_idx(starting_position.x, starting_position.y);
@@ -127,8 +128,8 @@ impl DrunkardsWalkBuilder {
                         drunk_x = starting_position.x;
                         drunk_y = starting_position.y;
                     } else {
-                        drunk_x = crate::rng::roll_dice(1, build_data.map.width - 3) + 1;
-                        drunk_y = crate::rng::roll_dice(1, build_data.map.height - 3) + 1;
+                        drunk_x = rng.roll_dice(1, build_data.map.width - 3) + 1;
+                        drunk_y = rng.roll_dice(1, build_data.map.height - 3) + 1;
                     }
                 }
             }
@@ -142,7 +143,7 @@ impl DrunkardsWalkBuilder {
                 paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, drunk_x, drunk_y);
                 build_data.map.tiles[drunk_idx] = TileType::DownStairs;
 
-                let stagger_direction = crate::rng::roll_dice(1, 4);
+                let stagger_direction = rng.roll_dice(1, 4);
                 match stagger_direction {
                     1 => { if drunk_x > 2 { drunk_x -= 1; } }
                     2 => { if drunk_x < build_data.map.width-2 { drunk_x += 1; } }
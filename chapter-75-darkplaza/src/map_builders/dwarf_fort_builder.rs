@@ -1,31 +1,11 @@
-use super::{BuilderChain, XStart, YStart, AreaStartingPosition, RoomSorter, RoomSort,
-    CullUnreachable, VoronoiSpawning, BspDungeonBuilder, DistantExit, BspCorridors,
-    CorridorSpawner, RoomDrawer, BuilderMap, MetaMapBuilder, DLABuilder, TileType,
-    AreaEndingPosition, XEnd, YEnd};
-
-pub fn dwarf_fort_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarven Fortress");
-    chain.start_with(BspDungeonBuilder::new());
-    chain.with(RoomSorter::new(RoomSort::CENTRAL));
-    chain.with(RoomDrawer::new());
-    chain.with(BspCorridors::new());
-    chain.with(CorridorSpawner::new());
-    chain.with(DragonsLair::new());
-
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::TOP));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::RIGHT, YEnd::BOTTOM));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(DragonSpawner::new());
-    chain
-}
+use super::{BuilderChain, BuilderMap, MetaMapBuilder, DLABuilder, TileType};
+use rltk::RandomNumberGenerator;
 
 pub struct DragonsLair {}
 
 impl MetaMapBuilder for DragonsLair {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -35,13 +15,13 @@ impl DragonsLair {
         Box::new(DragonsLair{})
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         build_data.map.depth = 7;
         build_data.take_snapshot();
 
-        let mut builder = BuilderChain::new(6, build_data.width, build_data.height, "New Map");
+        let mut builder = BuilderChain::new(&build_data.raws, 6, build_data.width, build_data.height, "New Map");
         builder.start_with(DLABuilder::insectoid());
-        builder.build_map();
+        builder.build_map(rng);
 
         // Add the history to our history
         for h in builder.build_data.history.iter() {
@@ -62,7 +42,7 @@ impl DragonsLair {
 pub struct DragonSpawner {}
 
 impl MetaMapBuilder for DragonSpawner {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
@@ -79,7 +59,7 @@ impl DragonSpawner {
         let seed_y = build_data.map.height / 2;
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if crate::map::tile_walkable(*tiletype) {
+            if build_data.map.tile_set.walkable(*tiletype) {
                 available_floors.push(
                     (
                         idx,
@@ -1,23 +1,11 @@
-use super::{BuilderChain, CellularAutomataBuilder, XStart, YStart, AreaStartingPosition,
-    CullUnreachable, VoronoiSpawning, MetaMapBuilder, BuilderMap, TileType};
-use crate::map;
-
-pub fn forest_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Into the Woods");
-    chain.start_with(CellularAutomataBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(YellowBrickRoad::new());
-    chain
-}
+use super::{MetaMapBuilder, BuilderMap, TileType};
+use rltk::RandomNumberGenerator;
 
 pub struct YellowBrickRoad {}
 
 impl MetaMapBuilder for YellowBrickRoad {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -30,7 +18,7 @@ impl YellowBrickRoad {
     fn find_exit(&self, build_data : &mut BuilderMap, seed_x : i32, seed_y: i32) -> (i32, i32) {
         let mut available_floors : Vec<(usize, f32)> = Vec::new();
         for (idx, tiletype) in build_data.map.tiles.iter().enumerate() {
-            if map::tile_walkable(*tiletype) {
+            if build_data.map.tile_set.walkable(*tiletype) {
                 available_floors.push(
                     (
                         idx,
@@ -63,7 +51,7 @@ impl YellowBrickRoad {
         }
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
         let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
 
@@ -88,7 +76,7 @@ impl YellowBrickRoad {
         build_data.take_snapshot();
 
         // Place exit
-        let exit_dir = crate::rng::roll_dice(1, 2);
+        let exit_dir = rng.roll_dice(1, 2);
         let (seed_x, seed_y, stream_startx, stream_starty) = if exit_dir == 1 {
             (build_data.map.width-1, 1, 0, build_data.height-1)
         } else {
@@ -0,0 +1,149 @@
+use super::*;
+use crate::raws::{LevelChain, BuilderStep};
+use super::forest::YellowBrickRoad;
+use super::town::TownBuilder;
+
+/// Turns a level definition from `levels.json` into a builder chain. The definition's own
+/// size, if it has one, takes precedence over the one passed in.
+pub fn chain_from_raws(raws : &Arc<RawMaster>, level : &LevelChain, new_depth: i32, width: i32, height: i32) -> BuilderChain {
+    let width = level.width.unwrap_or(width);
+    let height = level.height.unwrap_or(height);
+    let mut chain = BuilderChain::new(raws, new_depth, width, height, &level.name);
+    chain.build_data.map.theme = level.theme.clone();
+    chain.build_data.map.theme_regions = level.theme_regions.clone();
+    chain.start_with(initial_builder(&level.starter));
+    for step in level.builders.iter() {
+        if let Some(builder) = meta_builder(step) {
+            chain.with(builder);
+        } else {
+            crate::console_log(format!("WARNING - Unknown meta builder [{}] on level {}", step.builder, level.depth));
+        }
+    }
+    chain
+}
+
+/// The builder a level starts with. `PrefabLevel` takes either a REX Paint `template`
+/// (an embedded resource path) or the name of a hand-drawn `level`.
+fn initial_builder(step : &BuilderStep) -> Box<dyn InitialMapBuilder> {
+    match step.builder.as_str() {
+        "SimpleMap" => SimpleMapBuilder::new(),
+        "BspDungeon" => BspDungeonBuilder::new(),
+        "BspInterior" => BspInteriorBuilder::new(),
+        "CellularAutomata" => CellularAutomataBuilder::new(),
+        "Maze" => MazeBuilder::new(),
+        "Town" => TownBuilder::new(),
+        "Plaza" => PlazaMapBuilder::new(),
+        "DrunkardsWalk" => match step.param("mode") {
+            Some("open_area") => DrunkardsWalkBuilder::open_area(),
+            Some("open_halls") => DrunkardsWalkBuilder::open_halls(),
+            Some("fat_passages") => DrunkardsWalkBuilder::fat_passages(),
+            Some("fearful_symmetry") => DrunkardsWalkBuilder::fearful_symmetry(),
+            _ => DrunkardsWalkBuilder::winding_passages()
+        }
+        "DLA" => match step.param("mode") {
+            Some("walk_inwards") => DLABuilder::walk_inwards(),
+            Some("walk_outwards") => DLABuilder::walk_outwards(),
+            Some("heavy_erosion") => DLABuilder::heavy_erosion(),
+            Some("insectoid") => DLABuilder::insectoid(),
+            _ => DLABuilder::central_attractor()
+        }
+        "Voronoi" => match step.param("mode") {
+            Some("manhattan") => VoronoiCellBuilder::manhattan(),
+            _ => VoronoiCellBuilder::pythagoras()
+        }
+        "PrefabLevel" => {
+            use super::prefab_builder::prefab_levels;
+            match (step.param("template"), step.param("level")) {
+                (Some(template), _) => PrefabBuilder::rex_level(template),
+                (None, Some(name)) => match prefab_levels::named(name) {
+                    Some(level) => PrefabBuilder::constant(level),
+                    None => {
+                        crate::console_log(format!("WARNING - No prefab level named {}", name));
+                        PrefabBuilder::constant(prefab_levels::WFC_POPULATED)
+                    }
+                }
+                (None, None) => PrefabBuilder::constant(prefab_levels::WFC_POPULATED)
+            }
+        }
+        _ => {
+            // Cellular automata make a whole map on their own, whatever follows in the chain
+            crate::console_log(format!("WARNING - Unknown starting builder [{}], using CellularAutomata", step.builder));
+            CellularAutomataBuilder::new()
+        }
+    }
+}
+
+fn meta_builder(step : &BuilderStep) -> Option<Box<dyn MetaMapBuilder>> {
+    let builder : Box<dyn MetaMapBuilder> = match step.builder.as_str() {
+        "AreaStartingPosition" => {
+            let x = match step.param("x") { Some("left") => XStart::LEFT, Some("right") => XStart::RIGHT, _ => XStart::CENTER };
+            let y = match step.param("y") { Some("top") => YStart::TOP, Some("bottom") => YStart::BOTTOM, _ => YStart::CENTER };
+            AreaStartingPosition::new(x, y)
+        }
+        "AreaEndingPosition" => {
+            let x = match step.param("x") { Some("left") => XEnd::LEFT, Some("right") => XEnd::RIGHT, _ => XEnd::CENTER };
+            let y = match step.param("y") { Some("top") => YEnd::TOP, Some("bottom") => YEnd::BOTTOM, _ => YEnd::CENTER };
+            AreaEndingPosition::new(x, y)
+        }
+        "RoomSorter" => RoomSorter::new(match step.param("sort") {
+            Some("leftmost") => RoomSort::LEFTMOST,
+            Some("rightmost") => RoomSort::RIGHTMOST,
+            Some("topmost") => RoomSort::TOPMOST,
+            Some("bottommost") => RoomSort::BOTTOMMOST,
+            _ => RoomSort::CENTRAL
+        }),
+        "PrefabSection" => {
+            use super::prefab_builder::prefab_sections::*;
+            PrefabBuilder::sectional(match step.param("section") {
+                Some("UNDERGROUND_FORT") => UNDERGROUND_FORT,
+                Some("ORC_CAMP") => ORC_CAMP,
+                Some("DROW_ENTRY") => DROW_ENTRY,
+                Some(name) => return Some(PrefabBuilder::library_section(name)),
+                None => {
+                    crate::console_log("WARNING - PrefabSection needs a section name");
+                    return None;
+                }
+            })
+        }
+        "PrefabVaults" => PrefabBuilder::vaults(),
+        "CullUnreachable" => CullUnreachable::new(),
+        "VoronoiSpawning" => VoronoiSpawning::new(),
+        "DistantExit" => DistantExit::new(),
+        "WaveformCollapse" => {
+            let mut settings = WaveformSettings::default();
+            if let Some(sample) = step.param("sample") {
+                settings.sample = WaveformSample::RexFile(sample.to_string());
+            }
+            if let Some(retries) = step.param("retries").and_then(|n| n.parse().ok()) {
+                settings.max_retries = retries;
+            }
+            if let Some(backtracks) = step.param("backtracks").and_then(|n| n.parse().ok()) {
+                settings.max_backtracks = backtracks;
+            }
+            if let Some(ms) = step.param("time_ms").and_then(|n| n.parse().ok()) {
+                settings.time_budget = Some(std::time::Duration::from_millis(ms));
+            }
+            WaveformCollapseBuilder::with_settings(settings)
+        }
+        "RoomDrawer" => RoomDrawer::new(),
+        "RoomExploder" => RoomExploder::new(),
+        "RoomCornerRounder" => RoomCornerRounder::new(),
+        "DoglegCorridors" => DoglegCorridors::new(),
+        "BspCorridors" => BspCorridors::new(),
+        "NearestCorridors" => NearestCorridors::new(),
+        "StraightLineCorridors" => StraightLineCorridors::new(),
+        "CorridorSpawner" => CorridorSpawner::new(),
+        "RoomBasedSpawner" => RoomBasedSpawner::new(),
+        "RoomBasedStartingPosition" => RoomBasedStartingPosition::new(),
+        "RoomBasedStairs" => RoomBasedStairs::new(),
+        "DoorPlacement" => DoorPlacement::new(),
+        "CaveDecorator" => CaveDecorator::new(),
+        "CaveTransition" => CaveTransition::new(),
+        "YellowBrickRoad" => YellowBrickRoad::new(),
+        "DragonsLair" => DragonsLair::new(),
+        "DragonSpawner" => DragonSpawner::new(),
+        "MapValidator" => MapValidator::new(),
+        _ => return None
+    };
+    Some(builder)
+}
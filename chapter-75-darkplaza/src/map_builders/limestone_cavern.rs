@@ -1,52 +1,12 @@
-use super::{BuilderChain, DrunkardsWalkBuilder, XStart, YStart, AreaStartingPosition,
-    CullUnreachable, VoronoiSpawning, MetaMapBuilder, BuilderMap, TileType, DistantExit,
-    DLABuilder, PrefabBuilder, CellularAutomataBuilder, AreaEndingPosition,
-    BspDungeonBuilder, RoomSorter, RoomSort, NearestCorridors, RoomExploder, RoomDrawer,
-    RoomBasedSpawner, XEnd, YEnd};
-
-pub fn limestone_cavern_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Limestone Caverns");
-    chain.start_with(DrunkardsWalkBuilder::winding_passages());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(CaveDecorator::new());
-    chain
-}
-
-pub fn limestone_deep_cavern_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Deep Limestone Caverns");
-    chain.start_with(DLABuilder::central_attractor());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::TOP));
-    chain.with(VoronoiSpawning::new());
-    chain.with(DistantExit::new());
-    chain.with(CaveDecorator::new());
-    chain.with(PrefabBuilder::sectional(super::prefab_builder::prefab_sections::ORC_CAMP));
-    chain
-}
-
-pub fn limestone_transition_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "Dwarf Fort - Upper Reaches");
-    chain.start_with(CellularAutomataBuilder::new());
-    chain.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(VoronoiSpawning::new());
-    chain.with(CaveDecorator::new());
-    chain.with(CaveTransition::new());
-    chain.with(AreaStartingPosition::new(XStart::LEFT, YStart::CENTER));
-    chain.with(CullUnreachable::new());
-    chain.with(AreaEndingPosition::new(XEnd::RIGHT, YEnd::CENTER));
-    chain
-}
+use super::{BuilderChain, MetaMapBuilder, BuilderMap, TileType, BspDungeonBuilder, RoomSorter,
+    RoomSort, NearestCorridors, RoomExploder, RoomDrawer, RoomBasedSpawner};
+use rltk::RandomNumberGenerator;
 
 pub struct CaveDecorator {}
 
 impl MetaMapBuilder for CaveDecorator {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -56,13 +16,13 @@ impl CaveDecorator {
         Box::new(CaveDecorator{})
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let old_map = build_data.map.clone();
         for (idx,tt) in build_data.map.tiles.iter_mut().enumerate() {
             // Gravel Spawning
-            if *tt == TileType::Floor && crate::rng::roll_dice(1, 6)==1 {
+            if *tt == TileType::Floor && rng.roll_dice(1, 6)==1 {
                 *tt = TileType::Gravel;
-            } else if *tt == TileType::Floor && crate::rng::roll_dice(1, 10)==1 {
+            } else if *tt == TileType::Floor && rng.roll_dice(1, 10)==1 {
                 // Spawn passable pools
                 *tt = TileType::ShallowWater;
             } else if *tt == TileType::Wall {
@@ -77,7 +37,7 @@ impl CaveDecorator {
                 if neighbors == 2 {
                     *tt = TileType::DeepWater;
                 } else if neighbors == 1 {
-                    let roll = crate::rng::roll_dice(1, 4);
+                    let roll = rng.roll_dice(1, 4);
                     match roll {
                         1 => *tt = TileType::Stalactite,
                         2 => *tt = TileType::Stalagmite,
@@ -94,8 +54,8 @@ impl CaveDecorator {
 pub struct CaveTransition {}
 
 impl MetaMapBuilder for CaveTransition {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -105,19 +65,19 @@ impl CaveTransition {
         Box::new(CaveTransition{})
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         build_data.map.depth = 5;
         build_data.take_snapshot();
 
         // Build a BSP-based dungeon
-        let mut builder = BuilderChain::new(5, build_data.width, build_data.height, "New Map");
+        let mut builder = BuilderChain::new(&build_data.raws, 5, build_data.width, build_data.height, "New Map");
         builder.start_with(BspDungeonBuilder::new());
         builder.with(RoomDrawer::new());
         builder.with(RoomSorter::new(RoomSort::RIGHTMOST));
         builder.with(NearestCorridors::new());
         builder.with(RoomExploder::new());
         builder.with(RoomBasedSpawner::new());
-        builder.build_map();
+        builder.build_map(rng);
 
         // Add the history to our history
         for h in builder.build_data.history.iter() {
@@ -137,13 +97,13 @@ impl CaveTransition {
         // Keep Voronoi spawn data from the left half of the map
         let w = build_data.map.width;
         build_data.spawn_list.retain(|s| {
-            let x = s.0 as i32 / w;
+            let x = s.0 as i32 % w;
             x < w / 2
         });
 
         // Keep room spawn data from the right half of the map
         for s in builder.build_data.spawn_list.iter() {
-            let x = s.0 as i32 / w;
+            let x = s.0 as i32 % w;
             if x > w / 2 {
                 build_data.spawn_list.push(s.clone());
             }
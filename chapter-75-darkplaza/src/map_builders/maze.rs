@@ -1,11 +1,12 @@
 use super::{Map,  InitialMapBuilder, BuilderMap, TileType};
+use rltk::RandomNumberGenerator;
 
 pub struct MazeBuilder {}
 
 impl InitialMapBuilder for MazeBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
@@ -16,10 +17,10 @@ impl MazeBuilder {
     }
 
     #[allow(clippy::map_entry)]
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         // Maze gen
         let mut maze = Grid::new((build_data.map.width / 2)-2, (build_data.map.height / 2)-2);
-        maze.generate_maze(build_data);
+        maze.generate_maze(rng, build_data);
     }
 }
 
@@ -128,23 +129,23 @@ impl Grid {
         neighbors
     }
 
-    fn find_next_cell(&mut self) -> Option<usize> {
+    fn find_next_cell(&mut self, rng : &mut RandomNumberGenerator) -> Option<usize> {
         let neighbors = self.get_available_neighbors();
         if !neighbors.is_empty() {
             if neighbors.len() == 1 {
                 return Some(neighbors[0]);
             } else {
-                return Some(neighbors[(crate::rng::roll_dice(1, neighbors.len() as i32)-1) as usize]);
+                return Some(neighbors[(rng.roll_dice(1, neighbors.len() as i32)-1) as usize]);
             }
         }
         None
     }
 
-    fn generate_maze(&mut self, build_data : &mut BuilderMap) {
+    fn generate_maze(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let mut i = 0;
         loop {
             self.cells[self.current].visited = true;
-            let next = self.find_next_cell();
+            let next = self.find_next_cell(rng);
 
             match next {
                 Some(next) => {
@@ -1,5 +1,8 @@
-use super::{Map, Rect, TileType, Position, spawner, SHOW_MAPGEN_VISUALIZER};
+use super::{Map, Rect, TileType, Position, LevelKey, spawner, SHOW_MAPGEN_VISUALIZER};
+use crate::raws::RawMaster;
 use specs::prelude::*;
+use rltk::RandomNumberGenerator;
+use std::sync::Arc;
 mod simple_map;
 mod bsp_dungeon;
 mod bsp_interior;
@@ -33,9 +36,8 @@ mod forest;
 mod limestone_cavern;
 mod dwarf_fort_builder;
 mod area_ending_point;
-use forest::forest_builder;
-use limestone_cavern::*;
-use dwarf_fort_builder::*;
+use limestone_cavern::{CaveDecorator, CaveTransition};
+use dwarf_fort_builder::{DragonsLair, DragonSpawner};
 use distant_exit::DistantExit;
 use simple_map::SimpleMapBuilder;
 use bsp_dungeon::BspDungeonBuilder;
@@ -43,8 +45,10 @@ use bsp_interior::BspInteriorBuilder;
 use cellular_automata::CellularAutomataBuilder;
 use drunkard::DrunkardsWalkBuilder;
 use voronoi::VoronoiCellBuilder;
-use waveform_collapse::WaveformCollapseBuilder;
+use waveform_collapse::{WaveformCollapseBuilder, WaveformSettings, WaveformSample};
 use prefab_builder::PrefabBuilder;
+pub use prefab_builder::vault_library::{load_vaults, Vault, VaultLibrary};
+pub(crate) use prefab_builder::vault_library::link_vault_files;
 use room_based_spawner::RoomBasedSpawner;
 use room_based_starting_position::RoomBasedStartingPosition;
 use room_based_stairs::RoomBasedStairs;
@@ -64,12 +68,19 @@ use rooms_corridors_nearest::NearestCorridors;
 use rooms_corridors_lines::StraightLineCorridors;
 use room_corridor_spawner::CorridorSpawner;
 use door_placement::DoorPlacement;
-use town::town_builder;
 use area_ending_point::*;
-mod mushroom_forest;
-use mushroom_forest::*;
 mod dark_elves;
-use dark_elves::*;
+use dark_elves::PlazaMapBuilder;
+mod level_chains;
+pub use level_chains::chain_from_raws;
+mod validator;
+mod branch_entrance;
+use branch_entrance::BranchEntrance;
+pub use validator::{validate_map, MapValidationError, MapValidator};
+
+/// The size of a level whose chain in `levels.json` doesn't give one.
+pub const DEFAULT_MAP_WIDTH : i32 = 80;
+pub const DEFAULT_MAP_HEIGHT : i32 = 50;
 
 pub struct BuilderMap {
     pub spawn_list : Vec<(usize, String)>,
@@ -79,7 +90,9 @@ pub struct BuilderMap {
     pub corridors: Option<Vec<Vec<usize>>>,
     pub history : Vec<Map>,
     pub width: i32,
-    pub height: i32
+    pub height: i32,
+    pub validation_errors : Vec<MapValidationError>,
+    pub raws : Arc<RawMaster>
 }
 
 impl BuilderMap {
@@ -101,19 +114,21 @@ pub struct BuilderChain {
 }
 
 impl BuilderChain {
-    pub fn new<S : ToString>(new_depth : i32, width: i32, height: i32, name : S) -> BuilderChain {
+    pub fn new<S : ToString>(raws : &Arc<RawMaster>, new_depth : i32, width: i32, height: i32, name : S) -> BuilderChain {
         BuilderChain{
             starter: None,
             builders: Vec::new(),
             build_data : BuilderMap {
                 spawn_list: Vec::new(),
-                map: Map::new(new_depth, width, height, name),
+                map: Map::new(new_depth, width, height, name, &raws.tiles),
                 starting_position: None,
                 rooms: None,
                 corridors: None,
                 history : Vec::new(),
                 width,
-                height
+                height,
+                validation_errors : Vec::new(),
+                raws : raws.clone()
             }
         }
     }
@@ -129,18 +144,18 @@ impl BuilderChain {
         self.builders.push(metabuilder);
     }
 
-    pub fn build_map(&mut self) {
+    pub fn build_map(&mut self, rng : &mut RandomNumberGenerator) {
         match &mut self.starter {
             None => panic!("Cannot run a map builder chain without a starting build system"),
             Some(starter) => {
                 // Build the starting map
-                starter.build_map(&mut self.build_data);
+                starter.build_map(rng, &mut self.build_data);
             }
         }
 
         // Build additional layers in turn
         for metabuilder in self.builders.iter_mut() {
-            metabuilder.build_map(&mut self.build_data);
+            metabuilder.build_map(rng, &mut self.build_data);
         }
     }
 
@@ -152,16 +167,16 @@ impl BuilderChain {
 }
 
 pub trait InitialMapBuilder {
-    fn build_map(&mut self, build_data : &mut BuilderMap);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap);
 }
 
 pub trait MetaMapBuilder {
-    fn build_map(&mut self, build_data : &mut BuilderMap);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap);
 }
 
-fn random_start_position() -> (XStart, YStart) {
+fn random_start_position(rng : &mut RandomNumberGenerator) -> (XStart, YStart) {
     let x;
-    let xroll = crate::rng::roll_dice(1, 3);
+    let xroll = rng.roll_dice(1, 3);
     match xroll {
         1 => x = XStart::LEFT,
         2 => x = XStart::CENTER,
@@ -169,7 +184,7 @@ fn random_start_position() -> (XStart, YStart) {
     }
 
     let y;
-    let yroll = crate::rng::roll_dice(1, 3);
+    let yroll = rng.roll_dice(1, 3);
     match yroll {
         1 => y = YStart::BOTTOM,
         2 => y = YStart::CENTER,
@@ -179,8 +194,8 @@ fn random_start_position() -> (XStart, YStart) {
     (x, y)
 }
 
-fn random_room_builder(builder : &mut BuilderChain) {
-    let build_roll = crate::rng::roll_dice(1, 3);
+fn random_room_builder(rng : &mut RandomNumberGenerator, builder : &mut BuilderChain) {
+    let build_roll = rng.roll_dice(1, 3);
     match build_roll {
         1 => builder.start_with(SimpleMapBuilder::new()),
         2 => builder.start_with(BspDungeonBuilder::new()),
@@ -190,7 +205,7 @@ fn random_room_builder(builder : &mut BuilderChain) {
     // BSP Interior still makes holes in the walls
     if build_roll != 3 {
         // Sort by one of the 5 available algorithms
-        let sort_roll = crate::rng::roll_dice(1, 5);
+        let sort_roll = rng.roll_dice(1, 5);
         match sort_roll {
             1 => builder.with(RoomSorter::new(RoomSort::LEFTMOST)),
             2 => builder.with(RoomSorter::new(RoomSort::RIGHTMOST)),
@@ -201,7 +216,7 @@ fn random_room_builder(builder : &mut BuilderChain) {
 
         builder.with(RoomDrawer::new());
 
-        let corridor_roll = crate::rng::roll_dice(1, 4);
+        let corridor_roll = rng.roll_dice(1, 4);
         match corridor_roll {
             1 => builder.with(DoglegCorridors::new()),
             2 => builder.with(NearestCorridors::new()),
@@ -209,12 +224,12 @@ fn random_room_builder(builder : &mut BuilderChain) {
             _ => builder.with(BspCorridors::new())
         }
 
-        let cspawn_roll = crate::rng::roll_dice(1, 2);
+        let cspawn_roll = rng.roll_dice(1, 2);
         if cspawn_roll == 1 {
             builder.with(CorridorSpawner::new());
         }
 
-        let modifier_roll = crate::rng::roll_dice(1, 6);
+        let modifier_roll = rng.roll_dice(1, 6);
         match modifier_roll {
             1 => builder.with(RoomExploder::new()),
             2 => builder.with(RoomCornerRounder::new()),
@@ -222,30 +237,30 @@ fn random_room_builder(builder : &mut BuilderChain) {
         }
     }
 
-    let start_roll = crate::rng::roll_dice(1, 2);
+    let start_roll = rng.roll_dice(1, 2);
     match start_roll {
         1 => builder.with(RoomBasedStartingPosition::new()),
         _ => {
-            let (start_x, start_y) = random_start_position();
+            let (start_x, start_y) = random_start_position(rng);
             builder.with(AreaStartingPosition::new(start_x, start_y));
         }
     }
 
-    let exit_roll = crate::rng::roll_dice(1, 2);
+    let exit_roll = rng.roll_dice(1, 2);
     match exit_roll {
         1 => builder.with(RoomBasedStairs::new()),
         _ => builder.with(DistantExit::new())
     }
 
-    let spawn_roll = crate::rng::roll_dice(1, 2);
+    let spawn_roll = rng.roll_dice(1, 2);
     match spawn_roll {
         1 => builder.with(RoomBasedSpawner::new()),
         _ => builder.with(VoronoiSpawning::new())
     }
 }
 
-fn random_shape_builder(builder : &mut BuilderChain) {
-    let builder_roll = crate::rng::roll_dice(1, 16);
+fn random_shape_builder(rng : &mut RandomNumberGenerator, builder : &mut BuilderChain) {
+    let builder_roll = rng.roll_dice(1, 16);
     match builder_roll {
         1 => builder.start_with(CellularAutomataBuilder::new()),
         2 => builder.start_with(DrunkardsWalkBuilder::open_area()),
@@ -268,7 +283,7 @@ fn random_shape_builder(builder : &mut BuilderChain) {
     builder.with(CullUnreachable::new());
 
     // Now set the start to a random starting area
-    let (start_x, start_y) = random_start_position();
+    let (start_x, start_y) = random_start_position(rng);
     builder.with(AreaStartingPosition::new(start_x, start_y));
 
     // Setup an exit and spawn mobs
@@ -276,19 +291,23 @@ fn random_shape_builder(builder : &mut BuilderChain) {
     builder.with(DistantExit::new());
 }
 
-pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut builder = BuilderChain::new(new_depth, width, height, "New Map");
-    let type_roll = crate::rng::roll_dice(1, 2);
+pub fn random_builder(raws : &Arc<RawMaster>, rng : &mut RandomNumberGenerator, new_depth: i32, width: i32, height: i32) -> BuilderChain {
+    let mut builder = BuilderChain::new(raws, new_depth, width, height, "New Map");
+    let type_roll = rng.roll_dice(1, 2);
     match type_roll {
-        1 => random_room_builder(&mut builder),
-        _ => random_shape_builder(&mut builder)
+        1 => random_room_builder(rng, &mut builder),
+        _ => random_shape_builder(rng, &mut builder)
     }
 
-    if crate::rng::roll_dice(1, 3)==1 {
+    if rng.roll_dice(1, 3)==1 {
         builder.with(WaveformCollapseBuilder::new());
 
+        // The collapse can leave pockets cut off from the rest, so cull from the center
+        builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
+        builder.with(CullUnreachable::new());
+
         // Now set the start to a random starting area
-        let (start_x, start_y) = random_start_position();
+        let (start_x, start_y) = random_start_position(rng);
         builder.with(AreaStartingPosition::new(start_x, start_y));
 
         // Setup an exit and spawn mobs
@@ -296,8 +315,10 @@ pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
         builder.with(DistantExit::new());
     }
 
-    if crate::rng::roll_dice(1, 20)==1 {
+    if rng.roll_dice(1, 20)==1 {
         builder.with(PrefabBuilder::sectional(prefab_builder::prefab_sections::UNDERGROUND_FORT));
+        // The fort may be built over the exit
+        builder.with(DistantExit::new());
     }
 
     builder.with(DoorPlacement::new());
@@ -306,20 +327,62 @@ pub fn random_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
     builder
 }
 
-pub fn level_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    rltk::console::log(format!("Depth: {}", new_depth));
-    match new_depth {
-        1 => town_builder(new_depth, width, height),
-        2 => forest_builder(new_depth, width, height),
-        3 => limestone_cavern_builder(new_depth, width, height),
-        4 => limestone_deep_cavern_builder(new_depth, width, height),
-        5 => limestone_transition_builder(new_depth, width, height),
-        6 => dwarf_fort_builder(new_depth, width, height),
-        7 => mushroom_entrance(new_depth, width, height),
-        8 => mushroom_builder(new_depth, width, height),
-        9 => mushroom_exit(new_depth, width, height),
-        10 => dark_elf_city(new_depth, width, height),
-        11 => dark_elf_plaza(new_depth, width, height),
-        _ => random_builder(new_depth, width, height)
+/// How many times `build_level` tries for a map that passes `validate_map` before
+/// settling for the last one it made. What's still wrong with that one is logged, and left
+/// in its `validation_errors`.
+pub const MAX_BUILD_ATTEMPTS : usize = 20;
+
+/// Builds the map for `level` of the run identified by `run_seed`, including the up
+/// staircase at the starting position and any branch entrances. The same seed and level
+/// always give the same map. A map that fails validation is thrown away and built again,
+/// carrying on with the same random number generator so the retries are repeatable too.
+pub fn build_level(raws: &Arc<RawMaster>, run_seed: u64, level: &LevelKey, width: i32, height: i32) -> BuilderChain {
+    let mut rng = RandomNumberGenerator::seeded(crate::rng::level_seed(run_seed, &level.branch, level.depth));
+    let branch = crate::raws::get_branch(raws, &level.branch);
+    let branches_here = crate::raws::get_branches_from(raws, level);
+
+    let mut attempt = 1;
+    let mut builder = loop {
+        let mut builder = level_builder(raws, &mut rng, level, width, height);
+        builder.build_data.map.branch = level.branch.clone();
+        for b in branches_here.iter() {
+            builder.with(BranchEntrance::new(&b.name));
+        }
+        builder.with(MapValidator::new());
+        builder.build_map(&mut rng);
+
+        if builder.build_data.validation_errors.is_empty() || attempt == MAX_BUILD_ATTEMPTS {
+            break builder;
+        }
+        crate::console_log(format!("Rebuilding {} depth {} (attempt {})", level.branch, level.depth, attempt + 1));
+        attempt += 1;
+    };
+    for e in builder.build_data.validation_errors.iter() {
+        crate::console_log(format!("WARNING - {} depth {} still fails validation after {} attempts: {}", level.branch, level.depth, MAX_BUILD_ATTEMPTS, e));
+    }
+
+    if level.depth > 1 || !level.is_main() {
+        if let Some(pos) = &builder.build_data.starting_position {
+            let up_idx = builder.build_data.map.xy_idx(pos.x, pos.y);
+            builder.build_data.map.tiles[up_idx] = TileType::UpStairs;
+        }
+    }
+
+    // Branches end, so the bottom level has no way further down
+    if branch.map(|b| b.last_depth == level.depth).unwrap_or(false) {
+        for tile in builder.build_data.map.tiles.iter_mut() {
+            if *tile == TileType::DownStairs {
+                *tile = TileType::Floor;
+            }
+        }
+    }
+    builder
+}
+
+pub fn level_builder(raws: &Arc<RawMaster>, rng: &mut RandomNumberGenerator, level: &LevelKey, width: i32, height: i32) -> BuilderChain {
+    crate::console_log(format!("Depth: {} ({})", level.depth, level.branch));
+    match crate::raws::get_level_chain(raws, level) {
+        Some(chain) => chain_from_raws(raws, &chain, level.depth, width, height),
+        None => random_builder(raws, rng, level.depth, width, height)
     }
 }
@@ -1,15 +1,18 @@
 use super::{InitialMapBuilder, MetaMapBuilder, BuilderMap, TileType, Position};
+use rltk::RandomNumberGenerator;
 pub mod prefab_levels;
 pub mod prefab_sections;
-pub mod prefab_rooms;
-use std::collections::HashSet;
+pub mod vault_library;
+use vault_library::Vault;
+use std::collections::{HashMap, HashSet};
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Clone)]
 #[allow(dead_code)]
 pub enum PrefabMode {
-    RexLevel{ template : &'static str },
+    RexLevel{ template : String },
     Constant{ level : prefab_levels::PrefabLevel },
     Sectional{ section : prefab_sections::PrefabSection },
+    LibrarySection{ name : String },
     RoomVaults
 }
 
@@ -19,15 +22,15 @@ pub struct PrefabBuilder {
 }
 
 impl MetaMapBuilder for PrefabBuilder {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
 impl InitialMapBuilder for PrefabBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
@@ -40,9 +43,9 @@ impl PrefabBuilder {
     }
 
     #[allow(dead_code)]
-    pub fn rex_level(template : &'static str) -> Box<PrefabBuilder> {
+    pub fn rex_level(template : &str) -> Box<PrefabBuilder> {
         Box::new(PrefabBuilder{
-            mode : PrefabMode::RexLevel{ template },
+            mode : PrefabMode::RexLevel{ template : template.to_string() },
         })
     }
 
@@ -60,6 +63,14 @@ impl PrefabBuilder {
         })
     }
 
+    /// Places a section from the vault library, by name.
+    #[allow(dead_code)]
+    pub fn library_section(name : &str) -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder{
+            mode : PrefabMode::LibrarySection{ name : name.to_string() },
+        })
+    }
+
     #[allow(dead_code)]
     pub fn vaults() -> Box<PrefabBuilder> {
         Box::new(PrefabBuilder{
@@ -67,12 +78,13 @@ impl PrefabBuilder {
         })
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
-        match self.mode {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        match self.mode.clone() {
             PrefabMode::RexLevel{template} => self.load_rex_map(&template, build_data),
             PrefabMode::Constant{level} => self.load_ascii_map(&level, build_data),
             PrefabMode::Sectional{section} => self.apply_sectional(&section, build_data),
-            PrefabMode::RoomVaults => self.apply_room_vaults(build_data)
+            PrefabMode::LibrarySection{name} => self.apply_library_section(&name, build_data),
+            PrefabMode::RoomVaults => self.apply_room_vaults(rng, build_data)
         }
         build_data.take_snapshot();
     }
@@ -83,7 +95,7 @@ impl PrefabBuilder {
             return;
         }
         match ch {
-            ' ' => build_data.map.tiles[idx] = TileType::Floor,
+            ' ' | '.' => build_data.map.tiles[idx] = TileType::Floor,
             '#' => build_data.map.tiles[idx] = TileType::Wall,
             '≈' => build_data.map.tiles[idx] = TileType::DeepWater,
             '@' => {
@@ -126,14 +138,37 @@ impl PrefabBuilder {
                 build_data.spawn_list.push((idx, "Watch Fire".to_string()));
             }
             _ => {
-                rltk::console::log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
+                crate::console_log(format!("Unknown glyph loading map: {}", (ch as u8) as char));
+            }
+        }
+    }
+
+    /// Like `char_to_map`, but glyphs in the vault's legend spawn the named entity first.
+    fn glyph_to_map(&mut self, ch : char, legend : &HashMap<char, String>, idx: usize, build_data : &mut BuilderMap) {
+        if let Some(name) = legend.get(&ch) {
+            if idx < build_data.map.tiles.len() {
+                // The legend can name a tile from the raws as well as something to spawn
+                if let Some(tile) = build_data.map.tile_set.named(name) {
+                    build_data.map.tiles[idx] = tile;
+                } else {
+                    build_data.map.tiles[idx] = TileType::Floor;
+                    build_data.spawn_list.push((idx, name.clone()));
+                }
             }
+        } else {
+            self.char_to_map(ch, idx, build_data);
         }
     }
 
     #[allow(dead_code)]
     fn load_rex_map(&mut self, path: &str, build_data : &mut BuilderMap) {
-        let xp_file = rltk::rex::XpFile::from_resource(path).unwrap();
+        let xp_file = match crate::rex_assets::load_xp(path) {
+            Ok(xp_file) => xp_file,
+            Err(e) => {
+                crate::console_log(format!("WARNING - Unable to load prefab level: {}", e));
+                return;
+            }
+        };
 
         for layer in &xp_file.layers {
             for y in 0..layer.height {
@@ -185,36 +220,57 @@ impl PrefabBuilder {
 
     #[allow(dead_code)]
     fn apply_sectional(&mut self, section : &prefab_sections::PrefabSection, build_data : &mut BuilderMap) {
-        use prefab_sections::*;
-
         let string_vec = PrefabBuilder::read_ascii_to_vec(section.template);
+        self.place_section(&string_vec, section.width, section.height, section.placement, &HashMap::new(), build_data);
+    }
+
+    fn apply_library_section(&mut self, name : &str, build_data : &mut BuilderMap) {
+        let section = build_data.raws.vaults.section(name);
+        match section {
+            Some(Vault{ glyphs, width, height, placement : Some(placement), legend, .. }) => {
+                self.place_section(&glyphs, width, height, placement, &legend, build_data);
+            }
+            _ => crate::console_log(format!("WARNING - No section named {} in the vault library", name))
+        }
+    }
+
+    fn place_section(
+        &mut self,
+        glyphs : &[char],
+        width : usize,
+        height : usize,
+        placement : (prefab_sections::HorizontalPlacement, prefab_sections::VerticalPlacement),
+        legend : &HashMap<char, String>,
+        build_data : &mut BuilderMap)
+    {
+        use prefab_sections::*;
 
         // Place the new section
         let chunk_x;
-        match section.placement.0 {
+        match placement.0 {
             HorizontalPlacement::Left => chunk_x = 0,
-            HorizontalPlacement::Center => chunk_x = (build_data.map.width / 2) - (section.width as i32 / 2),
-            HorizontalPlacement::Right => chunk_x = (build_data.map.width-1) - section.width as i32
+            HorizontalPlacement::Center => chunk_x = (build_data.map.width / 2) - (width as i32 / 2),
+            HorizontalPlacement::Right => chunk_x = (build_data.map.width-1) - width as i32
         }
 
         let chunk_y;
-        match section.placement.1 {
+        match placement.1 {
             VerticalPlacement::Top => chunk_y = 0,
-            VerticalPlacement::Center => chunk_y = (build_data.map.height / 2) - (section.height as i32 / 2),
-            VerticalPlacement::Bottom => chunk_y = (build_data.map.height-1) - section.height as i32
+            VerticalPlacement::Center => chunk_y = (build_data.map.height / 2) - (height as i32 / 2),
+            VerticalPlacement::Bottom => chunk_y = (build_data.map.height-1) - height as i32
         }
 
         // Build the map
         self.apply_previous_iteration(|x,y| {
-            x < chunk_x || x > (chunk_x + section.width as i32) || y < chunk_y || y > (chunk_y + section.height as i32)
+            x < chunk_x || x > (chunk_x + width as i32) || y < chunk_y || y > (chunk_y + height as i32)
         }, build_data);
 
         let mut i = 0;
-        for ty in 0..section.height {
-            for tx in 0..section.width {
+        for ty in 0..height {
+            for tx in 0..width {
                 if tx > 0 && tx < build_data.map.width as usize -1 && ty < build_data.map.height as usize -1 && ty > 0 {
                     let idx = build_data.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                    if i < string_vec.len() { self.char_to_map(string_vec[i], idx, build_data); }
+                    if i < glyphs.len() { self.glyph_to_map(glyphs[i], legend, idx, build_data); }
                 }
                 i += 1;
             }
@@ -222,34 +278,33 @@ impl PrefabBuilder {
         build_data.take_snapshot();
     }
 
-    fn apply_room_vaults(&mut self, build_data : &mut BuilderMap) {
-        use prefab_rooms::*;
-
+    fn apply_room_vaults(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         // Apply the previous builder, and keep all entities it spawns (for now)
         self.apply_previous_iteration(|_x,_y| true, build_data);
 
         // Do we want a vault at all?
-        let vault_roll = crate::rng::roll_dice(1, 6) + build_data.map.depth;
+        let vault_roll = rng.roll_dice(1, 6) + build_data.map.depth;
         if vault_roll < 4 { return; }
 
-        // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
-
-        // Filter the vault list down to ones that are applicable to the current depth
-        let mut possible_vaults : Vec<&PrefabRoom> = master_vault_list
-            .iter()
-            .filter(|v| { build_data.map.depth >= v.first_depth && build_data.map.depth <= v.last_depth })
-            .collect();
+        // Filter the vault library down to ones that are applicable to the current depth
+        let mut possible_vaults : Vec<Vault> = build_data.raws.vaults.rooms_for_depth(build_data.map.depth);
 
         if possible_vaults.is_empty() { return; } // Bail out if there's nothing to build
 
-        let n_vaults = i32::min(crate::rng::roll_dice(1, 3), possible_vaults.len() as i32);
+        let n_vaults = i32::min(rng.roll_dice(1, 3), possible_vaults.len() as i32);
         let mut used_tiles : HashSet<usize> = HashSet::new();
 
         for _i in 0..n_vaults {
 
-            let vault_index = if possible_vaults.len() == 1 { 0 } else { (crate::rng::roll_dice(1, possible_vaults.len() as i32)-1) as usize };
-            let vault = possible_vaults[vault_index];
+            // Pick a vault, favouring the heavier ones
+            let total_weight : i32 = possible_vaults.iter().map(|v| v.weight).sum();
+            let mut roll = rng.roll_dice(1, total_weight) - 1;
+            let mut vault_index = 0;
+            while roll >= possible_vaults[vault_index].weight {
+                roll -= possible_vaults[vault_index].weight;
+                vault_index += 1;
+            }
+            let vault = possible_vaults[vault_index].random_orientation(rng);
 
             // We'll make a list of places in which the vault could fit
             let mut vault_positions : Vec<Position> = Vec::new();
@@ -292,27 +347,26 @@ impl PrefabBuilder {
             }
 
             if !vault_positions.is_empty() {
-                let pos_idx = if vault_positions.len()==1 { 0 } else { (crate::rng::roll_dice(1, vault_positions.len() as i32)-1) as usize };
+                let pos_idx = if vault_positions.len()==1 { 0 } else { (rng.roll_dice(1, vault_positions.len() as i32)-1) as usize };
                 let pos = &vault_positions[pos_idx];
 
                 let chunk_x = pos.x;
                 let chunk_y = pos.y;
 
                 let width = build_data.map.width; // The borrow checker really doesn't like it
-                let height = build_data.map.height; // when we access `self` inside the `retain`
+                                                  // when we access `self` inside the `retain`
                 build_data.spawn_list.retain(|e| {
                     let idx = e.0 as i32;
                     let x = idx % width;
-                    let y = idx / height;
+                    let y = idx / width;
                     x < chunk_x || x > chunk_x + vault.width as i32 || y < chunk_y || y > chunk_y + vault.height as i32
                 });
 
-                let string_vec = PrefabBuilder::read_ascii_to_vec(vault.template);
                 let mut i = 0;
                 for ty in 0..vault.height {
                     for tx in 0..vault.width {
                         let idx = build_data.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                        self.char_to_map(string_vec[i], idx, build_data);
+                        self.glyph_to_map(vault.glyphs[i], &vault.legend, idx, build_data);
                         used_tiles.insert(idx);
                         i += 1;
                     }
@@ -12,6 +12,14 @@ pub const WFC_POPULATED : PrefabLevel = PrefabLevel{
     height: 43
 };
 
+/// The prefab level called `name` in `levels.json`.
+pub fn named(name : &str) -> Option<PrefabLevel> {
+    match name {
+        "WFC_POPULATED" => Some(WFC_POPULATED),
+        _ => None
+    }
+}
+
 #[allow(dead_code)]
 const LEVEL_MAP : &str =
 "
@@ -0,0 +1,330 @@
+use super::prefab_sections::{HorizontalPlacement, VerticalPlacement};
+use rltk::RandomNumberGenerator;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Embeds each vault file in the game, and lists them for `load_vaults` in the order
+/// they're read, so that the same seed always picks the same vaults.
+macro_rules! embedded_vaults {
+    ( $( $resource:ident => $file:expr ),* $(,)? ) => {
+        $( rltk::embedded_resource!($resource, $file); )*
+
+        pub(crate) fn link_vault_files() -> Vec<&'static str> {
+            $( rltk::link_resource!($resource, $file); )*
+            vec![ $( $file ),* ]
+        }
+    };
+}
+
+// A new vault goes in `raws/vaults` and needs a line here.
+embedded_vaults! {
+    CHECKERBOARD_VAULT => "../../../raws/vaults/checkerboard.txt",
+    LAVA_POOL_VAULT => "../../../raws/vaults/lava_pool.txt",
+    SILLY_SMILE_VAULT => "../../../raws/vaults/silly_smile.txt",
+    TOTALLY_NOT_A_TRAP_VAULT => "../../../raws/vaults/totally_not_a_trap.txt",
+}
+
+/// A hand-drawn room or map section, from `raws/vaults`.
+///
+/// Each vault is a `.txt` file: a header of `key: value` lines, a `---` line, then the
+/// map itself. A header without a map uses the `.xp` file of the same name instead, and
+/// an `.xp` file with no header gets the defaults below.
+///
+/// ```text
+/// name: Goblin Kitchen
+/// depth: 2-8
+/// weight: 3
+/// legend: k = Kobold
+/// ---
+/// ......
+/// .#k%#.
+/// ......
+/// ```
+///
+/// A legend entry can also name a tile from `raws/tiles.json` (`legend: ~ = Lava`).
+///
+/// Room vaults may be rotated and mirrored when placed, unless the header says
+/// `transform: no`. Directional glyphs (box drawing, arrows, slashes) turn with the vault,
+/// and so do the legend entries for them, so `legend: > = Dart Trap` still spawns the trap
+/// on whichever way the `>` ends up pointing.
+///
+/// Vaults with a `placement` (e.g. `placement: right top`) are sections rather than rooms,
+/// and are only placed by a `PrefabSection` builder that names them.
+#[derive(Clone)]
+pub struct Vault {
+    pub name : String,
+    pub width : usize,
+    pub height : usize,
+    pub glyphs : Vec<char>,
+    pub min_depth : i32,
+    pub max_depth : i32,
+    pub weight : i32,
+    pub legend : HashMap<char, String>,
+    pub placement : Option<(HorizontalPlacement, VerticalPlacement)>,
+    pub transform : bool
+}
+
+impl Vault {
+    fn named(name : &str) -> Vault {
+        Vault{
+            name : name.to_string(),
+            width : 0,
+            height : 0,
+            glyphs : Vec::new(),
+            min_depth : 0,
+            max_depth : 100,
+            weight : 1,
+            legend : HashMap::new(),
+            placement : None,
+            transform : true
+        }
+    }
+
+    /// A copy of the vault in one of its eight orientations, picked at random.
+    pub fn random_orientation(&self, rng : &mut RandomNumberGenerator) -> Vault {
+        if !self.transform {
+            return self.clone();
+        }
+        let mirror = rng.roll_dice(1, 2) == 1;
+        let quarter_turns = rng.roll_dice(1, 4) - 1;
+        self.transformed(mirror, quarter_turns)
+    }
+
+    /// A copy of the vault mirrored left-to-right (if `mirror`), then turned clockwise
+    /// `quarter_turns` times. Mirroring and a half turn together flip it top-to-bottom.
+    pub fn transformed(&self, mirror : bool, quarter_turns : i32) -> Vault {
+        let mut result = self.clone();
+        if mirror {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let ch = self.glyphs[y * self.width + (self.width - 1 - x)];
+                    result.glyphs[y * self.width + x] = mirror_glyph(ch);
+                }
+            }
+            result.legend = self.legend.iter().map(|(ch, name)| (mirror_glyph(*ch), name.clone())).collect();
+        }
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            result = result.rotated();
+        }
+        result
+    }
+
+    fn rotated(&self) -> Vault {
+        let mut result = self.clone();
+        result.width = self.height;
+        result.height = self.width;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // (x, y) ends up at (height-1-y, x)
+                let new_idx = x * result.width + (self.height - 1 - y);
+                result.glyphs[new_idx] = rotate_glyph(self.glyphs[y * self.width + x]);
+            }
+        }
+        result.legend = self.legend.iter().map(|(ch, name)| (rotate_glyph(*ch), name.clone())).collect();
+        result
+    }
+
+    /// Reads a vault from the text of the `.txt` vault file `file`.
+    pub fn parse(file : &str, text : &str) -> Result<Vault, String> {
+        let mut vault = Vault::named(&file_stem(file));
+        let mut lines = text.lines();
+
+        let mut has_map = false;
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if line == "---" {
+                has_map = true;
+                break;
+            }
+            if line.is_empty() || line.starts_with("//") { continue; }
+            parse_header_line(&mut vault, line)?;
+        }
+
+        if has_map {
+            vault.set_rows(lines.map(|l| l.trim_end_matches('\r').chars().collect()).collect())?;
+        } else {
+            load_xp_glyphs(&mut vault, &format!("{}.xp", file.trim_end_matches(".txt")))?;
+        }
+        Ok(vault)
+    }
+
+    fn set_rows(&mut self, mut rows : Vec<Vec<char>>) -> Result<(), String> {
+        while rows.last().map(|r| r.is_empty()).unwrap_or(false) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return Err("the map is empty".to_string());
+        }
+
+        // Editors like to strip trailing spaces, so short rows are padded out with floor
+        self.width = rows.iter().map(|r| r.len()).max().unwrap();
+        self.height = rows.len();
+        self.glyphs = Vec::with_capacity(self.width * self.height);
+        for row in rows.iter() {
+            self.glyphs.extend(row.iter());
+            self.glyphs.extend(std::iter::repeat_n('.', self.width - row.len()));
+        }
+        Ok(())
+    }
+}
+
+pub struct VaultLibrary {
+    vaults : Vec<Vault>
+}
+
+impl VaultLibrary {
+    pub fn empty() -> VaultLibrary {
+        VaultLibrary{ vaults : Vec::new() }
+    }
+
+    /// Room vaults that are allowed to appear at `depth`.
+    pub fn rooms_for_depth(&self, depth : i32) -> Vec<Vault> {
+        self.vaults
+            .iter()
+            .filter(|v| v.placement.is_none() && v.weight > 0 && depth >= v.min_depth && depth <= v.max_depth)
+            .cloned()
+            .collect()
+    }
+
+    pub fn section(&self, name : &str) -> Option<Vault> {
+        self.vaults.iter().find(|v| v.placement.is_some() && v.name == name).cloned()
+    }
+}
+
+/// Reads every embedded vault. Vaults that can't be read are logged and skipped.
+pub fn load_vaults() -> VaultLibrary {
+    let files = link_vault_files();
+    let mut vaults = Vec::new();
+    for file in files.iter() {
+        let result = if let Some(stem) = file.strip_suffix(".xp") {
+            // An image with a header is read along with the header
+            if files.contains(&format!("{}.txt", stem).as_str()) { continue; }
+            let mut vault = Vault::named(&file_stem(file));
+            load_xp_glyphs(&mut vault, file).map(|_| vault)
+        } else {
+            load_text_vault(file)
+        };
+        match result {
+            Ok(vault) => vaults.push(vault),
+            Err(e) => crate::console_log(format!("WARNING - Skipping vault {}: {}", file, e))
+        }
+    }
+    VaultLibrary{ vaults }
+}
+
+fn file_stem(file : &str) -> String {
+    Path::new(file).file_stem().and_then(|s| s.to_str()).unwrap_or("Vault").to_string()
+}
+
+fn embedded_file(file : &str) -> Result<Vec<u8>, String> {
+    rltk::embedding::EMBED
+        .lock()
+        .get_resource(file.to_string())
+        .map(|data| data.to_vec())
+        .ok_or_else(|| format!("{} isn't embedded in the game", file))
+}
+
+fn load_text_vault(file : &str) -> Result<Vault, String> {
+    let data = embedded_file(file)?;
+    let text = std::str::from_utf8(&data).map_err(|e| e.to_string())?;
+    Vault::parse(file, text)
+}
+
+fn parse_header_line(vault : &mut Vault, line : &str) -> Result<(), String> {
+    let (key, value) = line.split_once(':').ok_or_else(|| format!("bad header line '{}'", line))?;
+    let value = value.trim();
+    match key.trim() {
+        "name" => vault.name = value.to_string(),
+        "depth" => {
+            let (min, max) = value.split_once('-').unwrap_or((value, value));
+            vault.min_depth = min.trim().parse().map_err(|_| format!("bad depth '{}'", value))?;
+            vault.max_depth = max.trim().parse().map_err(|_| format!("bad depth '{}'", value))?;
+        }
+        "weight" => vault.weight = value.parse().map_err(|_| format!("bad weight '{}'", value))?,
+        "legend" => {
+            let (glyph, spawn) = value.split_once('=').ok_or_else(|| format!("bad legend '{}'", value))?;
+            let mut glyph = glyph.trim().chars();
+            match (glyph.next(), glyph.next()) {
+                (Some(ch), None) => { vault.legend.insert(ch, spawn.trim().to_string()); }
+                _ => return Err(format!("legend needs a single glyph: '{}'", value))
+            }
+        }
+        "placement" => {
+            let mut words = value.split_whitespace();
+            let horizontal = match words.next() {
+                Some("left") => HorizontalPlacement::Left,
+                Some("center") => HorizontalPlacement::Center,
+                Some("right") => HorizontalPlacement::Right,
+                _ => return Err(format!("bad placement '{}'", value))
+            };
+            let vertical = match words.next() {
+                Some("top") => VerticalPlacement::Top,
+                Some("center") => VerticalPlacement::Center,
+                Some("bottom") => VerticalPlacement::Bottom,
+                _ => return Err(format!("bad placement '{}'", value))
+            };
+            vault.placement = Some((horizontal, vertical));
+        }
+        "transform" => vault.transform = match value {
+            "yes" | "true" => true,
+            "no" | "false" => false,
+            _ => return Err(format!("bad transform '{}'", value))
+        },
+        other => return Err(format!("unknown header '{}'", other))
+    }
+    Ok(())
+}
+
+/// The glyph as it looks after mirroring left-to-right.
+fn mirror_glyph(ch : char) -> char {
+    match ch {
+        '/' => '\\', '\\' => '/',
+        '(' => ')', ')' => '(',
+        '[' => ']', ']' => '[',
+        '{' => '}', '}' => '{',
+        '←' => '→', '→' => '←',
+        '◄' => '►', '►' => '◄',
+        '┌' => '┐', '┐' => '┌', '└' => '┘', '┘' => '└',
+        '├' => '┤', '┤' => '├',
+        '╔' => '╗', '╗' => '╔', '╚' => '╝', '╝' => '╚',
+        '╠' => '╣', '╣' => '╠',
+        '▌' => '▐', '▐' => '▌',
+        _ => ch
+    }
+}
+
+/// The glyph as it looks after a quarter turn clockwise.
+fn rotate_glyph(ch : char) -> char {
+    match ch {
+        '|' => '-', '-' => '|',
+        '/' => '\\', '\\' => '/',
+        '│' => '─', '─' => '│',
+        '║' => '═', '═' => '║',
+        '↑' => '→', '→' => '↓', '↓' => '←', '←' => '↑',
+        '▲' => '►', '►' => '▼', '▼' => '◄', '◄' => '▲',
+        '┌' => '┐', '┐' => '┘', '┘' => '└', '└' => '┌',
+        '├' => '┬', '┬' => '┤', '┤' => '┴', '┴' => '├',
+        '╔' => '╗', '╗' => '╝', '╝' => '╚', '╚' => '╔',
+        '╠' => '╦', '╦' => '╣', '╣' => '╩', '╩' => '╠',
+        '▀' => '▐', '▐' => '▄', '▄' => '▌', '▌' => '▀',
+        _ => ch
+    }
+}
+
+fn load_xp_glyphs(vault : &mut Vault, file : &str) -> Result<(), String> {
+    let data = embedded_file(file)?;
+    let xp_file = rltk::rex::XpFile::read(&mut std::io::Cursor::new(data)).map_err(|e| format!("{}: {:?}", file, e))?;
+    let layer = xp_file.layers.first().ok_or_else(|| format!("{} has no layers", file))?;
+
+    let mut rows = Vec::with_capacity(layer.height);
+    for y in 0..layer.height {
+        let mut row = Vec::with_capacity(layer.width);
+        for x in 0..layer.width {
+            let ch = layer.get(x, y).map(|cell| cell.ch).unwrap_or(0);
+            // REX Paint stores CP437; untouched cells are blank
+            row.push(if ch == 0 || ch == 32 { '.' } else { rltk::to_char(ch as u8) });
+        }
+        rows.push(row);
+    }
+    vault.set_rows(rows)
+}
@@ -1,10 +1,11 @@
 use super::{MetaMapBuilder, BuilderMap, spawner};
+use rltk::RandomNumberGenerator;
 
 pub struct RoomBasedSpawner {}
 
 impl MetaMapBuilder for RoomBasedSpawner {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -14,10 +15,10 @@ impl RoomBasedSpawner {
         Box::new(RoomBasedSpawner{})
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         if let Some(rooms) = &build_data.rooms {
             for room in rooms.iter().skip(1) {
-                spawner::spawn_room(&build_data.map, room, build_data.map.depth, &mut build_data.spawn_list);
+                spawner::spawn_room(&build_data.map, rng, &build_data.raws, room, build_data.map.depth, &mut build_data.spawn_list);
             }
         } else {
             panic!("Room Based Spawning only works after rooms have been created");
@@ -1,8 +1,9 @@
 use super::{MetaMapBuilder, BuilderMap, TileType};
+use rltk::RandomNumberGenerator;
 pub struct RoomBasedStairs {}
 
 impl MetaMapBuilder for RoomBasedStairs {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
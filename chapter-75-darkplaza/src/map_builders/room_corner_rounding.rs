@@ -1,9 +1,10 @@
 use super::{MetaMapBuilder, BuilderMap, TileType, Rect};
+use rltk::RandomNumberGenerator;
 
 pub struct RoomCornerRounder {}
 
 impl MetaMapBuilder for RoomCornerRounder {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
         self.build(build_data);
     }
 }
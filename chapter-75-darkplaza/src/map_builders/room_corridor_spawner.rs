@@ -1,10 +1,11 @@
 use super::{MetaMapBuilder, BuilderMap, spawner};
+use rltk::RandomNumberGenerator;
 
 pub struct CorridorSpawner {}
 
 impl MetaMapBuilder for CorridorSpawner {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -14,11 +15,13 @@ impl CorridorSpawner {
         Box::new(CorridorSpawner{})
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         if let Some(corridors) = &build_data.corridors {
             for c in corridors.iter() {
                 let depth = build_data.map.depth;
                 spawner::spawn_region(&build_data.map,
+                    rng,
+                    &build_data.raws,
                     &c,
                     depth,
                     &mut build_data.spawn_list);
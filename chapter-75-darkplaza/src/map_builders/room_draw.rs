@@ -1,10 +1,11 @@
 use super::{MetaMapBuilder, BuilderMap, TileType, Rect};
+use rltk::RandomNumberGenerator;
 
 pub struct RoomDrawer {}
 
 impl MetaMapBuilder for RoomDrawer {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -45,7 +46,7 @@ impl RoomDrawer {
         }
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let rooms : Vec<Rect>;
         if let Some(rooms_builder) = &build_data.rooms {
             rooms = rooms_builder.clone();
@@ -54,7 +55,7 @@ impl RoomDrawer {
         }
 
         for room in rooms.iter() {
-            let room_type = crate::rng::roll_dice(1,4);
+            let room_type = rng.roll_dice(1,4);
             match room_type {
                 1 => self.circle(build_data, room),
                 _ => self.rectangle(build_data, room)
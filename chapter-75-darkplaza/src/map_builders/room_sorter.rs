@@ -1,4 +1,5 @@
 use super::{MetaMapBuilder, BuilderMap, Rect };
+use rltk::RandomNumberGenerator;
 
 #[allow(dead_code)]
 pub enum RoomSort { LEFTMOST, RIGHTMOST, TOPMOST, BOTTOMMOST, CENTRAL }
@@ -9,7 +10,7 @@ pub struct RoomSorter {
 
 impl MetaMapBuilder for RoomSorter {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         self.sorter(build_data);
     }
 }
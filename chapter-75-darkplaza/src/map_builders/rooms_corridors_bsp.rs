@@ -1,11 +1,12 @@
 use super::{MetaMapBuilder, BuilderMap, Rect, draw_corridor };
+use rltk::RandomNumberGenerator;
 
 pub struct BspCorridors {}
 
 impl MetaMapBuilder for BspCorridors {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.corridors(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.corridors(rng, build_data);
     }
 }
 
@@ -15,7 +16,7 @@ impl BspCorridors {
         Box::new(BspCorridors{})
     }
 
-    fn corridors(&mut self, build_data : &mut BuilderMap) {
+    fn corridors(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let rooms : Vec<Rect>;
         if let Some(rooms_builder) = &build_data.rooms {
             rooms = rooms_builder.clone();
@@ -27,10 +28,10 @@ impl BspCorridors {
         for i in 0..rooms.len()-1 {
             let room = rooms[i];
             let next_room = rooms[i+1];
-            let start_x = room.x1 + (crate::rng::roll_dice(1, i32::abs(room.x1 - room.x2))-1);
-            let start_y = room.y1 + (crate::rng::roll_dice(1, i32::abs(room.y1 - room.y2))-1);
-            let end_x = next_room.x1 + (crate::rng::roll_dice(1, i32::abs(next_room.x1 - next_room.x2))-1);
-            let end_y = next_room.y1 + (crate::rng::roll_dice(1, i32::abs(next_room.y1 - next_room.y2))-1);
+            let start_x = room.x1 + (rng.roll_dice(1, i32::abs(room.x1 - room.x2))-1);
+            let start_y = room.y1 + (rng.roll_dice(1, i32::abs(room.y1 - room.y2))-1);
+            let end_x = next_room.x1 + (rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2))-1);
+            let end_y = next_room.y1 + (rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2))-1);
             let corridor = draw_corridor(&mut build_data.map, start_x, start_y, end_x, end_y);
             corridors.push(corridor);
             build_data.take_snapshot();
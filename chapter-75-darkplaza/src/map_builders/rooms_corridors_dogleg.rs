@@ -1,11 +1,12 @@
 use super::{MetaMapBuilder, BuilderMap, Rect, apply_horizontal_tunnel, apply_vertical_tunnel };
+use rltk::RandomNumberGenerator;
 
 pub struct DoglegCorridors {}
 
 impl MetaMapBuilder for DoglegCorridors {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.corridors(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.corridors(rng, build_data);
     }
 }
 
@@ -15,7 +16,7 @@ impl DoglegCorridors {
         Box::new(DoglegCorridors{})
     }
 
-    fn corridors(&mut self, build_data : &mut BuilderMap) {
+    fn corridors(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         let rooms : Vec<Rect>;
         if let Some(rooms_builder) = &build_data.rooms {
             rooms = rooms_builder.clone();
@@ -28,7 +29,7 @@ impl DoglegCorridors {
             if i > 0 {
                 let (new_x, new_y) = room.center();
                 let (prev_x, prev_y) = rooms[i as usize -1].center();
-                if crate::rng::range(0,2) == 1 {
+                if rng.range(0,2) == 1 {
                     let mut c1 = apply_horizontal_tunnel(&mut build_data.map, prev_x, new_x, prev_y);
                     let mut c2 = apply_vertical_tunnel(&mut build_data.map, prev_y, new_y, new_x);
                     c1.append(&mut c2);
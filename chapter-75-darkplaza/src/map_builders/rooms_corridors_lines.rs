@@ -1,11 +1,12 @@
 use super::{MetaMapBuilder, BuilderMap, Rect, TileType };
+use rltk::RandomNumberGenerator;
 use std::collections::HashSet;
 
 pub struct StraightLineCorridors {}
 
 impl MetaMapBuilder for StraightLineCorridors {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         self.corridors(build_data);
     }
 }
@@ -1,18 +1,13 @@
-use super::{BuilderChain, BuilderMap, InitialMapBuilder, TileType, Position};
+use super::{BuilderMap, InitialMapBuilder, TileType, Position, wall_in_border};
+use rltk::RandomNumberGenerator;
 use std::collections::HashSet;
 
-pub fn town_builder(new_depth: i32, width: i32, height: i32) -> BuilderChain {
-    let mut chain = BuilderChain::new(new_depth, width, height, "The Town of Bracketon");
-    chain.start_with(TownBuilder::new());
-    chain
-}
-
 pub struct TownBuilder {}
 
 impl InitialMapBuilder for TownBuilder {
     #[allow(dead_code)]
-    fn build_map(&mut self, build_data : &mut BuilderMap) {
-        self.build_rooms(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build_rooms(rng, build_data);
     }
 }
 
@@ -25,13 +20,14 @@ impl TownBuilder {
         Box::new(TownBuilder{})
     }
 
-    pub fn build_rooms(&mut self, build_data : &mut BuilderMap) {
+    pub fn build_rooms(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         self.grass_layer(build_data);
-        self.water_and_piers(build_data);
-        let (mut available_building_tiles, wall_gap_y) = self.town_walls(build_data);
-        let mut buildings = self.buildings(build_data, &mut available_building_tiles);
-        let doors = self.add_doors(build_data, &mut buildings, wall_gap_y);
+        self.water_and_piers(rng, build_data);
+        let (mut available_building_tiles, wall_gap_y) = self.town_walls(rng, build_data);
+        let mut buildings = self.buildings(rng, build_data, &mut available_building_tiles);
+        let doors = self.add_doors(rng, build_data, &mut buildings, wall_gap_y);
         self.add_paths(build_data, &doors);
+        self.street_lamps(build_data, wall_gap_y);
 
         for y in wall_gap_y-3 .. wall_gap_y + 4 {
             let exit_idx = build_data.map.xy_idx(build_data.width-2, y);
@@ -39,10 +35,11 @@ impl TownBuilder {
         }
 
         let building_size = self.sort_buildings(&buildings);
-        self.building_factory(build_data, &buildings, &building_size);
+        self.building_factory(rng, build_data, &buildings, &building_size);
 
-        self.spawn_dockers(build_data);
-        self.spawn_townsfolk(build_data, &mut available_building_tiles);
+        self.spawn_dockers(rng, build_data);
+        self.spawn_townsfolk(rng, build_data, &mut available_building_tiles);
+        wall_in_border(build_data);
 
         // Make visible for screenshot
         for t in build_data.map.visible_tiles.iter_mut() {
@@ -59,11 +56,11 @@ impl TownBuilder {
         build_data.take_snapshot();
     }
 
-    fn water_and_piers(&mut self, build_data : &mut BuilderMap) {
-        let mut n = (crate::rng::roll_dice(1, 65535) as f32) / 65535f32;
+    fn water_and_piers(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let mut n = (rng.roll_dice(1, 65535) as f32) / 65535f32;
         let mut water_width : Vec<i32> = Vec::new();
         for y in 0..build_data.height {
-            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + crate::rng::roll_dice(1, 6);
+            let n_water = (f32::sin(n) * 10.0) as i32 + 14 + rng.roll_dice(1, 6);
             water_width.push(n_water);
             n += 0.1;
             for x in 0..n_water {
@@ -78,9 +75,9 @@ impl TownBuilder {
         build_data.take_snapshot();
 
         // Add piers
-        for _i in 0..crate::rng::roll_dice(1, 4)+6 {
-            let y = crate::rng::roll_dice(1, build_data.height)-1;
-            for x in 2 + crate::rng::roll_dice(1, 6) .. water_width[y as usize] + 4 {
+        for _i in 0..rng.roll_dice(1, 4)+6 {
+            let y = rng.roll_dice(1, build_data.height)-1;
+            for x in 2 + rng.roll_dice(1, 6) .. water_width[y as usize] + 4 {
                 let idx = build_data.map.xy_idx(x, y);
                 build_data.map.tiles[idx] = TileType::Bridge;
             }
@@ -88,11 +85,11 @@ impl TownBuilder {
         build_data.take_snapshot();
     }
 
-    fn town_walls(&mut self, build_data : &mut BuilderMap)
+    fn town_walls(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)
         -> (HashSet<usize>, i32)
     {
         let mut available_building_tiles : HashSet<usize> = HashSet::new();
-        let wall_gap_y = crate::rng::roll_dice(1, build_data.height - 9) + 5;
+        let wall_gap_y = rng.roll_dice(1, build_data.height - 9) + 5;
         for y in 1 .. build_data.height-2 {
             if !(y > wall_gap_y-4 && y < wall_gap_y+4)  {
                 let idx = build_data.map.xy_idx(30, y);
@@ -128,6 +125,7 @@ impl TownBuilder {
     }
 
     fn buildings(&mut self,
+        rng : &mut RandomNumberGenerator,
         build_data : &mut BuilderMap,
         available_building_tiles : &mut HashSet<usize>)
     -> Vec<(i32, i32, i32, i32)>
@@ -135,10 +133,10 @@ impl TownBuilder {
         let mut buildings : Vec<(i32, i32, i32, i32)> = Vec::new();
         let mut n_buildings = 0;
         while n_buildings < 12 {
-            let bx = crate::rng::roll_dice(1, build_data.map.width - 32) + 30;
-            let by = crate::rng::roll_dice(1, build_data.map.height)-2;
-            let bw = crate::rng::roll_dice(1, 8)+4;
-            let bh = crate::rng::roll_dice(1, 8)+4;
+            let bx = rng.roll_dice(1, build_data.map.width - 32) + 30;
+            let by = rng.roll_dice(1, build_data.map.height)-2;
+            let bw = rng.roll_dice(1, 8)+4;
+            let bh = rng.roll_dice(1, 8)+4;
             let mut possible = true;
             for y in by .. by+bh {
                 for x in bx .. bx+bw {
@@ -191,6 +189,7 @@ impl TownBuilder {
     }
 
     fn add_doors(&mut self,
+        rng : &mut RandomNumberGenerator,
         build_data : &mut BuilderMap,
         buildings: &mut Vec<(i32, i32, i32, i32)>,
         wall_gap_y : i32)
@@ -198,7 +197,7 @@ impl TownBuilder {
     {
         let mut doors = Vec::new();
         for building in buildings.iter() {
-            let door_x = building.0 + 1 + crate::rng::roll_dice(1, building.2 - 3);
+            let door_x = building.0 + 1 + rng.roll_dice(1, building.2 - 3);
             let cy = building.1 + (building.3 / 2);
             let idx = if cy > wall_gap_y {
                 // Door on the north wall
@@ -256,6 +255,18 @@ impl TownBuilder {
         }
     }
 
+    fn street_lamps(&mut self, build_data : &mut BuilderMap, wall_gap_y : i32) {
+        // Along both edges of the main road, to light the way at night
+        for x in (34 .. build_data.width-4).step_by(8) {
+            for y in [wall_gap_y-3, wall_gap_y+3].iter() {
+                let idx = build_data.map.xy_idx(x, *y);
+                if build_data.map.tiles[idx] == TileType::Road {
+                    build_data.spawn_list.push((idx, "Street Lamp".to_string()));
+                }
+            }
+        }
+    }
+
     fn sort_buildings(&mut self, buildings: &[(i32, i32, i32, i32)]) -> Vec<(usize, i32, BuildingTag)>
     {
         let mut building_size : Vec<(usize, i32, BuildingTag)> = Vec::new();
@@ -282,6 +293,7 @@ impl TownBuilder {
     }
 
     fn building_factory(&mut self,
+        rng : &mut RandomNumberGenerator,
         build_data : &mut BuilderMap,
         buildings: &[(i32, i32, i32, i32)],
         building_index : &[(usize, i32, BuildingTag)])
@@ -289,14 +301,14 @@ impl TownBuilder {
         for (i,building) in buildings.iter().enumerate() {
             let build_type = &building_index[i].2;
             match build_type {
-                BuildingTag::Pub => self.build_pub(&building, build_data),
-                BuildingTag::Temple => self.build_temple(&building, build_data),
-                BuildingTag::Blacksmith => self.build_smith(&building, build_data),
-                BuildingTag::Clothier => self.build_clothier(&building, build_data),
-                BuildingTag::Alchemist => self.build_alchemist(&building, build_data),
-                BuildingTag::PlayerHouse => self.build_my_house(&building, build_data),
-                BuildingTag::Hovel => self.build_hovel(&building, build_data),
-                BuildingTag::Abandoned => self.build_abandoned_house(&building, build_data),
+                BuildingTag::Pub => self.build_pub(rng, &building, build_data),
+                BuildingTag::Temple => self.build_temple(rng, &building, build_data),
+                BuildingTag::Blacksmith => self.build_smith(rng, &building, build_data),
+                BuildingTag::Clothier => self.build_clothier(rng, &building, build_data),
+                BuildingTag::Alchemist => self.build_alchemist(rng, &building, build_data),
+                BuildingTag::PlayerHouse => self.build_my_house(rng, &building, build_data),
+                BuildingTag::Hovel => self.build_hovel(rng, &building, build_data),
+                BuildingTag::Abandoned => self.build_abandoned_house(rng, &building, build_data),
                 _ => {}
             }
         }
@@ -304,6 +316,7 @@ impl TownBuilder {
 
     fn random_building_spawn(
         &mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap,
         to_place : &mut Vec<&str>,
@@ -312,7 +325,7 @@ impl TownBuilder {
         for y in building.1 .. building.1 + building.3 {
             for x in building.0 .. building.0 + building.2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::WoodFloor && idx != player_idx && crate::rng::roll_dice(1, 3)==1 && !to_place.is_empty() {
+                if build_data.map.tiles[idx] == TileType::WoodFloor && idx != player_idx && rng.roll_dice(1, 3)==1 && !to_place.is_empty() {
                     let entity_tag = to_place[0];
                     to_place.remove(0);
                     build_data.spawn_list.push((idx, entity_tag.to_string()));
@@ -322,6 +335,7 @@ impl TownBuilder {
     }
 
     fn build_pub(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
@@ -336,81 +350,88 @@ impl TownBuilder {
         // Place other items
         let mut to_place : Vec<&str> = vec!["Barkeep", "Shady Salesman", "Patron", "Patron", "Keg",
             "Table", "Chair", "Table", "Chair"];
-        self.random_building_spawn(building, build_data, &mut to_place, player_idx);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, player_idx);
     }
 
     fn build_temple(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Priest", "Altar", "Parishioner", "Parishioner", "Chair", "Chair", "Candle", "Candle"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_smith(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Blacksmith", "Anvil", "Water Trough", "Weapon Rack", "Armor Stand"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_clothier(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Clothier", "Cabinet", "Table", "Loom", "Hide Rack"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_alchemist(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Alchemist", "Chemistry Set", "Dead Thing", "Chair", "Table"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_my_house(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Mom", "Bed", "Cabinet", "Chair", "Table"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_hovel(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         // Place items
         let mut to_place : Vec<&str> = vec!["Peasant", "Bed", "Chair", "Table"];
-        self.random_building_spawn(building, build_data, &mut to_place, 0);
+        self.random_building_spawn(rng, building, build_data, &mut to_place, 0);
     }
 
     fn build_abandoned_house(&mut self,
+        rng : &mut RandomNumberGenerator,
         building: &(i32, i32, i32, i32),
         build_data : &mut BuilderMap)
     {
         for y in building.1 .. building.1 + building.3 {
             for x in building.0 .. building.0 + building.2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::WoodFloor && idx != 0 && crate::rng::roll_dice(1, 2)==1 {
+                if build_data.map.tiles[idx] == TileType::WoodFloor && idx != 0 && rng.roll_dice(1, 2)==1 {
                     build_data.spawn_list.push((idx, "Rat".to_string()));
                 }
             }
         }
     }
 
-    fn spawn_dockers(&mut self, build_data : &mut BuilderMap) {
+    fn spawn_dockers(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         for (idx, tt) in build_data.map.tiles.iter().enumerate() {
-            if *tt == TileType::Bridge && crate::rng::roll_dice(1, 6)==1 {
-                let roll = crate::rng::roll_dice(1, 3);
+            if *tt == TileType::Bridge && rng.roll_dice(1, 6)==1 {
+                let roll = rng.roll_dice(1, 3);
                 match roll {
                     1 => build_data.spawn_list.push((idx, "Dock Worker".to_string())),
                     2 => build_data.spawn_list.push((idx, "Wannabe Pirate".to_string())),
@@ -421,12 +442,15 @@ impl TownBuilder {
     }
 
     fn spawn_townsfolk(&mut self,
+        rng : &mut RandomNumberGenerator,
         build_data : &mut BuilderMap,
         available_building_tiles : &mut HashSet<usize>)
     {
-        for idx in available_building_tiles.iter() {
-            if crate::rng::roll_dice(1, 10)==1 {
-                let roll = crate::rng::roll_dice(1, 4);
+        let mut tiles : Vec<usize> = available_building_tiles.iter().copied().collect();
+        tiles.sort_unstable();
+        for idx in tiles.iter() {
+            if rng.roll_dice(1, 10)==1 {
+                let roll = rng.roll_dice(1, 4);
                 match roll {
                     1 => build_data.spawn_list.push((*idx, "Peasant".to_string())),
                     2 => build_data.spawn_list.push((*idx, "Drunk".to_string())),
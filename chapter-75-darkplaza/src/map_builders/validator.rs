@@ -0,0 +1,177 @@
+use super::{MetaMapBuilder, BuilderMap, TileType};
+use rltk::RandomNumberGenerator;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Something wrong with a finished map.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapValidationError {
+    NoStartingPosition,
+    StartNotWalkable { x: i32, y: i32 },
+    NoDownStairs,
+    DownStairsUnreachable { x: i32, y: i32 },
+    DownStairsOnStart { x: i32, y: i32 },
+    BranchStairsUnreachable { x: i32, y: i32 },
+    NoBranchStairs { branch: String },
+    SpawnOutOfBounds { idx: usize, name: String },
+    SpawnNotWalkable { x: i32, y: i32, name: String },
+    OpenBorder { x: i32, y: i32 }
+}
+
+impl fmt::Display for MapValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapValidationError::NoStartingPosition => write!(f, "No starting position"),
+            MapValidationError::StartNotWalkable{x, y} => write!(f, "Starting position ({}, {}) is not walkable", x, y),
+            MapValidationError::NoDownStairs => write!(f, "No down staircase"),
+            MapValidationError::DownStairsUnreachable{x, y} => write!(f, "Down staircase at ({}, {}) can't be reached from the start", x, y),
+            MapValidationError::DownStairsOnStart{x, y} => write!(f, "Down staircase at ({}, {}) is where the up staircase goes", x, y),
+            MapValidationError::BranchStairsUnreachable{x, y} => write!(f, "Branch staircase at ({}, {}) can't be reached from the start", x, y),
+            MapValidationError::NoBranchStairs{branch} => write!(f, "No staircase into {}", branch),
+            MapValidationError::SpawnOutOfBounds{idx, name} => write!(f, "{} spawns at index {}, outside the map", name, idx),
+            MapValidationError::SpawnNotWalkable{x, y, name} => write!(f, "{} spawns on an unwalkable tile at ({}, {})", name, x, y),
+            MapValidationError::OpenBorder{x, y} => write!(f, "Border tile ({}, {}) is walkable", x, y)
+        }
+    }
+}
+
+/// Checks a finished map: the down and branch stairs must be there and reachable from the
+/// start over walkable tiles, every spawn must be on a walkable tile, and the border must be solid.
+/// The bottom level of a branch is the only one allowed no way down.
+pub fn validate_map(build_data : &BuilderMap) -> Result<(), Vec<MapValidationError>> {
+    let map = &build_data.map;
+    let mut errors = Vec::new();
+
+    // The border must be solid
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if x == 0 || y == 0 || x == map.width-1 || y == map.height-1 {
+                let idx = map.xy_idx(x, y);
+                if map.walkable(idx) {
+                    errors.push(MapValidationError::OpenBorder{ x, y });
+                }
+            }
+        }
+    }
+
+    // Spawns must be on walkable tiles
+    for (idx, name) in build_data.spawn_list.iter() {
+        if *idx >= map.tiles.len() {
+            errors.push(MapValidationError::SpawnOutOfBounds{ idx: *idx, name: name.clone() });
+        } else if !map.walkable(*idx) {
+            errors.push(MapValidationError::SpawnNotWalkable{
+                x: *idx as i32 % map.width,
+                y: *idx as i32 / map.width,
+                name: name.clone()
+            });
+        }
+    }
+
+    // Every down staircase must be reachable from the start
+    let stairs : Vec<usize> = map.tiles.iter()
+        .enumerate()
+        .filter(|(_, tt)| **tt == TileType::DownStairs)
+        .map(|(idx, _)| idx)
+        .collect();
+    let branch_stairs : Vec<usize> = map.tiles.iter()
+        .enumerate()
+        .filter(|(_, tt)| **tt == TileType::BranchStairs)
+        .map(|(idx, _)| idx)
+        .collect();
+    let branch_bottom = crate::raws::get_branch(&build_data.raws, &map.branch)
+        .map(|b| b.last_depth == map.depth)
+        .unwrap_or(false);
+    if stairs.is_empty() && !branch_bottom {
+        errors.push(MapValidationError::NoDownStairs);
+    }
+
+    // Every branch that starts here needs its way in
+    for b in crate::raws::get_branches_from(&build_data.raws, &map.key()) {
+        if !map.branch_entrances.values().any(|name| *name == b.name) {
+            errors.push(MapValidationError::NoBranchStairs{ branch: b.name });
+        }
+    }
+
+    match &build_data.starting_position {
+        None => errors.push(MapValidationError::NoStartingPosition),
+        Some(start) => {
+            let start_idx = map.xy_idx(start.x, start.y);
+            if !map.walkable(start_idx) {
+                errors.push(MapValidationError::StartNotWalkable{ x: start.x, y: start.y });
+            } else {
+                if stairs.contains(&start_idx) {
+                    errors.push(MapValidationError::DownStairsOnStart{ x: start.x, y: start.y });
+                }
+                let reachable = reachable_tiles(build_data, start_idx);
+                for idx in stairs.iter().filter(|idx| !reachable[**idx]) {
+                    errors.push(MapValidationError::DownStairsUnreachable{
+                        x: *idx as i32 % map.width,
+                        y: *idx as i32 / map.width
+                    });
+                }
+                for idx in branch_stairs.iter().filter(|idx| !reachable[**idx]) {
+                    errors.push(MapValidationError::BranchStairsUnreachable{
+                        x: *idx as i32 % map.width,
+                        y: *idx as i32 / map.width
+                    });
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Flood fills walkable tiles from `start_idx`, moving in the same 8 directions the player can.
+fn reachable_tiles(build_data : &BuilderMap, start_idx : usize) -> Vec<bool> {
+    let map = &build_data.map;
+    let mut reachable = vec![false; map.tiles.len()];
+    let mut open_list = VecDeque::new();
+    reachable[start_idx] = true;
+    open_list.push_back(start_idx);
+
+    while let Some(idx) = open_list.pop_front() {
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        for dy in -1 ..= 1 {
+            for dx in -1 ..= 1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx > map.width-1 || ny < 0 || ny > map.height-1 { continue; }
+                let nidx = map.xy_idx(nx, ny);
+                if !reachable[nidx] && map.walkable(nidx) {
+                    reachable[nidx] = true;
+                    open_list.push_back(nidx);
+                }
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Runs `validate_map` as part of a chain. Problems are logged and kept in
+/// `BuilderMap::validation_errors` rather than aborting the build.
+pub struct MapValidator {}
+
+impl MetaMapBuilder for MapValidator {
+    fn build_map(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(build_data);
+    }
+}
+
+impl MapValidator {
+    pub fn new() -> Box<MapValidator> {
+        Box::new(MapValidator{})
+    }
+
+    fn build(&mut self, build_data : &mut BuilderMap) {
+        if let Err(errors) = validate_map(build_data) {
+            for e in errors.iter() {
+                crate::console_log(format!("Map validation ({}): {}", build_data.map.name, e));
+            }
+            build_data.validation_errors = errors;
+        } else {
+            build_data.validation_errors.clear();
+        }
+    }
+}
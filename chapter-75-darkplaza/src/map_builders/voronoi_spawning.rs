@@ -1,11 +1,12 @@
 use super::{MetaMapBuilder, BuilderMap, TileType, spawner};
-use std::collections::HashMap;
+use rltk::RandomNumberGenerator;
+use std::collections::BTreeMap;
 
 pub struct VoronoiSpawning {}
 
 impl MetaMapBuilder for VoronoiSpawning {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -16,9 +17,9 @@ impl VoronoiSpawning {
     }
 
     #[allow(clippy::map_entry)]
-    fn build(&mut self, build_data : &mut BuilderMap) {
-        let mut noise_areas : HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = rltk::FastNoise::seeded(crate::rng::roll_dice(1, 65536) as u64);
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let mut noise_areas : BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
         noise.set_noise_type(rltk::NoiseType::Cellular);
         noise.set_frequency(0.08);
         noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
@@ -41,7 +42,7 @@ impl VoronoiSpawning {
 
         // Spawn the entities
         for area in noise_areas.iter() {
-            spawner::spawn_region(&build_data.map, area.1, build_data.map.depth, &mut build_data.spawn_list);
+            spawner::spawn_region(&build_data.map, rng, &build_data.raws, area.1, build_data.map.depth, &mut build_data.spawn_list);
         }
     }
 }
@@ -59,10 +59,10 @@ pub fn build_patterns(map : &Map, chunk_size: i32, include_flipping: bool, dedup
 
     // Dedupe
     if dedupe {
-        rltk::console::log(format!("Pre de-duplication, there are {} patterns", patterns.len()));
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set.into_iter());
-        rltk::console::log(format!("There are {} patterns", patterns.len()));
+        crate::console_log(format!("Pre de-duplication, there are {} patterns", patterns.len()));
+        let mut seen: HashSet<Vec<TileType>> = HashSet::new();
+        patterns.retain(|p| seen.insert(p.clone())); // dedup, keeping the original order
+        crate::console_log(format!("There are {} patterns", patterns.len()));
     }
 
     patterns
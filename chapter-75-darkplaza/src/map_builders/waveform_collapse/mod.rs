@@ -1,17 +1,54 @@
-use super::{MetaMapBuilder, BuilderMap, Map, TileType};
+use super::{MetaMapBuilder, BuilderMap, Map, TileType, wall_in_border};
+use rltk::RandomNumberGenerator;
 mod common;
 use common::*;
 mod constraints;
 use constraints::*;
 mod solver;
 use solver::*;
+use std::time::Duration;
+
+/// Where the builder gets the patterns it stitches together.
+#[derive(Clone)]
+pub enum WaveformSample {
+    /// Chunks of the map left by the previous builder in the chain.
+    PreviousMap,
+    /// A REX Paint image, by embedded resource path: `#` is wall, anything else floor.
+    RexFile(String)
+}
+
+pub struct WaveformSettings {
+    pub sample : WaveformSample,
+    pub chunk_size : i32,
+    /// How many times to start again from an empty map after the solver gives up.
+    pub max_retries : usize,
+    /// How many choices the solver may undo before giving up on an attempt.
+    pub max_backtracks : usize,
+    /// Wall-clock limit for all attempts together. The result then depends on machine
+    /// speed, so seeded runs only reproduce when this is `None`. Ignored on the web.
+    pub time_budget : Option<Duration>
+}
+
+impl Default for WaveformSettings {
+    fn default() -> WaveformSettings {
+        WaveformSettings{
+            sample : WaveformSample::PreviousMap,
+            chunk_size : 8,
+            max_retries : 10,
+            max_backtracks : 2000,
+            time_budget : None
+        }
+    }
+}
 
 /// Provides a map builder using the Wave Function Collapse algorithm.
-pub struct WaveformCollapseBuilder {}
+pub struct WaveformCollapseBuilder {
+    settings : WaveformSettings
+}
 
 impl MetaMapBuilder for WaveformCollapseBuilder {
-    fn build_map(&mut self, build_data : &mut BuilderMap)  {
-        self.build(build_data);
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap)  {
+        self.build(rng, build_data);
     }
 }
 
@@ -19,43 +56,88 @@ impl WaveformCollapseBuilder {
     /// Constructor for waveform collapse.
     #[allow(dead_code)]
     pub fn new() -> Box<WaveformCollapseBuilder> {
-        Box::new(WaveformCollapseBuilder{})
+        Box::new(WaveformCollapseBuilder{ settings : WaveformSettings::default() })
+    }
+
+    /// Waveform collapse using a REX Paint sample, such as `../../resources/wfc-demo1.xp`.
+    #[allow(dead_code)]
+    pub fn rex_sample(path : &str) -> Box<WaveformCollapseBuilder> {
+        Box::new(WaveformCollapseBuilder{
+            settings : WaveformSettings{ sample : WaveformSample::RexFile(path.to_string()), ..Default::default() }
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn with_settings(settings : WaveformSettings) -> Box<WaveformCollapseBuilder> {
+        Box::new(WaveformCollapseBuilder{ settings })
     }
 
-    fn build(&mut self, build_data : &mut BuilderMap) {
-        const CHUNK_SIZE :i32 = 8;
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let chunk_size = self.settings.chunk_size;
         build_data.take_snapshot();
 
-        let patterns = build_patterns(&build_data.map, CHUNK_SIZE, true, true);
-        let constraints = patterns_to_constraints(patterns, CHUNK_SIZE);
-        self.render_tile_gallery(&constraints, CHUNK_SIZE, build_data);
+        let sample = match &self.settings.sample {
+            WaveformSample::PreviousMap => build_data.map.clone(),
+            WaveformSample::RexFile(path) => match load_rex_sample(path, &build_data.map) {
+                Ok(sample) => sample,
+                Err(e) => {
+                    crate::console_log(format!("WARNING - {}; sampling the previous map instead", e));
+                    build_data.map.clone()
+                }
+            }
+        };
+        let patterns = build_patterns(&sample, chunk_size, true, true);
+        let constraints = patterns_to_constraints(patterns, chunk_size);
 
+        // Keep what the previous builders made, in case we can't do better
         let old_map = build_data.map.clone();
-
-        build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name);
+        let old_spawns = build_data.spawn_list.clone();
+        let old_rooms = build_data.rooms.take();
+        let old_corridors = build_data.corridors.take();
         build_data.spawn_list.clear();
-        build_data.rooms = None;
-        build_data.corridors = None;
-        let mut tries = 0;
-        loop {
-            let mut solver = Solver::new(constraints.clone(), CHUNK_SIZE, &build_data.map);
-            while !solver.iteration(&mut build_data.map) {
+
+        self.render_tile_gallery(&constraints, chunk_size, build_data);
+
+        let deadline = Deadline::after(self.settings.time_budget);
+        let mut solved = false;
+        for attempt in 0 ..= self.settings.max_retries {
+            build_data.map = Map::new(old_map.depth, build_data.width, build_data.height, &old_map.name, &old_map.tile_set);
+            build_data.map.branch = old_map.branch.clone();
+            build_data.map.theme = old_map.theme.clone();
+            build_data.map.theme_regions = old_map.theme_regions.clone();
+            let mut solver = Solver::new(constraints.clone(), chunk_size, &build_data.map, self.settings.max_backtracks);
+            while !solver.iteration(rng, &mut build_data.map) {
                 build_data.take_snapshot();
+                if deadline.passed() { break; }
             }
             build_data.take_snapshot();
-            if solver.possible { break; } // If it has hit an impossible condition, try again
-            tries += 1;
-            if tries > 10 { break; }
+
+            if solver.possible && !deadline.passed() {
+                // Chunks go right up to the edge of the map, so close it off
+                wall_in_border(build_data);
+
+                // A solution that is all wall is no use to anyone
+                if build_data.map.tiles.iter().any(|tt| build_data.map.tile_set.walkable(*tt)) {
+                    solved = true;
+                    break;
+                }
+            }
+            if deadline.passed() {
+                crate::console_log(format!("Waveform collapse ran out of time on attempt {}", attempt + 1));
+                break;
+            }
         }
 
-        if tries > 10 {
-            // Restore the old one
+        if !solved {
             build_data.map = old_map;
+            build_data.spawn_list = old_spawns;
+            build_data.rooms = old_rooms;
+            build_data.corridors = old_corridors;
         }
     }
 
     fn render_tile_gallery(&mut self, constraints: &[MapChunk], chunk_size: i32, build_data : &mut BuilderMap) {
-        build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name);
+        build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name, &build_data.raws.tiles);
         let mut counter = 0;
         let mut x = 1;
         let mut y = 1;
@@ -71,7 +153,7 @@ impl WaveformCollapseBuilder {
                 if y + chunk_size > build_data.map.height {
                     // Move to the next page
                     build_data.take_snapshot();
-                    build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name);
+                    build_data.map = Map::new(build_data.map.depth, build_data.width, build_data.height, &build_data.map.name, &build_data.raws.tiles);
 
                     x = 1;
                     y = 1;
@@ -83,3 +165,47 @@ impl WaveformCollapseBuilder {
         build_data.take_snapshot();
     }
 }
+
+/// Loads a REX Paint image as a sample map: `#` is wall, everything else is floor.
+fn load_rex_sample(path : &str, map : &Map) -> Result<Map, String> {
+    let xp_file = crate::rex_assets::load_xp(path)
+        .map_err(|e| format!("Unable to load waveform sample: {}", e))?;
+    let layer = xp_file.layers.first().ok_or_else(|| format!("Waveform sample {} has no layers", path))?;
+    let mut sample = Map::new(map.depth, layer.width as i32, layer.height as i32, &map.name, &map.tile_set);
+    for y in 0..layer.height {
+        for x in 0..layer.width {
+            let cell = layer.get(x, y).unwrap();
+            let idx = sample.xy_idx(x as i32, y as i32);
+            sample.tiles[idx] = if cell.ch == '#' as u32 { TileType::Wall } else { TileType::Floor };
+        }
+    }
+    Ok(sample)
+}
+
+/// An optional point in time to give up by. The web build has no clock, so it never expires there.
+struct Deadline {
+    #[cfg(not(target_arch = "wasm32"))]
+    at : Option<std::time::Instant>
+}
+
+impl Deadline {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn after(budget : Option<Duration>) -> Deadline {
+        Deadline{ at : budget.map(|b| std::time::Instant::now() + b) }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn after(_budget : Option<Duration>) -> Deadline {
+        Deadline{}
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn passed(&self) -> bool {
+        self.at.map(|at| std::time::Instant::now() >= at).unwrap_or(false)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn passed(&self) -> bool {
+        false
+    }
+}
@@ -1,5 +1,11 @@
-use super::{MapChunk, Map};
-use std::collections::HashSet;
+use super::{MapChunk, Map, TileType};
+use rltk::RandomNumberGenerator;
+
+/// A chunk the solver has filled, and the patterns it hasn't tried there yet.
+struct Decision {
+    chunk_index : usize,
+    untried : Vec<usize>
+}
 
 pub struct Solver {
     constraints: Vec<MapChunk>,
@@ -7,18 +13,18 @@ pub struct Solver {
     chunks : Vec<Option<usize>>,
     chunks_x : usize,
     chunks_y : usize,
-    remaining : Vec<(usize, i32)>, // (index, # neighbors)
+    remaining : Vec<usize>,
+    decisions : Vec<Decision>,
+    max_backtracks : usize,
+    pub backtracks : usize,
     pub possible: bool
 }
 
 impl Solver {
-    pub fn new(constraints: Vec<MapChunk>, chunk_size: i32, map : &Map) -> Solver {
+    pub fn new(constraints: Vec<MapChunk>, chunk_size: i32, map : &Map, max_backtracks : usize) -> Solver {
         let chunks_x = (map.width / chunk_size) as usize;
         let chunks_y = (map.height / chunk_size) as usize;
-        let mut remaining : Vec<(usize, i32)> = Vec::new();
-        for i in 0..(chunks_x*chunks_y) {
-            remaining.push((i, 0));
-        }
+        let remaining : Vec<usize> = (0..(chunks_x*chunks_y)).collect();
 
         Solver {
             constraints,
@@ -27,6 +33,9 @@ impl Solver {
             chunks_x,
             chunks_y,
             remaining,
+            decisions: Vec::new(),
+            max_backtracks,
+            backtracks: 0,
             possible: true
         }
     }
@@ -35,194 +44,124 @@ impl Solver {
         ((y * self.chunks_x) + x) as usize
     }
 
-    fn count_neighbors(&self, chunk_x:usize, chunk_y:usize) -> i32 {
-        let mut neighbors = 0;
-
-        if chunk_x > 0 {
-            let left_idx = self.chunk_idx(chunk_x-1, chunk_y);
-            match self.chunks[left_idx] {
-                None => {}
-                Some(_) => {
-                    neighbors += 1;
-                }
-            }
-        }
+    /// The chunks around `chunk_index`, each with the side of that chunk which faces it.
+    fn neighbors(&self, chunk_index : usize) -> Vec<(usize, usize)> {
+        let chunk_x = chunk_index % self.chunks_x;
+        let chunk_y = chunk_index / self.chunks_x;
+        let mut neighbors = Vec::new();
+        if chunk_x > 0 { neighbors.push((self.chunk_idx(chunk_x-1, chunk_y), 3)); }
+        if chunk_x < self.chunks_x-1 { neighbors.push((self.chunk_idx(chunk_x+1, chunk_y), 2)); }
+        if chunk_y > 0 { neighbors.push((self.chunk_idx(chunk_x, chunk_y-1), 1)); }
+        if chunk_y < self.chunks_y-1 { neighbors.push((self.chunk_idx(chunk_x, chunk_y+1), 0)); }
+        neighbors
+    }
 
-        if chunk_x < self.chunks_x-1 {
-            let right_idx = self.chunk_idx(chunk_x+1, chunk_y);
-            match self.chunks[right_idx] {
-                None => {}
-                Some(_) => {
-                    neighbors += 1;
-                }
-            }
-        }
+    fn count_neighbors(&self, chunk_index : usize) -> i32 {
+        self.neighbors(chunk_index).iter().filter(|(n, _)| self.chunks[*n].is_some()).count() as i32
+    }
 
-        if chunk_y > 0 {
-            let up_idx = self.chunk_idx(chunk_x, chunk_y-1);
-            match self.chunks[up_idx] {
-                None => {}
-                Some(_) => {
-                    neighbors += 1;
+    /// Every pattern that fits alongside the chunks already placed around `chunk_index`.
+    fn options_for(&self, chunk_index : usize) -> Vec<usize> {
+        let mut allowed = vec![true; self.constraints.len()];
+        for (neighbor, side) in self.neighbors(chunk_index) {
+            if let Some(nt) = self.chunks[neighbor] {
+                let mut fits = vec![false; self.constraints.len()];
+                for i in self.constraints[nt].compatible_with[side].iter() {
+                    fits[*i] = true;
                 }
-            }
-        }
-
-        if chunk_y < self.chunks_y-1 {
-            let down_idx = self.chunk_idx(chunk_x, chunk_y+1);
-            match self.chunks[down_idx] {
-                None => {}
-                Some(_) => {
-                    neighbors += 1;
+                for (a, f) in allowed.iter_mut().zip(fits.iter()) {
+                    *a = *a && *f;
                 }
             }
         }
-        neighbors
+        (0..self.constraints.len()).filter(|i| allowed[*i]).collect()
     }
 
-    pub fn iteration(&mut self, map: &mut Map) -> bool {
-        if self.remaining.is_empty() { return true; }
-
-        // Populate the neighbor count of the remaining list
-        let mut remain_copy = self.remaining.clone();
-        let mut neighbors_exist = false;
-        for r in remain_copy.iter_mut() {
-            let idx = r.0;
-            let chunk_x = idx % self.chunks_x;
-            let chunk_y = idx / self.chunks_x;
-            let neighbor_count = self.count_neighbors(chunk_x, chunk_y);
-            if neighbor_count > 0 { neighbors_exist = true; }
-            *r = (r.0, neighbor_count);
-        }
-        remain_copy.sort_by(|a,b| b.1.cmp(&a.1));
-        self.remaining = remain_copy;
-
-        // Pick a random chunk we haven't dealt with yet and get its index, remove from remaining list
-        let remaining_index = if !neighbors_exist {
-            (crate::rng::roll_dice(1, self.remaining.len() as i32)-1) as usize
-        } else {
-            0usize
-        };
-        let chunk_index = self.remaining[remaining_index].0;
-        self.remaining.remove(remaining_index);
-
-        let chunk_x = chunk_index % self.chunks_x;
-        let chunk_y = chunk_index / self.chunks_x;
-
-        let mut neighbors = 0;
-        let mut options : Vec<Vec<usize>> = Vec::new();
+    /// Placing a chunk is pointless if it leaves an empty neighbor with nothing that fits.
+    fn neighbors_still_solvable(&self, chunk_index : usize) -> bool {
+        self.neighbors(chunk_index)
+            .iter()
+            .filter(|(n, _)| self.chunks[*n].is_none())
+            .all(|(n, _)| !self.options_for(*n).is_empty())
+    }
 
-        if chunk_x > 0 {
-            let left_idx = self.chunk_idx(chunk_x-1, chunk_y);
-            match self.chunks[left_idx] {
-                None => {}
-                Some(nt) => {
-                    neighbors += 1;
-                    options.push(self.constraints[nt].compatible_with[3].clone());
-                }
-            }
+    fn shuffle(rng : &mut RandomNumberGenerator, options : &mut [usize]) {
+        for i in (1..options.len()).rev() {
+            let j = (rng.roll_dice(1, i as i32 + 1) - 1) as usize;
+            options.swap(i, j);
         }
+    }
 
-        if chunk_x < self.chunks_x-1 {
-            let right_idx = self.chunk_idx(chunk_x+1, chunk_y);
-            match self.chunks[right_idx] {
-                None => {}
-                Some(nt) => {
-                    neighbors += 1;
-                    options.push(self.constraints[nt].compatible_with[2].clone());
-                }
+    fn draw_chunk(&self, map : &mut Map, chunk_index : usize) {
+        let chunk_x = (chunk_index % self.chunks_x) as i32;
+        let chunk_y = (chunk_index / self.chunks_x) as i32;
+        let left_x = chunk_x * self.chunk_size;
+        let top_y = chunk_y * self.chunk_size;
+
+        let mut i : usize = 0;
+        for y in top_y .. top_y + self.chunk_size {
+            for x in left_x .. left_x + self.chunk_size {
+                let mapidx = map.xy_idx(x, y);
+                map.tiles[mapidx] = match self.chunks[chunk_index] {
+                    Some(pattern) => self.constraints[pattern].pattern[i],
+                    None => TileType::Wall
+                };
+                i += 1;
             }
         }
+    }
 
-        if chunk_y > 0 {
-            let up_idx = self.chunk_idx(chunk_x, chunk_y-1);
-            match self.chunks[up_idx] {
-                None => {}
-                Some(nt) => {
-                    neighbors += 1;
-                    options.push(self.constraints[nt].compatible_with[1].clone());
-                }
-            }
+    /// Fills in one more chunk, backing up through earlier choices if nothing fits. Returns
+    /// true once the map is complete, or once the solver has given up.
+    pub fn iteration(&mut self, rng : &mut RandomNumberGenerator, map: &mut Map) -> bool {
+        if self.remaining.is_empty() || !self.possible { return true; }
+
+        // Work outwards from what's already placed: take the open chunk with the most
+        // placed neighbors, or a random one if nothing is placed nearby.
+        let mut best = (0usize, -1);
+        for (i, chunk_index) in self.remaining.iter().enumerate() {
+            let neighbor_count = self.count_neighbors(*chunk_index);
+            if neighbor_count > best.1 { best = (i, neighbor_count); }
         }
+        let remaining_index = if best.1 == 0 {
+            (rng.roll_dice(1, self.remaining.len() as i32)-1) as usize
+        } else {
+            best.0
+        };
+        let chunk_index = self.remaining.remove(remaining_index);
 
-        if chunk_y < self.chunks_y-1 {
-            let down_idx = self.chunk_idx(chunk_x, chunk_y+1);
-            match self.chunks[down_idx] {
-                None => {}
-                Some(nt) => {
-                    neighbors += 1;
-                    options.push(self.constraints[nt].compatible_with[0].clone());
-                }
-            }
-        }
+        let mut untried = self.options_for(chunk_index);
+        Solver::shuffle(rng, &mut untried);
+        self.decisions.push(Decision{ chunk_index, untried });
+        self.advance(map);
 
-        if neighbors == 0 {
-            // There is nothing nearby, so we can have anything!
-            let new_chunk_idx = (crate::rng::roll_dice(1, self.constraints.len() as i32)-1) as usize;
-            self.chunks[chunk_index] = Some(new_chunk_idx);
-            let left_x = chunk_x as i32 * self.chunk_size as i32;
-            let right_x = (chunk_x as i32+1) * self.chunk_size as i32;
-            let top_y = chunk_y as i32 * self.chunk_size as i32;
-            let bottom_y = (chunk_y as i32+1) * self.chunk_size as i32;
-
-
-            let mut i : usize = 0;
-            for y in top_y .. bottom_y {
-                for x in left_x .. right_x {
-                    let mapidx = map.xy_idx(x, y);
-                    let tile = self.constraints[new_chunk_idx].pattern[i];
-                    map.tiles[mapidx] = tile;
-                    i += 1;
-                }
-            }
-        }
-        else {
-            // There are neighbors, so we try to be compatible with them
-            let mut options_to_check : HashSet<usize> = HashSet::new();
-            for o in options.iter() {
-                for i in o.iter() {
-                    options_to_check.insert(*i);
-                }
-            }
+        !self.possible
+    }
 
-            let mut possible_options : Vec<usize> = Vec::new();
-            for new_chunk_idx in options_to_check.iter() {
-                let mut possible = true;
-                for o in options.iter() {
-                    if !o.contains(new_chunk_idx) { possible = false; }
-                }
-                if possible {
-                    possible_options.push(*new_chunk_idx);
+    /// Tries the next pattern for the latest decision. When a decision runs out of
+    /// patterns it is undone, and the one before it moves on to its next pattern instead.
+    fn advance(&mut self, map : &mut Map) {
+        while let Some(decision) = self.decisions.last_mut() {
+            let chunk_index = decision.chunk_index;
+            if let Some(pattern) = decision.untried.pop() {
+                self.chunks[chunk_index] = Some(pattern);
+                if self.neighbors_still_solvable(chunk_index) {
+                    self.draw_chunk(map, chunk_index);
+                    return;
                 }
-            }
-
-            if possible_options.is_empty() {
-                rltk::console::log("Oh no! It's not possible!");
-                self.possible = false;
-                return true;
             } else {
-                let new_chunk_idx = if possible_options.len() == 1 { 0 }
-                    else { crate::rng::roll_dice(1, possible_options.len() as i32)-1 };
-
-                self.chunks[chunk_index] = Some(possible_options[new_chunk_idx as usize]);
-                let left_x = chunk_x as i32 * self.chunk_size as i32;
-                let right_x = (chunk_x as i32+1) * self.chunk_size as i32;
-                let top_y = chunk_y as i32 * self.chunk_size as i32;
-                let bottom_y = (chunk_y as i32+1) * self.chunk_size as i32;
-
-
-                let mut i : usize = 0;
-                for y in top_y .. bottom_y {
-                    for x in left_x .. right_x {
-                        let mapidx = map.xy_idx(x, y);
-                        let tile = self.constraints[possible_options[new_chunk_idx as usize]].pattern[i];
-                        map.tiles[mapidx] = tile;
-                        i += 1;
-                    }
+                self.decisions.pop();
+                self.chunks[chunk_index] = None;
+                self.draw_chunk(map, chunk_index);
+                self.remaining.push(chunk_index);
+                self.backtracks += 1;
+
+                if self.decisions.is_empty() || self.backtracks > self.max_backtracks {
+                    crate::console_log(format!("Oh no! It's not possible! (gave up after {} backtracks)", self.backtracks));
+                    self.possible = false;
+                    return;
                 }
             }
         }
-
-        false
     }
 }
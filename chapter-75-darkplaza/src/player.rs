@@ -1,11 +1,12 @@
-use rltk::{VirtualKeyCode, Rltk, Point};
+use rltk::Point;
 use specs::prelude::*;
 use std::cmp::{max, min};
 use super::{Position, Player, Viewshed, State, Map, RunState, Attributes, WantsToMelee, Item,
     WantsToPickupItem, TileType, HungerClock, HungerState,
     EntityMoved, Door, BlocksTile, BlocksVisibility, Renderable, Pools, Faction,
     raws::Reaction, Vendor, VendorMode, WantsToCastSpell, Target, Equipped, Weapon,
-    WantsToShoot, Name};
+    WantsToShoot, Name, Digger, Digging, LightSource, effects::add_effect, effects::EffectType,
+    effects::Targets, effects::DOOR_NOISE, effects::SHOUT_NOISE, Darkvision, gamelog::GameLog, input::PlayerCommand};
 
 fn get_player_target_list(ecs : &mut World) -> Vec<(f32,Entity)> {
     let mut possible_targets : Vec<(f32,Entity)> = Vec::new();
@@ -16,6 +17,7 @@ fn get_player_target_list(ecs : &mut World) -> Vec<(f32,Entity)> {
     let map = ecs.fetch::<Map>();
     let positions = ecs.read_storage::<Position>();
     let factions = ecs.read_storage::<Faction>();
+    let darkvision = ecs.read_storage::<Darkvision>();
     for (equipped, weapon) in (&equipped, &weapon).join() {
         if equipped.owner == *player_entity && weapon.range.is_some() {
             let range = weapon.range.unwrap();
@@ -25,8 +27,9 @@ fn get_player_target_list(ecs : &mut World) -> Vec<(f32,Entity)> {
                 for tile_point in vs.visible_tiles.iter() {
                     let tile_idx = map.xy_idx(tile_point.x, tile_point.y);
                     let distance_to_target = rltk::DistanceAlg::Pythagoras.distance2d(*tile_point, rltk::Point::new(player_pos.x, player_pos.y));
-                    if distance_to_target < range as f32 {
-                        crate::spatial::for_each_tile_content(tile_idx, |possible_target| {
+                    let in_sight = map.can_make_out(rltk::Point::new(player_pos.x, player_pos.y), darkvision.get(*player_entity), tile_idx);
+                    if distance_to_target < range as f32 && in_sight {
+                        map.spatial.for_each_tile_content(tile_idx, |possible_target| {
                             if possible_target != *player_entity && factions.get(possible_target).is_some() {
                                 possible_targets.push((distance_to_target, possible_target));
                             }
@@ -69,13 +72,13 @@ fn fire_on_target(ecs: &mut World) -> RunState {
                 .append("You fire at")
                 .color(rltk::CYAN)
                 .append(&name.name)
-                .log();
+                .log(&mut ecs.write_resource::<GameLog>());
         }
         shoot_store.insert(*player_entity, WantsToShoot{ target }).expect("Insert Fail");
 
         RunState::Ticking
     } else {
-        crate::gamelog::Logger::new().append("You don't have a target selected!").log();
+        crate::gamelog::Logger::new().append("You don't have a target selected!").log(&mut ecs.write_resource::<GameLog>());
         RunState::AwaitingInput
     }
 
@@ -125,6 +128,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let mut renderables = ecs.write_storage::<Renderable>();
     let factions = ecs.read_storage::<Faction>();
     let vendors = ecs.read_storage::<Vendor>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let diggers = ecs.read_storage::<Digger>();
+    let raws = crate::raws::get_raws(ecs);
     let mut result = RunState::AwaitingInput;
 
     let mut swap_entities : Vec<(Entity, i32, i32)> = Vec::new();
@@ -133,7 +139,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
         if pos.x + delta_x < 1 || pos.x + delta_x > map.width-1 || pos.y + delta_y < 1 || pos.y + delta_y > map.height-1 { return RunState::AwaitingInput; }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
-        result = crate::spatial::for_each_tile_content_with_gamemode(destination_idx, |potential_target| {
+        result = map.spatial.for_each_tile_content_with_gamemode(destination_idx, |potential_target| {
             if let Some(_vendor) = vendors.get(potential_target) {
                 return Some(RunState::ShowVendor{ vendor: potential_target, mode : VendorMode::Sell });
             }
@@ -144,7 +150,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                     let reaction = crate::raws::faction_reaction(
                         &faction.name,
                         "Player",
-                        &crate::raws::RAWS.lock().unwrap()
+                        &raws
                     );
                     if reaction != Reaction::Attack { hostile = false; }
                 }
@@ -178,12 +184,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                 let glyph = renderables.get_mut(potential_target).unwrap();
                 glyph.glyph = rltk::to_cp437('/');
                 viewshed.dirty = true;
+                add_effect(ecs, Some(entity), EffectType::Noise{ volume : DOOR_NOISE }, Targets::Tile{ tile_idx : destination_idx as i32 });
                 return Some(RunState::Ticking);
             }
             None
         });
 
-        if !crate::spatial::is_blocked(destination_idx) {
+        if !map.spatial.is_blocked(destination_idx) {
             pos.x = min(map.width-1 , max(0, pos.x + delta_x));
             pos.y = min(map.height-1, max(0, pos.y + delta_y));
             entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
@@ -196,8 +203,19 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
             match map.tiles[destination_idx] {
                 TileType::DownStairs => result = RunState::NextLevel,
                 TileType::UpStairs => result = RunState::PreviousLevel,
+                TileType::BranchStairs => result = RunState::EnterBranch,
                 _ => {}
             }
+        } else if map.tile_set.has_flag(map.tiles[destination_idx], "diggable") {
+            // Walking into rock with a pick-axe in hand starts digging
+            let dig_turns = (&equipped, &diggers).join()
+                .find(|(equip, _)| equip.owner == entity)
+                .map(|(_, digger)| digger.turns);
+            if let Some(turns) = dig_turns {
+                crate::gamelog::Logger::new().append("You start digging.").log(&mut ecs.write_resource::<GameLog>());
+                add_effect(ecs, Some(entity), EffectType::Dig{ turns }, Targets::Tile{ tile_idx : destination_idx as i32 });
+                result = RunState::Ticking;
+            }
         }
     }
 
@@ -212,15 +230,17 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     result
 }
 
-pub fn try_next_level(ecs: &mut World) -> bool {
+pub fn try_next_level(ecs: &mut World) -> Option<RunState> {
     let player_pos = ecs.fetch::<Point>();
     let map = ecs.fetch::<Map>();
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
-    if map.tiles[player_idx] == TileType::DownStairs {
-        true
-    } else {
-        crate::gamelog::Logger::new().append("There is no way down from here.").log();
-        false
+    match map.tiles[player_idx] {
+        TileType::DownStairs => Some(RunState::NextLevel),
+        TileType::BranchStairs => Some(RunState::EnterBranch),
+        _ => {
+            crate::gamelog::Logger::new().append("There is no way down from here.").log(&mut ecs.write_resource::<GameLog>());
+            None
+        }
     }
 }
 
@@ -231,7 +251,7 @@ pub fn try_previous_level(ecs: &mut World) -> bool {
     if map.tiles[player_idx] == TileType::UpStairs {
         true
     } else {
-        crate::gamelog::Logger::new().append("There is no way up from here.").log();
+        crate::gamelog::Logger::new().append("There is no way up from here.").log(&mut ecs.write_resource::<GameLog>());
         false
     }
 }
@@ -251,7 +271,7 @@ fn get_item(ecs: &mut World) {
     }
 
     match target_item {
-        None => crate::gamelog::Logger::new().append("There is nothing here to pick up.").log(),
+        None => crate::gamelog::Logger::new().append("There is nothing here to pick up.").log(&mut ecs.write_resource::<GameLog>()),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup.insert(*player_entity, WantsToPickupItem{ collected_by: *player_entity, item }).expect("Unable to insert want to pickup");
@@ -259,18 +279,30 @@ fn get_item(ecs: &mut World) {
     }
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
+fn shout(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    crate::gamelog::Logger::new().append("You shout!").log(&mut ecs.write_resource::<GameLog>());
+    add_effect(ecs, Some(*player_entity), EffectType::Noise{ volume : SHOUT_NOISE }, Targets::Single{ target : *player_entity });
+    RunState::Ticking
+}
+
+/// Whether the player can make out anything that would attack them.
+fn hostile_in_view(ecs: &World) -> bool {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let factions = ecs.read_storage::<Faction>();
 
     let worldmap_resource = ecs.fetch::<Map>();
+    let player_pos = *ecs.fetch::<Point>();
+    let darkvision = ecs.read_storage::<Darkvision>();
+    let raws = crate::raws::get_raws(ecs);
 
-    let mut can_heal = true;
+    let mut hostile = false;
     let viewshed = viewshed_components.get(*player_entity).unwrap();
     for tile in viewshed.visible_tiles.iter() {
         let idx = worldmap_resource.xy_idx(tile.x, tile.y);
-        crate::spatial::for_each_tile_content(idx, |entity_id| {
+        if !worldmap_resource.can_make_out(player_pos, darkvision.get(*player_entity), idx) { continue; }
+        worldmap_resource.spatial.for_each_tile_content(idx, |entity_id| {
             let faction = factions.get(entity_id);
             match faction {
                 None => {}
@@ -278,15 +310,34 @@ fn skip_turn(ecs: &mut World) -> RunState {
                     let reaction = crate::raws::faction_reaction(
                         &faction.name,
                         "Player",
-                        &crate::raws::RAWS.lock().unwrap()
+                        &raws
                     );
                     if reaction == Reaction::Attack {
-                        can_heal = false;
+                        hostile = true;
                     }
                 }
             }
         });
     }
+    hostile
+}
+
+fn toggle_light(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut lights = ecs.write_storage::<LightSource>();
+    if let Some(light) = lights.get_mut(player_entity) {
+        light.lit = !light.lit;
+        let message = if light.lit { "You uncover your lantern." } else { "You cover your lantern, and the dark closes in." };
+        crate::gamelog::Logger::new().append(message).log(&mut ecs.write_resource::<GameLog>());
+        RunState::Ticking
+    } else {
+        RunState::AwaitingInput
+    }
+}
+
+fn skip_turn(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut can_heal = !hostile_in_view(ecs);
 
     let hunger_clocks = ecs.read_storage::<HungerClock>();
     let hc = hunger_clocks.get(*player_entity);
@@ -302,7 +353,7 @@ fn skip_turn(ecs: &mut World) -> RunState {
         let mut health_components = ecs.write_storage::<Pools>();
         let pools = health_components.get_mut(*player_entity).unwrap();
         pools.hit_points.current = i32::min(pools.hit_points.current + 1, pools.hit_points.max);
-        if crate::rng::roll_dice(1,6)==1 {
+        if ecs.write_resource::<rltk::RandomNumberGenerator>().roll_dice(1,6)==1 {
             pools.mana.current = i32::min(pools.mana.current + 1, pools.mana.max);
         }
     }
@@ -364,122 +415,93 @@ fn use_spell_hotkey(gs: &mut State, key: i32) -> RunState {
                 return RunState::Ticking;
             }
         } else {
-            crate::gamelog::Logger::new().append("You don't have enough mana to cast that!").log();
+            crate::gamelog::Logger::new().append("You don't have enough mana to cast that!").log(&mut gs.ecs.write_resource::<GameLog>());
         }
     }
 
     RunState::Ticking
 }
 
-pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
-    // Hotkeys
-    if ctx.shift && ctx.key.is_some() {
-        let key : Option<i32> =
-            match ctx.key.unwrap() {
-                VirtualKeyCode::Key1 => Some(1),
-                VirtualKeyCode::Key2 => Some(2),
-                VirtualKeyCode::Key3 => Some(3),
-                VirtualKeyCode::Key4 => Some(4),
-                VirtualKeyCode::Key5 => Some(5),
-                VirtualKeyCode::Key6 => Some(6),
-                VirtualKeyCode::Key7 => Some(7),
-                VirtualKeyCode::Key8 => Some(8),
-                VirtualKeyCode::Key9 => Some(9),
-                _ => None
-            };
-        if let Some(key) = key {
-            return use_consumable_hotkey(gs, key-1);
-        }
-    }
-    if ctx.control && ctx.key.is_some() {
-        let key : Option<i32> =
-            match ctx.key.unwrap() {
-                VirtualKeyCode::Key1 => Some(1),
-                VirtualKeyCode::Key2 => Some(2),
-                VirtualKeyCode::Key3 => Some(3),
-                VirtualKeyCode::Key4 => Some(4),
-                VirtualKeyCode::Key5 => Some(5),
-                VirtualKeyCode::Key6 => Some(6),
-                VirtualKeyCode::Key7 => Some(7),
-                VirtualKeyCode::Key8 => Some(8),
-                VirtualKeyCode::Key9 => Some(9),
-                _ => None
-            };
-        if let Some(key) = key {
-            return use_spell_hotkey(gs, key-1);
-        }
-    }
-
-    // Player movement
-    match ctx.key {
-        None => { return RunState::AwaitingInput } // Nothing happened
-        Some(key) => match key {
-            VirtualKeyCode::Left |
-            VirtualKeyCode::Numpad4 |
-            VirtualKeyCode::H => return try_move_player(-1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Right |
-            VirtualKeyCode::Numpad6 |
-            VirtualKeyCode::L => return try_move_player(1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Up |
-            VirtualKeyCode::Numpad8 |
-            VirtualKeyCode::K => return try_move_player(0, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Down |
-            VirtualKeyCode::Numpad2 |
-            VirtualKeyCode::J => return try_move_player(0, 1, &mut gs.ecs),
-
-            // Diagonals
-            VirtualKeyCode::Numpad9 |
-            VirtualKeyCode::U => return try_move_player(1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad7 |
-            VirtualKeyCode::Y => return try_move_player(-1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad3 |
-            VirtualKeyCode::N => return try_move_player(1, 1, &mut gs.ecs),
+/// Whether the player is partway through digging through some rock.
+pub fn is_digging(ecs: &World) -> bool {
+    ecs.read_storage::<Digging>().contains(*ecs.fetch::<Entity>())
+}
 
-            VirtualKeyCode::Numpad1 |
-            VirtualKeyCode::B => return try_move_player(-1, 1, &mut gs.ecs),
+/// While the player is digging, `KeepDigging` puts another turn into the rock. Anything
+/// else stops them, as does an enemy coming into view; then this returns `None` if there's
+/// a command left to carry out as usual.
+pub fn keep_digging(ecs: &mut World, command: PlayerCommand) -> Option<RunState> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let dig = ecs.read_storage::<Digging>().get(player_entity).cloned()?;
+
+    let (next_to_rock, carry_on) = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = *ecs.fetch::<Point>();
+        let (dx, dy) = (dig.tile_idx % map.width - player_pos.x, dig.tile_idx / map.width - player_pos.y);
+        // Walking into the same rock again is carrying on too
+        (i32::abs(dx) <= 1 && i32::abs(dy) <= 1,
+            command == PlayerCommand::KeepDigging || command == PlayerCommand::Move{ dx, dy })
+    };
+    if carry_on && next_to_rock && !hostile_in_view(ecs) {
+        add_effect(ecs, Some(player_entity), EffectType::Dig{ turns : dig.turns }, Targets::Tile{ tile_idx : dig.tile_idx });
+        return Some(RunState::Ticking);
+    }
 
-            // Skip Turn
-            VirtualKeyCode::Numpad5 |
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+    ecs.write_storage::<Digging>().remove(player_entity);
+    crate::gamelog::Logger::new().append("You stop digging.").log(&mut ecs.write_resource::<GameLog>());
+    if command == PlayerCommand::KeepDigging { Some(RunState::AwaitingInput) } else { None }
+}
 
-            // Level changes
-            VirtualKeyCode::Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
-            }
-            VirtualKeyCode::Comma => {
-                if try_previous_level(&mut gs.ecs) {
-                    return RunState::PreviousLevel;
-                }
+pub fn player_input(gs: &mut State, command: PlayerCommand) -> RunState {
+    match command {
+        PlayerCommand::UseConsumable(slot) => use_consumable_hotkey(gs, slot),
+        PlayerCommand::CastSpell(slot) => use_spell_hotkey(gs, slot),
+        PlayerCommand::Move{ dx, dy } => try_move_player(dx, dy, &mut gs.ecs),
+        PlayerCommand::Wait => skip_turn(&mut gs.ecs),
+        PlayerCommand::Shout => shout(&mut gs.ecs),
+        PlayerCommand::ToggleLight => toggle_light(&mut gs.ecs),
+
+        // Level changes
+        PlayerCommand::Descend => try_next_level(&mut gs.ecs).unwrap_or(RunState::Ticking),
+        PlayerCommand::Ascend => {
+            if try_previous_level(&mut gs.ecs) {
+                RunState::PreviousLevel
+            } else {
+                RunState::Ticking
             }
+        }
 
-            // Picking up items
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::D => return RunState::ShowDropItem,
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
-
-            // Ranged
-            VirtualKeyCode::V => {
-                cycle_target(&mut gs.ecs);
-                return RunState::AwaitingInput;
+        // Picking up items
+        PlayerCommand::PickUp => {
+            get_item(&mut gs.ecs);
+            RunState::Ticking
+        }
+        PlayerCommand::OpenInventory => RunState::ShowInventory,
+        PlayerCommand::OpenDropMenu => RunState::ShowDropItem,
+        PlayerCommand::OpenRemoveMenu => RunState::ShowRemoveItem,
+
+        // Ranged
+        PlayerCommand::CycleTarget => {
+            cycle_target(&mut gs.ecs);
+            RunState::AwaitingInput
+        }
+        PlayerCommand::Fire => fire_on_target(&mut gs.ecs),
+
+        // Save and Quit
+        PlayerCommand::SaveAndQuit => {
+            let slot = crate::saveload_system::run_slot(&gs.ecs);
+            if gs.options.permadeath {
+                // A run with one life gets one save
+                RunState::SaveGame{ slot }
+            } else {
+                RunState::ShowSaveMenu{ name : slot }
             }
-            VirtualKeyCode::F => return fire_on_target(&mut gs.ecs),
-
-            // Save and Quit
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+        }
 
-            // Cheating!
-            VirtualKeyCode::Backslash => return RunState::ShowCheatMenu,
+        // Cheating!
+        PlayerCommand::OpenCheatMenu => RunState::ShowCheatMenu,
 
-            _ => { return RunState::AwaitingInput }
-        },
+        // Menu commands mean nothing out here
+        _ => RunState::AwaitingInput
     }
-    RunState::Ticking
 }
@@ -1,4 +1,5 @@
 use crate::raws::{SpawnTableType, spawn_type_by_name, RawMaster};
+use rltk::RandomNumberGenerator;
 
 pub struct RandomEntry {
     name : String,
@@ -35,12 +36,12 @@ impl MasterTable {
         }
     }
 
-    pub fn roll(&self) -> String {
-        let roll = crate::rng::roll_dice(1, 4);
+    pub fn roll(&self, rng : &mut RandomNumberGenerator) -> String {
+        let roll = rng.roll_dice(1, 4);
         match roll {
-            1 => self.items.roll(),
-            2 => self.props.roll(),
-            3 => self.mobs.roll(),
+            1 => self.items.roll(rng),
+            2 => self.props.roll(rng),
+            3 => self.mobs.roll(rng),
             _ => "None".to_string()
         }
     }
@@ -64,9 +65,9 @@ impl RandomTable {
         }
     }
 
-    pub fn roll(&self) -> String {
+    pub fn roll(&self, rng : &mut RandomNumberGenerator) -> String {
         if self.total_weight == 0 { return "None".to_string(); }
-        let mut roll = crate::rng::roll_dice(1, self.total_weight)-1;
+        let mut roll = rng.roll_dice(1, self.total_weight)-1;
         let mut index : usize = 0;
 
         while roll > 0 {
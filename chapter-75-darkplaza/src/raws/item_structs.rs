@@ -14,7 +14,8 @@ pub struct Item {
     pub vendor_category : Option<String>,
     pub magic : Option<MagicItem>,
     pub attributes : Option<ItemAttributeBonus>,
-    pub template_magic : Option<ItemMagicTemplate>
+    pub template_magic : Option<ItemMagicTemplate>,
+    pub dig_turns : Option<i32>
 }
 
 #[derive(Deserialize, Debug, Clone)]
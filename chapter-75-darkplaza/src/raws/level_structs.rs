@@ -0,0 +1,50 @@
+use serde::{Deserialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct LevelRaws {
+    #[serde(default)]
+    pub branches : Vec<Branch>,
+    pub levels : Vec<LevelChain>
+}
+
+/// A side dungeon, entered by a branch staircase on `entrance_depth` of its parent
+/// branch. Its levels run from `first_depth` to `last_depth`, and only spawn entries
+/// tagged with its name.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Branch {
+    pub name : String,
+    pub title : String,
+    pub parent : String,
+    pub entrance_depth : i32,
+    pub first_depth : i32,
+    pub last_depth : i32
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LevelChain {
+    #[serde(default = "crate::map::main_branch")]
+    pub branch : String,
+    pub depth : i32,
+    pub name : String,
+    pub width : Option<i32>,
+    pub height : Option<i32>,
+    #[serde(default)]
+    pub theme : String,
+    #[serde(default)]
+    pub theme_regions : Vec<crate::map::ThemeRegion>,
+    pub starter : BuilderStep,
+    pub builders : Vec<BuilderStep>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuilderStep {
+    pub builder : String,
+    pub params : Option<HashMap<String, String>>
+}
+
+impl BuilderStep {
+    pub fn param(&self, key : &str) -> Option<&str> {
+        self.params.as_ref().and_then(|p| p.get(key)).map(|s| s.as_str())
+    }
+}
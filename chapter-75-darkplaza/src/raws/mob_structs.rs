@@ -23,7 +23,9 @@ pub struct Mob {
     pub gold : Option<String>,
     pub vendor : Option<Vec<String>>,
     pub abilities : Option<Vec<MobAbility>>,
-    pub on_death : Option<Vec<MobAbility>>
+    pub on_death : Option<Vec<MobAbility>>,
+    pub dig_turns : Option<i32>,
+    pub darkvision : Option<i32>
 }
 
 #[derive(Deserialize, Debug)]
@@ -51,7 +53,9 @@ pub struct NaturalAttack {
 #[derive(Deserialize, Debug)]
 pub struct MobLight {
     pub range : i32,
-    pub color : String
+    pub color : String,
+    pub falloff : Option<String>,
+    pub flicker : Option<f32>
 }
 
 #[derive(Deserialize, Debug)]
@@ -14,17 +14,20 @@ mod spell_structs;
 pub use spell_structs::Spell;
 mod weapon_traits;
 pub use weapon_traits::*;
+mod level_structs;
+pub use level_structs::*;
+mod tile_structs;
+pub use tile_structs::*;
 
 mod rawmaster;
 pub use rawmaster::*;
 use serde::{Deserialize};
-use std::sync::Mutex;
+use specs::prelude::*;
+use std::sync::Arc;
 
 rltk::embedded_resource!(RAW_FILE, "../../raws/spawns.json");
-
-lazy_static! {
-    pub static ref RAWS : Mutex<RawMaster> = Mutex::new(RawMaster::empty());
-}
+rltk::embedded_resource!(LEVEL_FILE, "../../raws/levels.json");
+rltk::embedded_resource!(TILE_FILE, "../../raws/tiles.json");
 
 #[derive(Deserialize, Debug)]
 pub struct Raws {
@@ -38,7 +41,16 @@ pub struct Raws {
     pub weapon_traits : Vec<WeaponTrait>
 }
 
-pub fn load_raws() {
+/// Reads the embedded raw files, tiles and vaults included, for the caller to hold on to.
+pub fn load_raws() -> RawMaster {
+    rltk::link_resource!(TILE_FILE, "../../raws/tiles.json");
+    let tile_data = rltk::embedding::EMBED
+        .lock()
+        .get_resource("../../raws/tiles.json".to_string())
+        .unwrap();
+    let tile_string = std::str::from_utf8(&tile_data).expect("Unable to convert to a valid UTF-8 string.");
+    let tiles : TileRaws = serde_json::from_str(&tile_string).expect("Unable to parse tile JSON");
+
     rltk::link_resource!(RAW_FILE, "../../raws/spawns.json");
 
     // Retrieve the raw data as an array of u8 (8-bit unsigned chars)
@@ -49,5 +61,50 @@ pub fn load_raws() {
     let raw_string = std::str::from_utf8(&raw_data).expect("Unable to convert to a valid UTF-8 string.");
     let decoder : Raws = serde_json::from_str(&raw_string).expect("Unable to parse JSON");
 
-    RAWS.lock().unwrap().load(decoder);
+    let mut raws = RawMaster::empty();
+    raws.load(decoder);
+    raws.tiles = Arc::new(crate::map::TileSet::from_raws(&tiles.tiles));
+
+    rltk::link_resource!(LEVEL_FILE, "../../raws/levels.json");
+    let level_data = rltk::embedding::EMBED
+        .lock()
+        .get_resource("../../raws/levels.json".to_string())
+        .unwrap();
+    let level_string = std::str::from_utf8(&level_data).expect("Unable to convert to a valid UTF-8 string.");
+    let levels : LevelRaws = serde_json::from_str(&level_string).expect("Unable to parse level JSON");
+
+    raws.load_levels(levels);
+
+    raws.vaults = crate::map_builders::load_vaults();
+    raws
+}
+
+/// A fingerprint of the raw files, vaults included, so a replay can tell whether it's being
+/// played back with the same ones it was recorded with.
+pub fn raws_hash() -> u64 {
+    use std::hash::Hasher;
+    rltk::link_resource!(RAW_FILE, "../../raws/spawns.json");
+    rltk::link_resource!(LEVEL_FILE, "../../raws/levels.json");
+    rltk::link_resource!(TILE_FILE, "../../raws/tiles.json");
+
+    let mut files = vec!["../../raws/spawns.json", "../../raws/levels.json", "../../raws/tiles.json"];
+    files.extend(crate::map_builders::link_vault_files());
+
+    let mut hasher = crate::replay::StableHasher::default();
+    for file in files {
+        let data = rltk::embedding::EMBED
+            .lock()
+            .get_resource(file.to_string())
+            .unwrap();
+        // The name goes in too, since the order vaults are read in changes which get picked
+        hasher.write(file.as_bytes());
+        hasher.write(data);
+    }
+    hasher.finish()
+}
+
+/// The raws a world was set up with. They never change once loaded, so this hands out
+/// another reference to them instead of a borrow of the world; spawning needs both.
+pub fn get_raws(ecs : &World) -> Arc<RawMaster> {
+    Arc::clone(&ecs.fetch::<Arc<RawMaster>>())
 }
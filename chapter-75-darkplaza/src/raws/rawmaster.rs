@@ -1,10 +1,14 @@
 use std::collections::{HashMap, HashSet};
 use specs::prelude::*;
 use crate::components::*;
-use super::{Raws, faction_structs::Reaction};
+use super::{Raws, faction_structs::Reaction, LevelRaws, LevelChain, Branch};
+use crate::map::{LevelKey, TileSet};
+use crate::map_builders::VaultLibrary;
+use std::sync::Arc;
 use crate::random_table::{MasterTable, RandomTable};
 use crate::{attr_bonus, npc_hp, mana_at_level};
 use regex::Regex;
+use rltk::RandomNumberGenerator;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
 pub fn parse_dice_string(dice : &str) -> (i32, i32, i32) {
@@ -43,7 +47,14 @@ pub struct RawMaster {
     prop_index : HashMap<String, usize>,
     loot_index : HashMap<String, usize>,
     faction_index : HashMap<String, HashMap<String, Reaction>>,
-    spell_index : HashMap<String, usize>
+    spell_index : HashMap<String, usize>,
+    levels : Vec<LevelChain>,
+    level_index : HashMap<LevelKey, usize>,
+    branches : Vec<Branch>,
+    /// What each kind of tile is like, from `tiles.json`.
+    pub tiles : Arc<TileSet>,
+    /// The hand-drawn rooms and sections from `raws/vaults`.
+    pub vaults : VaultLibrary
 }
 
 struct NewMagicItem {
@@ -69,7 +80,12 @@ impl RawMaster {
             prop_index : HashMap::new(),
             loot_index : HashMap::new(),
             faction_index : HashMap::new(),
-            spell_index : HashMap::new()
+            spell_index : HashMap::new(),
+            levels : Vec::new(),
+            level_index : HashMap::new(),
+            branches : Vec::new(),
+            tiles : Arc::new(TileSet::default()),
+            vaults : VaultLibrary::empty()
         }
     }
 
@@ -89,7 +105,7 @@ impl RawMaster {
                     });
                 }
             } else {
-                rltk::console::log(format!("{} is marked as templated, but isn't a weapon or armor.", item.name));
+                crate::console_log(format!("{} is marked as templated, but isn't a weapon or armor.", item.name));
             }
         }
     }
@@ -151,7 +167,9 @@ impl RawMaster {
                 weight : 10 - i32::abs(nmw.bonus),
                 min_depth : 1 + i32::abs((nmw.bonus-1)*3),
                 max_depth : 100,
-                add_map_depth_to_weight : None
+                add_map_depth_to_weight : None,
+                branch : None,
+                time : None
             });
         }
     }
@@ -177,7 +195,9 @@ impl RawMaster {
                         weight : 9 - i32::abs(nmw.bonus),
                         min_depth : 2 + i32::abs((nmw.bonus-1)*3),
                         max_depth : 100,
-                        add_map_depth_to_weight : None
+                        add_map_depth_to_weight : None,
+                        branch : None,
+                        time : None
                     });
                 }
             }
@@ -192,7 +212,7 @@ impl RawMaster {
 
         for (i,item) in self.raws.items.iter().enumerate() {
             if used_names.contains(&item.name) {
-                rltk::console::log(format!("WARNING -  duplicate item name in raws [{}]", item.name));
+                crate::console_log(format!("WARNING -  duplicate item name in raws [{}]", item.name));
             }
             self.item_index.insert(item.name.clone(), i);
             used_names.insert(item.name.clone());
@@ -201,14 +221,14 @@ impl RawMaster {
         }
         for (i,mob) in self.raws.mobs.iter().enumerate() {
             if used_names.contains(&mob.name) {
-                rltk::console::log(format!("WARNING -  duplicate mob name in raws [{}]", mob.name));
+                crate::console_log(format!("WARNING -  duplicate mob name in raws [{}]", mob.name));
             }
             self.mob_index.insert(mob.name.clone(), i);
             used_names.insert(mob.name.clone());
         }
         for (i,prop) in self.raws.props.iter().enumerate() {
             if used_names.contains(&prop.name) {
-                rltk::console::log(format!("WARNING -  duplicate prop name in raws [{}]", prop.name));
+                crate::console_log(format!("WARNING -  duplicate prop name in raws [{}]", prop.name));
             }
             self.prop_index.insert(prop.name.clone(), i);
             used_names.insert(prop.name.clone());
@@ -216,7 +236,7 @@ impl RawMaster {
 
         for spawn in self.raws.spawn_table.iter() {
             if !used_names.contains(&spawn.name) {
-                rltk::console::log(format!("WARNING - Spawn tables references unspecified entity {}", spawn.name));
+                crate::console_log(format!("WARNING - Spawn tables references unspecified entity {}", spawn.name));
             }
         }
 
@@ -246,6 +266,22 @@ impl RawMaster {
         self.build_magic_weapon_or_armor(&items_to_build);
         self.build_traited_weapons(&items_to_build);
     }
+
+    pub fn load_levels(&mut self, levels : LevelRaws) {
+        self.levels = levels.levels;
+        self.branches = levels.branches;
+        self.level_index = HashMap::new();
+        for (i,level) in self.levels.iter().enumerate() {
+            let key = LevelKey::new(&level.branch, level.depth);
+            if self.level_index.contains_key(&key) {
+                crate::console_log(format!("WARNING -  duplicate level in raws [{} {}]", level.branch, level.depth));
+            }
+            if !key.is_main() && !self.branches.iter().any(|b| b.name == level.branch) {
+                crate::console_log(format!("WARNING -  level in unknown branch [{}]", level.branch));
+            }
+            self.level_index.insert(key, i);
+        }
+    }
 }
 
 #[inline(always)]
@@ -299,8 +335,7 @@ pub fn get_vendor_items(categories: &[String], raws : &RawMaster) -> Vec<(String
     result
 }
 
-pub fn get_scroll_tags() -> Vec<String> {
-    let raws = &super::RAWS.lock().unwrap();
+pub fn get_scroll_tags(raws : &RawMaster) -> Vec<String> {
     let mut result = Vec::new();
 
     for item in raws.raws.items.iter() {
@@ -314,8 +349,7 @@ pub fn get_scroll_tags() -> Vec<String> {
     result
 }
 
-pub fn get_potion_tags() -> Vec<String> {
-    let raws = &super::RAWS.lock().unwrap();
+pub fn get_potion_tags(raws : &RawMaster) -> Vec<String> {
     let mut result = Vec::new();
 
     for item in raws.raws.items.iter() {
@@ -329,8 +363,7 @@ pub fn get_potion_tags() -> Vec<String> {
     result
 }
 
-pub fn is_tag_magic(tag : &str) -> bool {
-    let raws = &super::RAWS.lock().unwrap();
+pub fn is_tag_magic(raws : &RawMaster, tag : &str) -> bool {
     if raws.item_index.contains_key(tag) {
         let item_template = &raws.raws.items[raws.item_index[tag]];
         item_template.magic.is_some()
@@ -371,7 +404,7 @@ pub fn string_to_slot(slot : &str) -> EquipmentSlot {
         "Feet" => EquipmentSlot::Feet,
         "Hands" => EquipmentSlot::Hands,
         "Melee" => EquipmentSlot::Melee,
-        _ => { rltk::console::log(format!("Warning: unknown equipment slot type [{}])", slot)); EquipmentSlot::Melee }
+        _ => { crate::console_log(format!("Warning: unknown equipment slot type [{}])", slot)); EquipmentSlot::Melee }
     }
 }
 
@@ -393,6 +426,38 @@ fn parse_particle(n : &str) -> SpawnParticleBurst {
     }
 }
 
+fn light_source(light : &super::mob_structs::MobLight) -> LightSource {
+    let falloff = match light.falloff.as_deref() {
+        None | Some("linear") => LightFalloff::Linear,
+        Some("quadratic") => LightFalloff::Quadratic,
+        Some("flat") => LightFalloff::Flat,
+        Some(other) => {
+            crate::console_log(format!("Warning: unknown light falloff [{}], using linear.", other));
+            LightFalloff::Linear
+        }
+    };
+    LightSource{
+        range: light.range,
+        color : rltk::RGB::from_hex(&light.color).expect("Bad color"),
+        falloff,
+        flicker : light.flicker.unwrap_or(0.0),
+        lit : true
+    }
+}
+
+fn parse_gas(n : &str) -> Option<ReleasesGas> {
+    let tokens : Vec<_> = n.split(';').collect();
+    let gas = crate::map::GasType::named(tokens[0]);
+    let amount = tokens.get(1).and_then(|amount| amount.parse::<f32>().ok());
+    match (gas, amount) {
+        (Some(gas), Some(amount)) => Some(ReleasesGas{ gas, amount }),
+        _ => {
+            crate::console_log(format!("Warning: can't make sense of gas [{}], so nothing is released.", n));
+            None
+        }
+    }
+}
+
 macro_rules! apply_effects {
     ( $effects:expr, $eb:expr ) => {
         for effect in $effects.iter() {
@@ -419,7 +484,11 @@ macro_rules! apply_effects {
                 "slow" => $eb = $eb.with(Slow{ initiative_penalty : effect.1.parse::<f32>().unwrap() }),
                 "damage_over_time" => $eb = $eb.with( DamageOverTime { damage : effect.1.parse::<i32>().unwrap() } ),
                 "target_self" => $eb = $eb.with( AlwaysTargetsSelf{} ),
-                _ => rltk::console::log(format!("Warning: consumable effect {} not implemented.", effect_name))
+                "dig" => $eb = $eb.with( Digger{ turns : effect.1.parse::<i32>().unwrap() } ),
+                "ignite" => $eb = $eb.with( Ignites{} ),
+                "gas" => if let Some(gas) = parse_gas(&effect.1) { $eb = $eb.with(gas) },
+                "noise" => $eb = $eb.with( MakesNoise{ volume : effect.1.parse::<i32>().unwrap() } ),
+                _ => crate::console_log(format!("Warning: consumable effect {} not implemented.", effect_name))
             }
         }
     };
@@ -484,6 +553,10 @@ pub fn spawn_named_item(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
             }
         }
 
+        if let Some(turns) = item_template.dig_turns {
+            eb = eb.with(Digger{ turns });
+        }
+
         if let Some(wearable) = &item_template.wearable {
             let slot = string_to_slot(&wearable.slot);
             eb = eb.with(Equippable{ slot });
@@ -535,6 +608,12 @@ pub fn spawn_named_item(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
 pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity> {
     if raws.mob_index.contains_key(key) {
         let mob_template = &raws.raws.mobs[raws.mob_index[key]];
+        let gold = if let Some(gold) = &mob_template.gold {
+            let (n, d, b) = parse_dice_string(&gold);
+            (ecs.write_resource::<RandomNumberGenerator>().roll_dice(n, d) + b) as f32
+        } else {
+            0.0
+        };
 
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
 
@@ -605,12 +684,7 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
             mana: Pool{current: mob_mana, max: mob_mana},
             total_weight : 0.0,
             total_initiative_penalty : 0.0,
-            gold : if let Some(gold) = &mob_template.gold {
-                    let (n, d, b) = parse_dice_string(&gold);
-                    (crate::rng::roll_dice(n, d) + b) as f32
-                } else {
-                    0.0
-                },
+            gold,
             god_mode : false
         };
         eb = eb.with(pools);
@@ -626,7 +700,7 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
                     "Melee" => { skills.skills.insert(Skill::Melee, *sk.1); }
                     "Defense" => { skills.skills.insert(Skill::Defense, *sk.1); }
                     "Magic" => { skills.skills.insert(Skill::Magic, *sk.1); }
-                    _ => { rltk::console::log(format!("Unknown skill referenced: [{}]", sk.0)); }
+                    _ => { crate::console_log(format!("Unknown skill referenced: [{}]", sk.0)); }
                 }
             }
         }
@@ -660,7 +734,7 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
         }
 
         if let Some(light) = &mob_template.light {
-            eb = eb.with(LightSource{ range: light.range, color : rltk::RGB::from_hex(&light.color).expect("Bad color") });
+            eb = eb.with(light_source(light));
         }
 
         if let Some(faction) = &mob_template.faction {
@@ -673,6 +747,14 @@ pub fn spawn_named_mob(raws: &RawMaster, ecs : &mut World, key : &str, pos : Spa
             eb = eb.with(Vendor{ categories : vendor.clone() });
         }
 
+        if let Some(turns) = mob_template.dig_turns {
+            eb = eb.with(Digger{ turns });
+        }
+
+        if let Some(range) = mob_template.darkvision {
+            eb = eb.with(Darkvision{ range });
+        }
+
         if let Some(ability_list) = &mob_template.abilities {
             let mut a = SpecialAbilities { abilities : Vec::new() };
             for ability in ability_list.iter() {
@@ -745,6 +827,9 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
         if let Some(blocks_visibility) = prop_template.blocks_visibility {
             if blocks_visibility { eb = eb.with(BlocksVisibility{}) };
         }
+        if let Some(flammable) = prop_template.flammable {
+            if flammable { eb = eb.with(Flammable{}) };
+        }
         if let Some(door_open) = prop_template.door_open {
             eb = eb.with(Door{ open: door_open });
         }
@@ -753,8 +838,7 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs : &mut World, key : &str, pos : Sp
             apply_effects!(entry_trigger.effects, eb);
         }
         if let Some(light) = &prop_template.light {
-            eb = eb.with(LightSource{ range: light.range, color : rltk::RGB::from_hex(&light.color).expect("Bad color") });
-            eb = eb.with(Viewshed{ range: light.range, dirty: true, visible_tiles: Vec::new() });
+            eb = eb.with(light_source(light));
         }
 
 
@@ -778,9 +862,9 @@ pub fn spawn_named_spell(raws: &RawMaster, ecs : &mut World, key : &str) -> Opti
 }
 
 pub fn spawn_all_spells(ecs : &mut World) {
-    let raws = &super::RAWS.lock().unwrap();
+    let raws = super::get_raws(ecs);
     for spell in raws.raws.spells.iter() {
-        spawn_named_spell(raws, ecs, &spell.name);
+        spawn_named_spell(&raws, ecs, &spell.name);
     }
 }
 
@@ -835,12 +919,25 @@ pub fn spawn_type_by_name(raws: &RawMaster, key : &str) -> SpawnTableType {
     }
 }
 
-pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> MasterTable {
+/// What can turn up on a level as it's built, whatever the time of day.
+pub fn get_spawn_table_for_depth(raws: &RawMaster, branch: &str, depth: i32) -> MasterTable {
+    spawn_table_for(raws, branch, depth, None)
+}
+
+/// What only turns up at this time of day: the spawn table entries marked "day" or "night".
+pub fn get_spawn_table_for_time(raws: &RawMaster, branch: &str, depth: i32, night: bool) -> MasterTable {
+    spawn_table_for(raws, branch, depth, Some(if night { "night" } else { "day" }))
+}
+
+fn spawn_table_for(raws: &RawMaster, branch: &str, depth: i32, time: Option<&str>) -> MasterTable {
     use super::SpawnTableEntry;
 
+    // Untagged entries belong to the main dungeon; branches only get their own
     let available_options : Vec<&SpawnTableEntry> = raws.raws.spawn_table
         .iter()
+        .filter(|a| a.branch.as_deref().unwrap_or(crate::map::MAIN_BRANCH) == branch)
         .filter(|a| depth >= a.min_depth && depth <= a.max_depth)
+        .filter(|a| a.time.as_deref() == time)
         .collect();
 
     let mut rt = MasterTable::new();
@@ -855,16 +952,33 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> MasterTable {
     rt
 }
 
-pub fn get_item_drop(raws: &RawMaster, table: &str) -> Option<String> {
+pub fn get_item_drop(raws: &RawMaster, rng: &mut RandomNumberGenerator, table: &str) -> Option<String> {
     if raws.loot_index.contains_key(table) {
         let mut rt = RandomTable::new();
         let available_options = &raws.raws.loot_tables[raws.loot_index[table]];
         for item in available_options.drops.iter() {
             rt.add(item.name.clone(), item.weight);
         }
-        let result =rt.roll();
+        let result =rt.roll(rng);
         return Some(result);
     }
 
     None
 }
+
+pub fn get_level_chain(raws: &RawMaster, level: &LevelKey) -> Option<LevelChain> {
+    raws.level_index.get(level).map(|i| raws.levels[*i].clone())
+}
+
+pub fn get_branch(raws: &RawMaster, name: &str) -> Option<Branch> {
+    raws.branches.iter().find(|b| b.name == name).cloned()
+}
+
+/// Branches whose entrance staircase is on `level`.
+pub fn get_branches_from(raws: &RawMaster, level: &LevelKey) -> Vec<Branch> {
+    raws.branches
+        .iter()
+        .filter(|b| b.parent == level.branch && b.entrance_depth == level.depth)
+        .cloned()
+        .collect()
+}
@@ -6,5 +6,8 @@ pub struct SpawnTableEntry {
     pub weight : i32,
     pub min_depth: i32,
     pub max_depth: i32,
-    pub add_map_depth_to_weight : Option<bool>
+    pub add_map_depth_to_weight : Option<bool>,
+    pub branch : Option<String>,
+    /// "day" or "night" for things that only turn up then.
+    pub time : Option<String>
 }
@@ -0,0 +1,35 @@
+use serde::{Deserialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct TileRaws {
+    pub tiles : Vec<TileDef>
+}
+
+/// A kind of terrain. `themes` gives it a different look on levels with that theme.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TileDef {
+    pub name : String,
+    pub walkable : bool,
+    pub opaque : bool,
+    #[serde(default = "default_cost")]
+    pub cost : f32,
+    #[serde(default)]
+    pub flags : Vec<String>,
+    pub appearance : TileLook,
+    #[serde(default)]
+    pub themes : HashMap<String, TileLook>
+}
+
+fn default_cost() -> f32 { 1.0 }
+
+/// A glyph and colours, e.g. `{ "glyph" : "~", "fg" : "#00FFFF" }`. A `connected` tile is
+/// drawn with lines joining it to its neighbours, like the walls of the default theme.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TileLook {
+    pub glyph : String,
+    pub fg : String,
+    pub bg : Option<String>,
+    #[serde(default)]
+    pub connected : bool
+}
@@ -0,0 +1,109 @@
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator, SerializeComponents, DeserializeComponents, ConvertSaveload};
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use serde_json::Value;
+use crate::components::*;
+
+/// Tells whether a component could be written to a save: `(&Probe::<T>(PhantomData)).saveable()`
+/// finds `CanSave` if `T` can be converted for saving, and falls back to `CantSave` if not.
+struct Probe<T>(PhantomData<T>);
+
+trait CanSave {
+    fn saveable(&self) -> bool { true }
+}
+
+impl<T : ConvertSaveload<SimpleMarker<SerializeMe>>> CanSave for Probe<T> {}
+
+trait CantSave {
+    fn saveable(&self) -> bool { false }
+}
+
+impl<T> CantSave for &Probe<T> {}
+
+/// Builds everything that has to know the full list of components from the one list below:
+/// registering them with the world, and writing them to and reading them from a save.
+macro_rules! component_registry {
+    ( saved { $( $saved:ty ),* $(,)? } not_saved { $( $not_saved:ty ),* $(,)? } ) => {
+        /// Registers every component, and the marker that picks out what gets saved.
+        pub fn register_components(ecs : &mut World) {
+            ecs.register::<SimpleMarker<SerializeMe>>();
+            $( ecs.register::<$saved>(); )*
+            $( ecs.register::<$not_saved>(); )*
+        }
+
+        /// The names saved components are stored under in a save file.
+        pub const SAVED_COMPONENTS : &[&str] = &[ $( stringify!($saved) ),* ];
+
+        /// Every registered component that could be written to a save, whether it is or not;
+        /// anything here that's missing from `SAVED_COMPONENTS` is lost on saving.
+        pub fn saveable_components() -> Vec<&'static str> {
+            let mut names = Vec::new();
+            $( if (&Probe::<$saved>(PhantomData)).saveable() { names.push(stringify!($saved)); } )*
+            $( if (&Probe::<$not_saved>(PhantomData)).saveable() { names.push(stringify!($not_saved)); } )*
+            names
+        }
+
+        /// Every saved component's storage, under its name, for the entities marked to be
+        /// saved.
+        pub(crate) fn serialize_components(ecs : &World) -> serde_json::Map<String, Value> {
+            let data = ( ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>() );
+            let mut components = serde_json::Map::new();
+            $(
+            components.insert(
+                stringify!($saved).to_string(),
+                SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
+                    &( ecs.read_storage::<$saved>(), ),
+                    &data.0,
+                    &data.1,
+                    serde_json::value::Serializer,
+                )
+                .unwrap()
+            );
+            )*
+            components
+        }
+
+        /// Reads back what `serialize_components` wrote. A component the save doesn't have
+        /// is left empty, and anything the save has that isn't listed here is ignored.
+        pub(crate) fn deserialize_components(ecs : &mut World, components : &mut serde_json::Map<String, Value>) -> Result<(), String> {
+            let mut d = (&mut ecs.entities(), &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(), &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>());
+            $(
+            if let Some(storage) = components.remove(stringify!($saved)) {
+                DeserializeComponents::<Infallible, _>::deserialize(
+                    &mut ( &mut ecs.write_storage::<$saved>(), ),
+                    &d.0, // entities
+                    &mut d.1, // marker
+                    &mut d.2, // allocater
+                    storage,
+                )
+                .map_err(|e| format!("The save's {} are damaged: {}", stringify!($saved), e))?;
+            }
+            )*
+            Ok(())
+        }
+    };
+}
+
+component_registry! {
+    saved {
+        Position, Renderable, Player, Viewshed,
+        Name, BlocksTile, WantsToMelee, Item, Consumable, Ranged, InflictsDamage,
+        AreaOfEffect, Confusion, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
+        WantsToDropItem, SerializationHelper, Equippable, Equipped, Weapon, Wearable,
+        WantsToRemoveItem, ParticleLifetime, HungerClock, ProvidesFood, MagicMapper, Hidden,
+        EntryTrigger, EntityMoved, SingleActivation, BlocksVisibility, Door,
+        Quips, Attributes, Skills, Pools, NaturalAttackDefense, LootTable,
+        OtherLevelPosition, DMSerializationHelper, LightSource, Initiative, MyTurn, Faction,
+        WantsToApproach, WantsToFlee, MoveMode, Chasing, EquipmentChanged, Vendor, TownPortal,
+        TeleportTo, ApplyMove, ApplyTeleport, MagicItem, ObfuscatedName, IdentifiedItem,
+        SpawnParticleBurst, SpawnParticleLine, CursedItem, ProvidesRemoveCurse, ProvidesIdentification,
+        AttributeBonus, StatusEffect, Duration, KnownSpells, SpellTemplate, WantsToCastSpell, TeachesSpell,
+        ProvidesMana, Slow, DamageOverTime, SpecialAbilities, TileSize, OnDeath, AlwaysTargetsSelf,
+        Target, WantsToShoot, Digger, Digging, Ignites, Flammable, ReleasesGas, WantsToInvestigate,
+        MakesNoise, Darkvision
+    }
+    not_saved {
+        LightCache
+    }
+}
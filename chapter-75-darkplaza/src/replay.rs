@@ -0,0 +1,197 @@
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use serde::{Serialize, Deserialize};
+use specs::prelude::*;
+use crate::{PlayerCommand, Position, Pools, ParticleLifetime, GameClock, Map, GameOptions};
+
+/// How many commands go by between checks on the game's state.
+const CHECKPOINT_INTERVAL : usize = 20;
+
+/// Where a run is recorded. Every run gets its own file, so dying and starting again doesn't
+/// throw away the replay that shows how it happened.
+pub fn replay_path(run_seed : u64) -> String {
+    format!("./replay-{}.jsonl", run_seed)
+}
+
+/// FNV-1a, for hashes that stay the same from one build (or machine) to the next, unlike
+/// the standard library's.
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xCBF2_9CE4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes : &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A fingerprint of everything that matters in the game: where everything is, how everyone
+/// is doing and what time it is. Particles are left out, since they're only for show.
+pub fn state_hash(ecs : &World) -> u64 {
+    let mut hasher = StableHasher::default();
+
+    let map = ecs.fetch::<Map>();
+    map.key().hash(&mut hasher);
+    ecs.fetch::<GameClock>().minutes.hash(&mut hasher);
+    let player_pos = ecs.fetch::<rltk::Point>();
+    (player_pos.x, player_pos.y).hash(&mut hasher);
+
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let particles = ecs.read_storage::<ParticleLifetime>();
+    for (entity, pos, _not_particle) in (&entities, &positions, !&particles).join() {
+        (entity.id(), pos.x, pos.y).hash(&mut hasher);
+    }
+
+    let pools = ecs.read_storage::<Pools>();
+    for (entity, pools) in (&entities, &pools).join() {
+        (entity.id(), pools.hit_points.current, pools.mana.current, pools.xp, pools.level).hash(&mut hasher);
+        pools.gold.to_bits().hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct ReplayHeader {
+    seed : u64,
+    raws_hash : u64,
+    #[serde(default)]
+    options : GameOptions
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub enum ReplayEntry {
+    Command(PlayerCommand),
+    /// What `state_hash` said just before the next command was played.
+    Checkpoint(u64)
+}
+
+/// A recorded run: the seed it started from, the raws and options it was played with, and
+/// everything the player did.
+pub struct Replay {
+    pub seed : u64,
+    pub raws_hash : u64,
+    pub options : GameOptions,
+    pub entries : Vec<ReplayEntry>
+}
+
+impl Replay {
+    /// Reads a replay written by a `Recorder`. A run that crashed part of the way through a
+    /// line still loads, up to the last whole one.
+    pub fn load(path : &str) -> Result<Replay, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut lines = BufReader::new(file).lines();
+        let header_line = lines.next().ok_or("The replay is empty")?.map_err(|e| e.to_string())?;
+        let header : ReplayHeader = serde_json::from_str(&header_line).map_err(|e| e.to_string())?;
+
+        let mut entries = Vec::new();
+        for line in lines {
+            let line = line.map_err(|e| e.to_string())?;
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) => break
+            }
+        }
+
+        Ok(Replay{ seed : header.seed, raws_hash : header.raws_hash, options : header.options, entries })
+    }
+}
+
+/// Writes down a run as it's played. Each command goes to disk as soon as it's made, so
+/// the replay survives the game crashing.
+pub struct Recorder {
+    path : String,
+    file : File,
+    commands : usize
+}
+
+impl Recorder {
+    pub fn start(path : &str, run_seed : u64, raws_hash : u64, options : GameOptions) -> std::io::Result<Recorder> {
+        let mut recorder = Recorder{ path : path.to_string(), file : File::create(path)?, commands : 0 };
+        recorder.write_line(&serde_json::to_string(&ReplayHeader{ seed : run_seed, raws_hash, options }).unwrap());
+        Ok(recorder)
+    }
+
+    fn write_line(&mut self, line : &str) {
+        // A replay that can't be written isn't worth stopping the game over
+        if let Err(e) = writeln!(self.file, "{}", line) {
+            rltk::console::log(format!("Unable to write to the replay: {}", e));
+        }
+    }
+
+    fn write_entry(&mut self, entry : ReplayEntry) {
+        self.write_line(&serde_json::to_string(&entry).unwrap());
+    }
+
+    pub fn record(&mut self, ecs : &World, command : PlayerCommand) {
+        if self.commands % CHECKPOINT_INTERVAL == 0 {
+            self.write_entry(ReplayEntry::Checkpoint(state_hash(ecs)));
+        }
+        self.write_entry(ReplayEntry::Command(command));
+        self.commands += 1;
+    }
+
+    /// Ends the recording with a last look at how the game turned out.
+    pub fn finish(mut self, ecs : &World) {
+        self.write_entry(ReplayEntry::Checkpoint(state_hash(ecs)));
+    }
+
+    /// Throws the recording away, for a run there's no point replaying.
+    pub fn discard(self) {
+        let Recorder{ path, file, .. } = self;
+        std::mem::drop(file);
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Feeds a replay's commands back to the game, checking along the way that it's still
+/// playing out the way it did the first time.
+pub struct Playback {
+    entries : Vec<ReplayEntry>,
+    next : usize,
+    commands : usize
+}
+
+impl Playback {
+    pub fn new(replay : Replay) -> Result<Playback, String> {
+        if replay.raws_hash != crate::raws::raws_hash() {
+            return Err("The replay was recorded with different raw files.".to_string());
+        }
+        Ok(Playback{ entries : replay.entries, next : 0, commands : 0 })
+    }
+
+    /// The next command to play, or `None` once they've all been played. Fails if the game
+    /// has gone somewhere the recording didn't.
+    pub fn next_command(&mut self, ecs : &World) -> Result<Option<PlayerCommand>, String> {
+        while self.next < self.entries.len() {
+            let entry = self.entries[self.next];
+            self.next += 1;
+            match entry {
+                ReplayEntry::Checkpoint(expected) => {
+                    let found = state_hash(ecs);
+                    if found != expected {
+                        return Err(format!("The replay diverged before command {}: expected state {:016x}, found {:016x}.",
+                            self.commands, expected, found));
+                    }
+                }
+                ReplayEntry::Command(command) => {
+                    self.commands += 1;
+                    return Ok(Some(command));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
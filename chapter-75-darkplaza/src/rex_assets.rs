@@ -20,3 +20,14 @@ impl RexAssets {
         }
     }
 }
+
+/// Reads an embedded REX Paint file. Unlike `XpFile::from_resource`, a missing or damaged
+/// file is an error rather than a panic.
+pub fn load_xp(path : &str) -> Result<XpFile, String> {
+    let data = rltk::embedding::EMBED
+        .lock()
+        .get_resource(path.to_string())
+        .ok_or_else(|| format!("{} isn't embedded in the game", path))?
+        .to_vec();
+    XpFile::read(&mut std::io::Cursor::new(data)).map_err(|e| format!("{}: {:?}", path, e))
+}
@@ -1,20 +1,39 @@
-use std::sync::Mutex;
 use rltk::prelude::*;
 
-lazy_static! {
-    static ref RNG: Mutex<RandomNumberGenerator> =
-        Mutex::new(RandomNumberGenerator::new());
-}
+// Each world keeps its own `RandomNumberGenerator` as a resource, and map builders are handed
+// one seeded for the level they're building; nothing here holds any state.
+
+/// Randomness for how things look, like lights flickering. It's kept apart from the world's
+/// generator so that what's on screen never changes how a run plays out.
+pub struct VisualRng(pub RandomNumberGenerator);
 
-pub fn reseed(seed: u64) {
-    *RNG.lock().unwrap() = RandomNumberGenerator::seeded(seed);
+impl VisualRng {
+    pub fn new(run_seed : u64) -> VisualRng {
+        VisualRng(RandomNumberGenerator::seeded(run_seed ^ 0x2545_F491_4F6C_DD1D))
+    }
 }
 
-pub fn roll_dice(n:i32, die_type: i32) -> i32 {
-    RNG.lock().unwrap().roll_dice(n, die_type)
+/// A fresh seed from the system's entropy, for starting a run nobody asked to repeat.
+pub fn random_seed() -> u64 {
+    RandomNumberGenerator::new().next_u64()
 }
 
-pub fn range(min: i32, max: i32) -> i32
-{
-    RNG.lock().unwrap().range(min, max)
+/// Derives the seed for a single level from the run seed. Every level gets its own
+/// stream, so a level is identical no matter what was generated before it.
+pub fn level_seed(run_seed: u64, branch: &str, depth: i32) -> u64 {
+    // Main dungeon levels keep the seeds they had before branches existed
+    let mut z = run_seed ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    if branch != crate::map::MAIN_BRANCH {
+        // FNV-1a of the branch name
+        let mut hash : u64 = 0xCBF2_9CE4_8422_2325;
+        for b in branch.bytes() {
+            hash = (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3);
+        }
+        z ^= hash;
+    }
+
+    // SplitMix64 finalizer
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
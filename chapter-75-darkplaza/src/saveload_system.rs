@@ -1,97 +1,346 @@
 use specs::prelude::*;
-use specs::saveload::{SimpleMarker, SimpleMarkerAllocator, SerializeComponents, DeserializeComponents, MarkedBuilder};
-use specs::error::NoError;
+use specs::saveload::{SimpleMarker, Marker};
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
 use super::components::*;
+use crate::raws::RawMaster;
+use crate::map::LevelKey;
 use std::fs::File;
 use std::path::Path;
 use std::fs;
 
-macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
-        $(
-        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
-            &( $ecs.read_storage::<$type>(), ),
-            &$data.0,
-            &$data.1,
-            &mut $ser,
-        )
-        .unwrap();
-        )*
-    };
+/// The save format this build writes. Bump it whenever a change to a saved component means
+/// older saves won't load as they are, and add a migration to `MIGRATIONS` that brings
+/// them up to date.
+pub const SAVE_VERSION : u32 = 2;
+
+/// Where the save slots are kept: every slot is a file in here, named after the slot. It's
+/// a world resource, so that a test can keep its saves away from the player's.
+#[derive(Clone)]
+pub struct SaveDir(pub String);
+
+impl Default for SaveDir {
+    fn default() -> Self {
+        SaveDir("./saves".to_string())
+    }
+}
+
+/// Where the one and only save lived before there were slots.
+const LEGACY_SAVE_PATH : &str = "./savegame.json";
+
+/// Brings a save (as plain JSON) up one version. `MIGRATIONS[0]` takes version 1 to 2,
+/// `MIGRATIONS[1]` takes 2 to 3, and so on, so an old save goes through each in turn. The
+/// raws are there for filling in what an old save didn't record.
+type Migration = fn(&mut Value, &RawMaster) -> Result<(), String>;
+const MIGRATIONS : [Migration; SAVE_VERSION as usize - 1] = [migrate_v1_to_v2];
+
+/// Saves from before the format had a version (version 1) are one JSON value after another,
+/// one for each of these components, in this order. Don't change it.
+const V1_COMPONENTS : [&str; 80] = ["Position", "Renderable", "Player", "Viewshed",
+    "Name", "BlocksTile", "WantsToMelee", "Item", "Consumable", "Ranged", "InflictsDamage",
+    "AreaOfEffect", "Confusion", "ProvidesHealing", "InBackpack", "WantsToPickupItem", "WantsToUseItem",
+    "WantsToDropItem", "SerializationHelper", "Equippable", "Equipped", "Weapon", "Wearable",
+    "WantsToRemoveItem", "ParticleLifetime", "HungerClock", "ProvidesFood", "MagicMapper", "Hidden",
+    "EntryTrigger", "EntityMoved", "SingleActivation", "BlocksVisibility", "Door",
+    "Quips", "Attributes", "Skills", "Pools", "NaturalAttackDefense", "LootTable",
+    "OtherLevelPosition", "DMSerializationHelper", "LightSource", "Initiative", "MyTurn", "Faction",
+    "WantsToApproach", "WantsToFlee", "MoveMode", "Chasing", "EquipmentChanged", "Vendor", "TownPortal",
+    "TeleportTo", "ApplyMove", "ApplyTeleport", "MagicItem", "ObfuscatedName", "IdentifiedItem",
+    "SpawnParticleBurst", "SpawnParticleLine", "CursedItem", "ProvidesRemoveCurse", "ProvidesIdentification",
+    "AttributeBonus", "StatusEffect", "Duration", "KnownSpells", "SpellTemplate", "WantsToCastSpell", "TeachesSpell",
+    "ProvidesMana", "Slow", "DamageOverTime", "SpecialAbilities", "TileSize", "OnDeath", "AlwaysTargetsSelf",
+    "Target", "WantsToShoot"];
+
+/// What a save says about itself, ahead of the world it holds.
+#[derive(Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub version : u32,
+    /// The raws the game was saved with; `None` if the save is too old to know.
+    pub raws_hash : Option<u64>,
+    /// Who was playing and how far they'd got, for listing the save; `None` in saves from
+    /// before there were slots.
+    #[serde(default)]
+    pub summary : Option<SaveSummary>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SaveSummary {
+    pub character : String,
+    pub level : i32,
+    pub depth : i32,
+    pub location : String,
+    /// When the game was saved, in seconds since the Unix epoch.
+    pub saved_at : u64
+}
+
+/// A save slot, as the load menu lists it.
+#[derive(Clone, PartialEq)]
+pub struct SaveSlot {
+    pub name : String,
+    /// `None` if the save is too old or too damaged to say.
+    pub summary : Option<SaveSummary>
+}
+
+/// A save file: the header, then each component type's storage under its own name. A
+/// component the save doesn't mention just starts out empty, so adding one doesn't break
+/// older saves.
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub header : SaveHeader,
+    pub components : serde_json::Map<String, Value>
+}
+
+fn slot_path(dir : &SaveDir, slot : &str) -> String {
+    format!("{}/{}.json", dir.0, slot)
+}
+
+/// The slot a run saves to. Each run has its own, named after its seed, so saving again
+/// (or autosaving) replaces the run's last save and leaves everyone else's alone.
+pub fn run_slot(ecs : &World) -> String {
+    format!("run-{}", ecs.fetch::<super::map::MasterDungeonMap>().seed)
+}
+
+fn summarize(ecs : &World) -> SaveSummary {
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<super::map::Map>();
+    let character = ecs.read_storage::<Name>().get(*player_entity).map_or("Player".to_string(), |n| n.name.clone());
+    let level = ecs.read_storage::<Pools>().get(*player_entity).map_or(1, |p| p.level);
+    let saved_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    SaveSummary{ character, level, depth : map.depth, location : map.name.clone(), saved_at }
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_ecs : &mut World) {
+pub fn save_game(_ecs : &World, _slot : &str) {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(ecs : &mut World) {
-    // Create helper
-    let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
-    let dungeon_master = ecs.get_mut::<super::map::MasterDungeonMap>().unwrap().clone();
-    let savehelper = ecs
-        .create_entity()
-        .with(SerializationHelper{ map : mapcopy })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-    let savehelper2 = ecs
-        .create_entity()
-        .with(DMSerializationHelper{ 
-            map : dungeon_master, 
-            log: crate::gamelog::clone_log(), 
-            events : crate::gamelog::clone_events() 
+pub fn save_game(ecs : &World, slot : &str) {
+    let mut components = crate::registry::serialize_components(ecs);
+
+    // The map and the dungeon master go in as two more entities, but without making them:
+    // saving mustn't change which entity ids get handed out next, or an autosave would
+    // change how the rest of the run plays out.
+    let helper_marker = ecs.read_storage::<SimpleMarker<SerializeMe>>().join().map(|m| m.id() + 1).max().unwrap_or(0);
+    let helper = SerializationHelper{ map : (*ecs.fetch::<super::map::Map>()).clone() };
+    let dm_helper = DMSerializationHelper{
+        map : (*ecs.fetch::<super::map::MasterDungeonMap>()).clone(),
+        log : ecs.fetch::<crate::gamelog::GameLog>().entries().to_vec(),
+        events : ecs.fetch::<crate::gamelog::GameEvents>().counts().clone(),
+        clock : *ecs.fetch::<crate::GameClock>()
+    };
+    components.insert("SerializationHelper".to_string(), json!([{ "marker" : [helper_marker], "components" : [helper] }]));
+    components.insert("DMSerializationHelper".to_string(), json!([{ "marker" : [helper_marker + 1], "components" : [dm_helper] }]));
+
+    let header = SaveHeader{
+        version : SAVE_VERSION,
+        raws_hash : Some(crate::raws::raws_hash()),
+        summary : Some(summarize(ecs))
+    };
+    let dir = ecs.fetch::<SaveDir>();
+    fs::create_dir_all(&dir.0).expect("Unable to create the save directory");
+    let writer = File::create(slot_path(&dir, slot)).unwrap();
+    serde_json::to_writer(writer, &SaveFile{ header, components }).unwrap();
+}
+
+/// Moves a save from before there were slots into a slot of its own. This only needs doing
+/// once, when the game starts.
+pub fn adopt_legacy_save(ecs : &World) {
+    let dir = ecs.fetch::<SaveDir>();
+    if Path::new(LEGACY_SAVE_PATH).exists() && fs::create_dir_all(&dir.0).is_ok() {
+        let _ = fs::rename(LEGACY_SAVE_PATH, slot_path(&dir, "savegame"));
+    }
+}
+
+fn slot_names(dir : &SaveDir) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(dir) = fs::read_dir(&dir.0) {
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// The header of a save, if it has one; saves from before versions don't.
+fn read_header(text : &str) -> Option<SaveHeader> {
+    let first = serde_json::Deserializer::from_str(text).into_iter::<Value>().next()?.ok()?;
+    serde_json::from_value(first.get("header")?.clone()).ok()
+}
+
+/// Every save slot, most recently saved first. This reads the start of each save, so the
+/// game keeps the list as a resource and only reads it again when the saves change.
+pub fn list_saves(ecs : &World) -> Vec<SaveSlot> {
+    let dir = ecs.fetch::<SaveDir>();
+    let mut slots : Vec<SaveSlot> = slot_names(&dir).into_iter()
+        .map(|name| {
+            let summary = fs::read_to_string(slot_path(&dir, &name)).ok()
+                .and_then(|text| read_header(&text))
+                .and_then(|header| header.summary);
+            SaveSlot{ name, summary }
         })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+        .collect();
+    slots.sort_by(|a, b| {
+        let a_time = a.summary.as_ref().map_or(0, |s| s.saved_at);
+        let b_time = b.summary.as_ref().map_or(0, |s| s.saved_at);
+        b_time.cmp(&a_time).then_with(|| a.name.cmp(&b.name))
+    });
+    slots
+}
 
-    // Actually serialize
-    {
-        let data = ( ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>() );
-
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
-        serialize_individually!(ecs, serializer, data, Position, Renderable, Player, Viewshed,
-            Name, BlocksTile, WantsToMelee, Item, Consumable, Ranged, InflictsDamage,
-            AreaOfEffect, Confusion, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
-            WantsToDropItem, SerializationHelper, Equippable, Equipped, Weapon, Wearable,
-            WantsToRemoveItem, ParticleLifetime, HungerClock, ProvidesFood, MagicMapper, Hidden,
-            EntryTrigger, EntityMoved, SingleActivation, BlocksVisibility, Door,
-            Quips, Attributes, Skills, Pools, NaturalAttackDefense, LootTable,
-            OtherLevelPosition, DMSerializationHelper, LightSource, Initiative, MyTurn, Faction,
-            WantsToApproach, WantsToFlee, MoveMode, Chasing, EquipmentChanged, Vendor, TownPortal,
-            TeleportTo, ApplyMove, ApplyTeleport, MagicItem, ObfuscatedName, IdentifiedItem,
-            SpawnParticleBurst, SpawnParticleLine, CursedItem, ProvidesRemoveCurse, ProvidesIdentification,
-            AttributeBonus, StatusEffect, Duration, KnownSpells, SpellTemplate, WantsToCastSpell, TeachesSpell,
-            ProvidesMana, Slow, DamageOverTime, SpecialAbilities, TileSize, OnDeath, AlwaysTargetsSelf,
-            Target, WantsToShoot
-        );
+fn corrupt(e : serde_json::Error) -> String {
+    format!("The save is damaged: {}", e)
+}
+
+/// Calls `f` on every map in the save: the current level, and each one the dungeon master
+/// keeps.
+fn for_each_map<F : FnMut(&mut Value) -> Result<(), String>>(value : &mut Value, f : &mut F) -> Result<(), String> {
+    match value {
+        Value::Object(fields) if fields.contains_key("tiles") && fields.contains_key("revealed_tiles") => f(value),
+        Value::Object(fields) => fields.values_mut().try_for_each(|v| for_each_map(v, f)),
+        Value::Array(values) => values.iter_mut().try_for_each(|v| for_each_map(v, f)),
+        _ => Ok(())
     }
+}
 
-    // Clean up
-    ecs.delete_entity(savehelper).expect("Crash on cleanup");
-    ecs.delete_entity(savehelper2).expect("Crash on cleanup");
+/// Calls `f` on each `component` stored in the save.
+fn for_each_component<F : FnMut(&mut serde_json::Map<String, Value>) -> Result<(), String>>(save : &mut Value, component : &str, f : &mut F) -> Result<(), String> {
+    let storage = match save["components"].get_mut(component) {
+        Some(Value::Array(storage)) => storage,
+        _ => return Ok(())
+    };
+    for entry in storage.iter_mut() {
+        for value in entry["components"].as_array_mut().into_iter().flatten() {
+            if let Some(fields) = value.as_object_mut() {
+                f(fields)?;
+            }
+        }
+    }
+    Ok(())
 }
 
-pub fn does_save_exist() -> bool {
-    Path::new("./savegame.json").exists()
+/// Replaces the `depth` and (optional, defaulting to the main dungeon) `branch` fields of a
+/// saved component with a level key called `level`.
+fn level_key_from(component : &mut serde_json::Map<String, Value>, depth : &str, branch : &str, level : &str) -> Result<(), String> {
+    let depth = component.remove(depth).ok_or("A component in the save doesn't say which level it's for.")?;
+    let branch = component.remove(branch).unwrap_or_else(|| json!(crate::map::MAIN_BRANCH));
+    component.insert(level.to_string(), json!({ "branch" : branch, "depth" : depth }));
+    Ok(())
 }
 
-macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
-        $(
-        DeserializeComponents::<NoError, _>::deserialize(
-            &mut ( &mut $ecs.write_storage::<$type>(), ),
-            &$data.0, // entities
-            &mut $data.1, // marker
-            &mut $data.2, // allocater
-            &mut $de,
-        )
-        .unwrap();
-        )*
+/// Version 1 kept the components in a fixed order rather than by name, and said which level
+/// something was on with just a depth. Its maps were all in the main dungeon, stored each tile
+/// by name, had no themes (they get the theme their level has in the raws), and had nothing
+/// burning, being dug, drifting about or remembered on them. The run had no seed, so it gets
+/// a fresh one.
+fn migrate_v1_to_v2(save : &mut Value, raws : &RawMaster) -> Result<(), String> {
+    let storages = match save["components"].take() {
+        Value::Array(storages) => storages,
+        _ => return Err("The save has no components in it.".to_string())
     };
+    if storages.len() != V1_COMPONENTS.len() {
+        return Err(format!("The save has {} kinds of component, where it should have {}.", storages.len(), V1_COMPONENTS.len()));
+    }
+    let components : serde_json::Map<String, Value> = V1_COMPONENTS.iter()
+        .map(|name| name.to_string())
+        .zip(storages)
+        .collect();
+    save["components"] = Value::Object(components);
+
+    for_each_component(save, "OtherLevelPosition", &mut |c| level_key_from(c, "depth", "branch", "level"))?;
+    for_each_component(save, "TeleportTo", &mut |c| level_key_from(c, "depth", "branch", "level"))?;
+    for_each_component(save, "ApplyTeleport", &mut |c| level_key_from(c, "dest_depth", "dest_branch", "dest_level"))?;
+    for_each_component(save, "DMSerializationHelper", &mut |helper| {
+        if let Some(Value::Object(dungeon)) = helper.get_mut("map") {
+            dungeon.entry("seed").or_insert_with(|| json!(crate::rng::random_seed()));
+        }
+        Ok(())
+    })?;
+
+    for_each_map(&mut save["components"], &mut |map| {
+        for field in ["branch_entrances", "dig_progress", "fires", "gas", "remembered"] {
+            if map.get(field).is_none() {
+                map[field] = json!({});
+            }
+        }
+        if map.get("branch").is_none() {
+            map["branch"] = json!(crate::map::MAIN_BRANCH);
+        }
+        if map.get("theme").is_none() {
+            let branch = map["branch"].as_str().unwrap_or(crate::map::MAIN_BRANCH).to_string();
+            let depth = map["depth"].as_i64().ok_or("A map in the save has no depth.")? as i32;
+            if let Some(chain) = crate::raws::get_level_chain(raws, &LevelKey::new(branch, depth)) {
+                map["theme"] = json!(chain.theme);
+                map["theme_regions"] = serde_json::to_value(&chain.theme_regions).map_err(|e| e.to_string())?;
+            }
+        }
+
+        let mut names : Vec<String> = Vec::new();
+        let tiles = map["tiles"].as_array_mut().ok_or("A map in the save has no tiles.")?;
+        for tile in tiles.iter_mut() {
+            let name = tile.as_str().ok_or("A map in the save has a damaged tile.")?.to_string();
+            let id = match names.iter().position(|n| *n == name) {
+                Some(id) => id,
+                None => { names.push(name); names.len() - 1 }
+            };
+            *tile = json!(id);
+        }
+        map["tile_names"] = json!(names);
+        Ok(())
+    })
 }
 
-pub fn load_game(ecs: &mut World) {
+/// Reads a save of any version this build knows about, migrating it up to the current one.
+/// Fails if it's damaged or from a newer version of the game.
+pub fn read_save(text : &str, raws : &RawMaster) -> Result<SaveFile, String> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+    let first = values.next().ok_or("The save is empty.")?.map_err(corrupt)?;
+    let mut save = if first.get("header").is_some() {
+        first
+    } else {
+        // No header, so it's from before there were versions
+        let mut storages = vec![first];
+        for storage in values {
+            storages.push(storage.map_err(corrupt)?);
+        }
+        json!({ "header" : { "version" : 1, "raws_hash" : null }, "components" : storages })
+    };
+
+    loop {
+        let version = save["header"]["version"].as_u64().ok_or("The save doesn't say which version it is.")?;
+        if version > SAVE_VERSION as u64 {
+            return Err(format!("The save is from a newer version of the game (format {}; this one reads up to {}).", version, SAVE_VERSION));
+        }
+        if version == SAVE_VERSION as u64 { break; }
+        if version == 0 {
+            return Err("The save doesn't say which version it is.".to_string());
+        }
+        MIGRATIONS[version as usize - 1](&mut save, raws)?;
+        save["header"]["version"] = json!(version + 1);
+    }
+
+    serde_json::from_value(save).map_err(corrupt)
+}
+
+/// Replaces the world with the game saved in `slot`. If this fails after the save has been read, the
+/// world may be left half-loaded, and should be thrown away.
+pub fn load_game(ecs: &mut World, slot : &str) -> Result<(), String> {
+    let path = slot_path(&ecs.fetch::<SaveDir>(), slot);
+    let data = fs::read_to_string(path).map_err(|e| format!("Unable to read the save: {}", e))?;
+    load_save(ecs, &data)
+}
+
+/// Replaces the world with the game in `text`, the contents of a save file; see `load_game`.
+pub fn load_save(ecs: &mut World, text : &str) -> Result<(), String> {
+    let raws = crate::raws::get_raws(ecs);
+    let SaveFile{ header, mut components } = read_save(text, &raws)?;
+
     {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -103,28 +352,7 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
-    let mut de = serde_json::Deserializer::from_str(&data);
-
-    {
-        let mut d = (&mut ecs.entities(), &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(), &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>());
-
-        deserialize_individually!(ecs, de, d, Position, Renderable, Player, Viewshed,
-            Name, BlocksTile, WantsToMelee, Item, Consumable, Ranged, InflictsDamage,
-            AreaOfEffect, Confusion, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
-            WantsToDropItem, SerializationHelper, Equippable, Equipped, Weapon, Wearable,
-            WantsToRemoveItem, ParticleLifetime, HungerClock, ProvidesFood, MagicMapper, Hidden,
-            EntryTrigger, EntityMoved, SingleActivation, BlocksVisibility, Door,
-            Quips, Attributes, Skills, Pools, NaturalAttackDefense, LootTable,
-            OtherLevelPosition, DMSerializationHelper, LightSource, Initiative, MyTurn, Faction,
-            WantsToApproach, WantsToFlee, MoveMode, Chasing, EquipmentChanged, Vendor, TownPortal,
-            TeleportTo, ApplyMove, ApplyTeleport, MagicItem, ObfuscatedName, IdentifiedItem,
-            SpawnParticleBurst, SpawnParticleLine, CursedItem, ProvidesRemoveCurse, ProvidesIdentification,
-            AttributeBonus, StatusEffect, Duration, KnownSpells, SpellTemplate, WantsToCastSpell, TeachesSpell,
-            ProvidesMana, Slow, DamageOverTime, SpecialAbilities, TileSize, OnDeath, AlwaysTargetsSelf,
-            Target, WantsToShoot
-        );
-    }
+    crate::registry::deserialize_components(ecs, &mut components)?;
 
     let mut deleteme : Option<Entity> = None;
     let mut deleteme2 : Option<Entity> = None;
@@ -137,15 +365,21 @@ pub fn load_game(ecs: &mut World) {
         for (e,h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
-            crate::spatial::set_size((worldmap.height * worldmap.width) as usize);
+            worldmap.attach_tiles(&raws.tiles)?;
+            let tile_count = (worldmap.height * worldmap.width) as usize;
+            worldmap.spatial.set_size(tile_count);
+            // Whatever the AI worked out on the map being replaced no longer applies
+            *ecs.write_resource::<crate::map::FlowFields>() = crate::map::FlowFields::default();
             deleteme = Some(e);
         }
         for (e,h) in (&entities, &helper2).join() {
             let mut dungeonmaster = ecs.write_resource::<super::map::MasterDungeonMap>();
             *dungeonmaster = h.map.clone();
+            dungeonmaster.attach_tiles(&raws.tiles)?;
             deleteme2 = Some(e);
-            crate::gamelog::restore_log(&mut h.log.clone());
-            crate::gamelog::load_events(h.events.clone());
+            ecs.write_resource::<crate::gamelog::GameLog>().restore(h.log.clone());
+            ecs.write_resource::<crate::gamelog::GameEvents>().restore(h.events.clone());
+            *ecs.write_resource::<crate::GameClock>() = h.clock;
         }
         for (e,_p,pos) in (&entities, &player, &position).join() {
             let mut ppos = ecs.write_resource::<rltk::Point>();
@@ -154,10 +388,19 @@ pub fn load_game(ecs: &mut World) {
             *player_resource = e;
         }
     }
-    ecs.delete_entity(deleteme.unwrap()).expect("Unable to delete helper");
-    ecs.delete_entity(deleteme2.unwrap()).expect("Unable to delete helper");
+    ecs.delete_entity(deleteme.ok_or("The save has no map in it.")?).expect("Unable to delete helper");
+    ecs.delete_entity(deleteme2.ok_or("The save has no dungeon in it.")?).expect("Unable to delete helper");
+
+    if header.raws_hash.is_some() && header.raws_hash != Some(crate::raws::raws_hash()) {
+        crate::gamelog::Logger::new()
+            .color(rltk::ORANGE)
+            .append("This game was saved with different raw files, so not everything may be as you left it.")
+            .log(&mut ecs.write_resource::<crate::gamelog::GameLog>());
+    }
+    Ok(())
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() { std::fs::remove_file("./savegame.json").expect("Unable to delete file"); }
+pub fn delete_save(ecs : &World, slot : &str) {
+    let path = slot_path(&ecs.fetch::<SaveDir>(), slot);
+    if Path::new(&path).exists() { std::fs::remove_file(path).expect("Unable to delete file"); }
 }
@@ -1,117 +1,115 @@
-use std::sync::Mutex;
 use specs::prelude::*;
-use crate::{ Map, tile_walkable, RunState };
+use crate::{ TileType, TileSet, RunState };
 
-struct SpatialMap {
+/// What's standing where on a level, and which tiles can't be entered. Each `Map` carries
+/// its own, rebuilt every turn by the map indexing system.
+#[derive(Default, Clone)]
+pub struct SpatialMap {
     blocked : Vec<(bool, bool)>,
     tile_content : Vec<Vec<(Entity, bool)>>
 }
 
 impl SpatialMap {
-    fn new() -> Self {
-        Self {
-            blocked: Vec::new(),
-            tile_content: Vec::new()
-        }
+    pub fn new(map_tile_count: usize) -> Self {
+        let mut spatial = SpatialMap::default();
+        spatial.set_size(map_tile_count);
+        spatial
     }
-}
 
-lazy_static! {
-    static ref SPATIAL_MAP : Mutex<SpatialMap> = Mutex::new(SpatialMap::new());
-}
+    pub fn set_size(&mut self, map_tile_count: usize) {
+        self.blocked = vec![(false, false); map_tile_count];
+        self.tile_content = vec![Vec::new(); map_tile_count];
+    }
 
-pub fn set_size(map_tile_count: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked = vec![(false, false); map_tile_count];
-    lock.tile_content = vec![Vec::new(); map_tile_count];
-}
+    pub fn clear(&mut self) {
+        self.blocked.iter_mut().for_each(|b| { b.0 = false; b.1 = false; });
+        for content in self.tile_content.iter_mut() {
+            content.clear();
+        }
+    }
 
-pub fn clear() {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked.iter_mut().for_each(|b| { b.0 = false; b.1 = false; });
-    for content in lock.tile_content.iter_mut() {
-        content.clear();
+    pub fn populate_blocked(&mut self, tiles: &[TileType], tile_set: &TileSet) {
+        for (i,tile) in tiles.iter().enumerate() {
+            self.blocked[i].0 = !tile_set.walkable(*tile);
+        }
     }
-}
 
-pub fn populate_blocked_from_map(map: &Map) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    for (i,tile) in map.tiles.iter().enumerate() {
-        lock.blocked[i].0 = !tile_walkable(*tile);
+    /// Re-reads whether the terrain at `idx` blocks movement, after the map has changed there.
+    pub fn refresh_tile(&mut self, tiles: &[TileType], tile_set: &TileSet, idx: usize) {
+        self.blocked[idx].0 = !tile_set.walkable(tiles[idx]);
     }
-}
 
-pub fn index_entity(entity: Entity, idx: usize, blocks_tile: bool) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].push((entity, blocks_tile));
-    if blocks_tile {
-        lock.blocked[idx].1 = true;
+    pub fn index_entity(&mut self, entity: Entity, idx: usize, blocks_tile: bool) {
+        self.tile_content[idx].push((entity, blocks_tile));
+        if blocks_tile {
+            self.blocked[idx].1 = true;
+        }
     }
-}
 
-pub fn is_blocked(idx: usize) -> bool {
-    let lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked[idx].0 || lock.blocked[idx].1
-}
+    pub fn is_blocked(&self, idx: usize) -> bool {
+        self.blocked[idx].0 || self.blocked[idx].1
+    }
 
-pub fn set_blocked(idx: usize, blocked: bool) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.blocked[idx] = (lock.blocked[idx].0, blocked);
-}
+    /// Which tiles are blocked, ignoring anything `entity` itself blocks; for pathing over a
+    /// whole map.
+    pub fn blocked_tiles_except(&self, entity: Entity) -> Vec<bool> {
+        self.blocked.iter()
+            .zip(self.tile_content.iter())
+            .map(|(blocked, content)| {
+                blocked.0 || (blocked.1 && content.iter().any(|(e, blocks)| *blocks && *e != entity))
+            })
+            .collect()
+    }
 
-pub fn for_each_tile_content<F>(idx: usize, mut f: F)
-where F : FnMut(Entity)
-{
-    let lock = SPATIAL_MAP.lock().unwrap();
-    for entity in lock.tile_content[idx].iter() {
-        f(entity.0);
+    pub fn for_each_tile_content<F>(&self, idx: usize, mut f: F)
+    where F : FnMut(Entity)
+    {
+        for entity in self.tile_content[idx].iter() {
+            f(entity.0);
+        }
     }
-}
 
-pub fn for_each_tile_content_with_gamemode<F>(idx: usize, mut f: F) -> RunState
-where F : FnMut(Entity)->Option<RunState>
-{
-    let lock = SPATIAL_MAP.lock().unwrap();
-    for entity in lock.tile_content[idx].iter() {
-        if let Some(rs) = f(entity.0) {
-            return rs;
+    pub fn for_each_tile_content_with_gamemode<F>(&self, idx: usize, mut f: F) -> RunState
+    where F : FnMut(Entity)->Option<RunState>
+    {
+        for entity in self.tile_content[idx].iter() {
+            if let Some(rs) = f(entity.0) {
+                return rs;
+            }
         }
+
+        RunState::AwaitingInput
     }
 
-    RunState::AwaitingInput
-}
+    pub fn get_tile_content_clone(&self, idx:usize) -> Vec<Entity> {
+        self.tile_content[idx].iter().map(|(e,_)| *e).collect()
+    }
 
-pub fn get_tile_content_clone(idx:usize) -> Vec<Entity> {
-    let lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].iter().map(|(e,_)| *e).collect()
-}
+    pub fn move_entity(&mut self, entity: Entity, moving_from: usize, moving_to: usize) {
+        let mut entity_blocks = false;
+        self.tile_content[moving_from].retain(|(e, blocks) | {
+            if *e == entity {
+                entity_blocks = *blocks;
+                false
+            } else {
+                true
+            }
+        });
+        self.tile_content[moving_to].push((entity, entity_blocks));
+
+        // Recalculate blocks for both tiles
+        let mut from_blocked = false;
+        let mut to_blocked = false;
+        self.tile_content[moving_from].iter().for_each(|(_,blocks)| if *blocks { from_blocked = true; } );
+        self.tile_content[moving_to].iter().for_each(|(_,blocks)| if *blocks { to_blocked = true; } );
+        self.blocked[moving_from].1 = from_blocked;
+        self.blocked[moving_to].1 = to_blocked;
+    }
 
-pub fn move_entity(entity: Entity, moving_from: usize, moving_to: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    let mut entity_blocks = false;
-    lock.tile_content[moving_from].retain(|(e, blocks) | {
-        if *e == entity {
-            entity_blocks = *blocks;
-            false
-        } else {
-            true
-        }
-    });
-    lock.tile_content[moving_to].push((entity, entity_blocks));
-
-    // Recalculate blocks for both tiles
-    let mut from_blocked = false;
-    let mut to_blocked = false;
-    lock.tile_content[moving_from].iter().for_each(|(_,blocks)| if *blocks { from_blocked = true; } );
-    lock.tile_content[moving_to].iter().for_each(|(_,blocks)| if *blocks { to_blocked = true; } );
-    lock.blocked[moving_from].1 = from_blocked;
-    lock.blocked[moving_to].1 = to_blocked;
+    pub fn remove_entity(&mut self, entity: Entity, idx: usize) {
+        self.tile_content[idx].retain(|(e, _)| *e != entity );
+        let mut from_blocked = false;
+        self.tile_content[idx].iter().for_each(|(_,blocks)| if *blocks { from_blocked = true; } );
+        self.blocked[idx].1 = from_blocked;
+    }
 }
-
-pub fn remove_entity(entity: Entity, idx: usize) {
-    let mut lock = SPATIAL_MAP.lock().unwrap();
-    lock.tile_content[idx].retain(|(e, _)| *e != entity );
-    let mut from_blocked = false;
-    lock.tile_content[idx].iter().for_each(|(_,blocks)| if *blocks { from_blocked = true; } );
-    lock.blocked[idx].1 = from_blocked;
-}
\ No newline at end of file
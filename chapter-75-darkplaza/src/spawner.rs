@@ -1,13 +1,13 @@
-use rltk::{ RGB };
+use rltk::{ RGB, RandomNumberGenerator };
 use specs::prelude::*;
 use super::{Pools, Pool, Player, Renderable, Name, Position, Viewshed, Rect,
     SerializeMe, random_table::MasterTable, HungerClock, HungerState, Map, TileType, raws::*,
-    Attribute, Attributes, Skills, Skill, LightSource, Initiative, Faction, EquipmentChanged,
+    Attribute, Attributes, Skills, Skill, LightSource, LightFalloff, Initiative, Faction, EquipmentChanged,
     OtherLevelPosition, MasterDungeonMap, EntryTrigger, TeleportTo, SingleActivation,
     StatusEffect, Duration, AttributeBonus, KnownSpells };
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 use std::collections::HashMap;
-use crate::{attr_bonus, player_hp_at_level, mana_at_level};
+use crate::{attr_bonus, player_hp_at_level, mana_at_level, LevelKey};
 
 /// Spawns the player and returns his/her entity object.
 pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
@@ -55,7 +55,7 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
             god_mode : false
         })
         .with(EquipmentChanged{})
-        .with(LightSource{ color: rltk::RGB::from_f32(1.0, 1.0, 0.5), range: 8 })
+        .with(LightSource{ color: rltk::RGB::from_f32(1.0, 1.0, 0.5), range: 8, falloff: LightFalloff::Linear, flicker: 0.0, lit: true })
         .with(Initiative{current: 0})
         .with(Faction{name : "Player".to_string() })
         .with(KnownSpells{ spells : Vec::new() })
@@ -63,13 +63,14 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
         .build();
 
     // Starting equipment
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Rusty Longsword", SpawnType::Equipped{by : player});
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Dried Sausage", SpawnType::Carried{by : player} );
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Beer", SpawnType::Carried{by : player});
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Stained Tunic", SpawnType::Equipped{by : player});
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Torn Trousers", SpawnType::Equipped{by : player});
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Old Boots", SpawnType::Equipped{by : player});
-    spawn_named_entity(&RAWS.lock().unwrap(), ecs, "Shortbow", SpawnType::Carried{by : player});
+    let raws = get_raws(ecs);
+    spawn_named_entity(&raws, ecs, "Rusty Longsword", SpawnType::Equipped{by : player});
+    spawn_named_entity(&raws, ecs, "Dried Sausage", SpawnType::Carried{by : player} );
+    spawn_named_entity(&raws, ecs, "Beer", SpawnType::Carried{by : player});
+    spawn_named_entity(&raws, ecs, "Stained Tunic", SpawnType::Equipped{by : player});
+    spawn_named_entity(&raws, ecs, "Torn Trousers", SpawnType::Equipped{by : player});
+    spawn_named_entity(&raws, ecs, "Old Boots", SpawnType::Equipped{by : player});
+    spawn_named_entity(&raws, ecs, "Shortbow", SpawnType::Carried{by : player});
 
     // Starting hangover
     ecs.create_entity()
@@ -90,12 +91,12 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity {
 
 const MAX_MONSTERS : i32 = 4;
 
-fn room_table(map_depth: i32) -> MasterTable {
-    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth)
+fn room_table(raws: &RawMaster, map: &Map, map_depth: i32) -> MasterTable {
+    get_spawn_table_for_depth(raws, &map.branch, map_depth)
 }
 
 /// Fills a room with stuff!
-pub fn spawn_room(map: &Map, room : &Rect, map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
+pub fn spawn_room(map: &Map, rng: &mut RandomNumberGenerator, raws: &RawMaster, room : &Rect, map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
     let mut possible_targets : Vec<usize> = Vec::new();
     { // Borrow scope - to keep access to the map separated
         for y in room.y1 + 1 .. room.y2 {
@@ -108,32 +109,59 @@ pub fn spawn_room(map: &Map, room : &Rect, map_depth: i32, spawn_list : &mut Vec
         }
     }
 
-    spawn_region(map, &possible_targets, map_depth, spawn_list);
+    spawn_region(map, rng, raws, &possible_targets, map_depth, spawn_list);
 }
 
 /// Fills a region with stuff!
-pub fn spawn_region(_map: &Map, area : &[usize], map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points : HashMap<usize, String> = HashMap::new();
+pub fn spawn_region(map: &Map, rng: &mut RandomNumberGenerator, raws: &RawMaster, area : &[usize], map_depth: i32, spawn_list : &mut Vec<(usize, String)>) {
+    let spawn_table = room_table(raws, map, map_depth);
+    let mut spawn_points : Vec<(usize, String)> = Vec::new();
     let mut areas : Vec<usize> = Vec::from(area);
 
     // Scope to keep the borrow checker happy
     {
-        let num_spawns = i32::min(areas.len() as i32, crate::rng::roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3);
+        let num_spawns = i32::min(areas.len() as i32, rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3);
         if num_spawns == 0 { return; }
 
         for _i in 0 .. num_spawns {
-            let array_index = if areas.len() == 1 { 0usize } else { (crate::rng::roll_dice(1, areas.len() as i32)-1) as usize };
+            let array_index = if areas.len() == 1 { 0usize } else { (rng.roll_dice(1, areas.len() as i32)-1) as usize };
 
             let map_idx = areas[array_index];
-            spawn_points.insert(map_idx, spawn_table.roll());
+            spawn_points.push((map_idx, spawn_table.roll(rng)));
             areas.remove(array_index);
         }
     }
 
     // Actually spawn the monsters
+    spawn_list.append(&mut spawn_points);
+}
+
+const MAX_TIMED_SPAWNS : i32 = 4;
+
+/// Adds whatever only comes out at this time of day to a freshly built level. This is kept
+/// apart from building the level, so the level itself comes out the same whatever the hour.
+pub fn spawn_for_time_of_day(ecs: &mut World, spawn_list : &[(usize, String)], player_start : usize) {
+    let night = ecs.fetch::<crate::GameClock>().is_night();
+    let raws = get_raws(ecs);
+    let mut spawn_points : Vec<(usize, String)> = Vec::new();
+    {
+        let map = ecs.fetch::<Map>();
+        let spawn_table = get_spawn_table_for_time(&raws, &map.branch, map.depth, night);
+        let mut areas : Vec<usize> = (0 .. map.tiles.len())
+            .filter(|idx| map.walkable(*idx))
+            .filter(|idx| *idx != player_start && !spawn_list.iter().any(|s| s.0 == *idx))
+            .collect();
+
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        for _i in 0 .. MAX_TIMED_SPAWNS {
+            if areas.is_empty() { break; }
+            let array_index = (rng.roll_dice(1, areas.len() as i32)-1) as usize;
+            spawn_points.push((areas.remove(array_index), spawn_table.roll(&mut rng)));
+        }
+    }
+
     for spawn in spawn_points.iter() {
-        spawn_list.push((*spawn.0, spawn.1.to_string()));
+        spawn_entity(ecs, &(&spawn.0, &spawn.1));
     }
 }
 
@@ -145,7 +173,8 @@ pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) {
     let y = (*spawn.0 / width) as i32;
     std::mem::drop(map);
 
-    let spawn_result = spawn_named_entity(&RAWS.lock().unwrap(), ecs, &spawn.1, SpawnType::AtPosition{ x, y});
+    let raws = crate::raws::get_raws(ecs);
+    let spawn_result = spawn_named_entity(&raws, ecs, &spawn.1, SpawnType::AtPosition{ x, y});
     if spawn_result.is_some() {
         return;
     }
@@ -158,7 +187,7 @@ pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) {
 pub fn spawn_town_portal(ecs: &mut World) {
     // Get current position & depth
     let map = ecs.fetch::<Map>();
-    let player_depth = map.depth;
+    let player_level = map.key();
     let player_pos = ecs.fetch::<rltk::Point>();
     let player_x = player_pos.x;
     let player_y = player_pos.y;
@@ -167,7 +196,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
 
     // Find part of the town for the portal
     let dm = ecs.fetch::<MasterDungeonMap>();
-    let town_map = dm.get_map(1).unwrap();
+    let town_map = dm.get_map(&LevelKey::main(1)).unwrap();
     let mut stairs_idx = 0;
     for (idx, tt) in town_map.tiles.iter().enumerate() {
         if *tt == TileType::DownStairs {
@@ -181,7 +210,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
 
     // Spawn the portal itself
     ecs.create_entity()
-        .with(OtherLevelPosition { x: portal_x, y: portal_y, depth: 1 })
+        .with(OtherLevelPosition { x: portal_x, y: portal_y, level: LevelKey::main(1) })
         .with(Renderable {
             glyph: rltk::to_cp437('♥'),
             fg: RGB::named(rltk::CYAN),
@@ -189,7 +218,7 @@ pub fn spawn_town_portal(ecs: &mut World) {
             render_order: 0
         })
         .with(EntryTrigger{})
-        .with(TeleportTo{ x: player_x, y: player_y, depth: player_depth, player_only: true })
+        .with(TeleportTo{ x: player_x, y: player_y, level: player_level, player_only: true })
         .with(SingleActivation{})
         .with(Name{ name : "Town Portal".to_string() })
         .build();
@@ -1,5 +1,7 @@
 use specs::prelude::*;
 use crate::{MyTurn, Faction, Position, Map, raws::Reaction, WantsToMelee, TileSize};
+use crate::raws::RawMaster;
+use std::sync::Arc;
 
 pub struct AdjacentAI {}
 
@@ -13,11 +15,12 @@ impl<'a> System<'a> for AdjacentAI {
         WriteStorage<'a, WantsToMelee>,
         Entities<'a>,
         ReadExpect<'a, Entity>,
-        ReadStorage<'a, TileSize>
+        ReadStorage<'a, TileSize>,
+        ReadExpect<'a, Arc<RawMaster>>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes) = data;
+        let (mut turns, factions, positions, map, mut want_melee, entities, player, sizes, raws) = data;
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, _turn, my_faction, pos) in (&entities, &turns, &factions, &positions).join() {
@@ -34,20 +37,20 @@ impl<'a> System<'a> for AdjacentAI {
                     parent_rect.get_all_tiles().iter().filter(|t| !mob_rect.contains(t)).for_each(|t| {
                         if t.0 > 0 && t.0 < w-1 && t.1 > 0 && t.1 < h-1 {
                             let target_idx = map.xy_idx(t.0, t.1);
-                            evaluate(target_idx, &map, &factions, &my_faction.name, &mut reactions);
+                            evaluate(target_idx, &map, &raws, &factions, &my_faction.name, &mut reactions);
                         }
                     });
                 } else {
 
                     // Add possible reactions to adjacents for each direction
-                    if pos.x > 0 { evaluate(idx-1, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.x < w-1 { evaluate(idx+1, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y > 0 { evaluate(idx-w as usize, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y < h-1 { evaluate(idx+w as usize, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y > 0 && pos.x > 0 { evaluate((idx-w as usize)-1, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y > 0 && pos.x < w-1 { evaluate((idx-w as usize)+1, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y < h-1 && pos.x > 0 { evaluate((idx+w as usize)-1, &map, &factions, &my_faction.name, &mut reactions); }
-                    if pos.y < h-1 && pos.x < w-1 { evaluate((idx+w as usize)+1, &map, &factions, &my_faction.name, &mut reactions); }
+                    if pos.x > 0 { evaluate(idx-1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.x < w-1 { evaluate(idx+1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y > 0 { evaluate(idx-w as usize, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y < h-1 { evaluate(idx+w as usize, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y > 0 && pos.x > 0 { evaluate((idx-w as usize)-1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y > 0 && pos.x < w-1 { evaluate((idx-w as usize)+1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y < h-1 && pos.x > 0 { evaluate((idx+w as usize)-1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
+                    if pos.y < h-1 && pos.x < w-1 { evaluate((idx+w as usize)+1, &map, &raws, &factions, &my_faction.name, &mut reactions); }
 
                 }
 
@@ -70,12 +73,12 @@ impl<'a> System<'a> for AdjacentAI {
     }
 }
 
-fn evaluate(idx : usize, map : &Map, factions : &ReadStorage<Faction>, my_faction : &str, reactions : &mut Vec<(Entity, Reaction)>) {
-    crate::spatial::for_each_tile_content(idx, |other_entity| {
+fn evaluate(idx : usize, map : &Map, raws : &RawMaster, factions : &ReadStorage<Faction>, my_faction : &str, reactions : &mut Vec<(Entity, Reaction)>) {
+    map.spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 other_entity,
-                crate::raws::faction_reaction(my_faction, &faction.name, &crate::raws::RAWS.lock().unwrap())
+                crate::raws::faction_reaction(my_faction, &faction.name, raws)
             ));
         }
     });
@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use crate::{MyTurn, WantsToApproach, Position, Map, ApplyMove};
+use crate::{MyTurn, WantsToApproach, Position, Map, ApplyMove, map::{FlowFields, step_toward}};
 
 pub struct ApproachAI {}
 
@@ -11,25 +11,35 @@ impl<'a> System<'a> for ApproachAI {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
-        WriteStorage<'a, ApplyMove>
+        WriteStorage<'a, ApplyMove>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, FlowFields>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, mut want_approach, positions, mut map,
-            entities, mut apply_move) = data;
+        let (mut turns, mut want_approach, positions, map,
+            entities, mut apply_move, player, mut flow) = data;
+
+        flow.sync(&map);
+        let player_idx = positions.get(*player).map(|pos| map.xy_idx(pos.x, pos.y));
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, approach, _myturn) in
             (&entities, &positions, &want_approach, &turns).join()
         {
             turn_done.push(entity);
-            let path = rltk::a_star_search(
-                map.xy_idx(pos.x, pos.y),
-                map.xy_idx(approach.idx % map.width, approach.idx / map.width),
-                &mut *map
-            );
-            if path.success && path.steps.len()>1 {
-                apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let target_idx = approach.idx as usize;
+
+            // Everyone heading for the player shares a field; anywhere else gets its own path
+            let step = if Some(target_idx) == player_idx {
+                step_toward(flow.toward_player(&map, target_idx), &map, my_idx)
+            } else {
+                let path = rltk::a_star_search(my_idx, target_idx, &*map);
+                if path.success && path.steps.len()>1 { Some(path.steps[1]) } else { None }
+            };
+            if let Some(step) = step {
+                apply_move.insert(entity, ApplyMove{ dest_idx: step }).expect("Unable to insert");
             }
         }
 
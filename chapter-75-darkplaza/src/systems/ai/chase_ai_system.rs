@@ -1,7 +1,11 @@
 use specs::prelude::*;
-use crate::{MyTurn, Chasing, Position, Map, ApplyMove, TileSize};
+use crate::{MyTurn, Chasing, Position, Map, ApplyMove, TileSize, FootprintMap,
+    map::{FlowFields, step_toward}};
 use std::collections::HashMap;
 
+/// How far away (in steps along the shared field) a monster will keep chasing something.
+const MAX_CHASE_DISTANCE : f32 = 15.0;
+
 pub struct ChaseAI {}
 
 impl<'a> System<'a> for ChaseAI {
@@ -13,12 +17,14 @@ impl<'a> System<'a> for ChaseAI {
         WriteExpect<'a, Map>,
         Entities<'a>,
         WriteStorage<'a, ApplyMove>,
-        ReadStorage<'a, TileSize>
+        ReadStorage<'a, TileSize>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, FlowFields>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, mut chasing, positions, mut map,
-            entities, mut apply_move, sizes) = data;
+        let (mut turns, mut chasing, positions, map,
+            entities, mut apply_move, sizes, player, mut flow) = data;
 
         let mut targets : HashMap<Entity, (i32, i32)> = HashMap::new();
         let mut end_chase : Vec<Entity> = Vec::new();
@@ -36,32 +42,40 @@ impl<'a> System<'a> for ChaseAI {
         }
         end_chase.clear();
 
+        flow.sync(&map);
+
         let mut turn_done : Vec<Entity> = Vec::new();
-        for (entity, pos, _chase, _myturn) in
+        for (entity, pos, chase, _myturn) in
             (&entities, &positions, &chasing, &turns).join()
         {
             turn_done.push(entity);
             let target_pos = targets[&entity];
-            let path;
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let target_idx = map.xy_idx(target_pos.0, target_pos.1);
+            let step;
 
             if let Some(size) = sizes.get(entity) {
-                let mut map_copy = map.clone();
-                map_copy.populate_blocked_multi(size.x, size.y);
-                path = rltk::a_star_search(
-                    map_copy.xy_idx(pos.x, pos.y),
-                    map_copy.xy_idx(target_pos.0, target_pos.1),
-                    &mut map_copy
+                let footprint = FootprintMap::new(&*map, entity, size.x, size.y);
+                let path = rltk::a_star_search(
+                    footprint.xy_idx(pos.x, pos.y),
+                    target_idx,
+                    &footprint
                 );
+                step = if path.success && path.steps.len()>1 && path.steps.len()<MAX_CHASE_DISTANCE as usize { Some(path.steps[1]) } else { None };
+            } else if chase.target == *player {
+                // Single-tile monsters share a field toward the player, and give up if it's too far
+                let field = flow.toward_player(&map, target_idx);
+                step = if field.map[my_idx] < MAX_CHASE_DISTANCE {
+                    // Someone's in the way of the shared route, so look for a way around them
+                    step_toward(field, &map, my_idx).or_else(|| a_star_step(&map, my_idx, target_idx))
+                } else {
+                    None
+                };
             } else {
-                path = rltk::a_star_search(
-                    map.xy_idx(pos.x, pos.y),
-                    map.xy_idx(target_pos.0, target_pos.1),
-                    &mut *map
-                );
+                step = a_star_step(&map, my_idx, target_idx);
             }
-            if path.success && path.steps.len()>1 && path.steps.len()<15 {
-                apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
-                turn_done.push(entity);
+            if let Some(step) = step {
+                apply_move.insert(entity, ApplyMove{ dest_idx: step }).expect("Unable to insert");
             } else {
                 end_chase.push(entity);
             }
@@ -75,3 +89,8 @@ impl<'a> System<'a> for ChaseAI {
         }
     }
 }
+
+fn a_star_step(map : &Map, from : usize, to : usize) -> Option<usize> {
+    let path = rltk::a_star_search(from, to, map);
+    if path.success && path.steps.len()>1 && path.steps.len()<MAX_CHASE_DISTANCE as usize { Some(path.steps[1]) } else { None }
+}
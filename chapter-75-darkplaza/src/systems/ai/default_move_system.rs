@@ -1,5 +1,7 @@
 use specs::prelude::*;
-use crate::{MyTurn, MoveMode, Movement, Position, Map, map::tile_walkable, ApplyMove};
+use crate::{MyTurn, MoveMode, Movement, Position, Map, ApplyMove, Digger};
+use crate::effects::{EffectQueue, EffectType, Targets};
+use rltk::RandomNumberGenerator;
 
 pub struct DefaultMoveAI {}
 
@@ -11,12 +13,15 @@ impl<'a> System<'a> for DefaultMoveAI {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         WriteStorage<'a, ApplyMove>,
-        Entities<'a>
+        Entities<'a>,
+        ReadStorage<'a, Digger>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, EffectQueue>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut turns, mut move_mode, positions, mut map,
-            mut apply_move, entities) = data;
+            mut apply_move, entities, diggers, mut rng, mut effects) = data;
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, mut mode, _myturn) in
@@ -30,7 +35,7 @@ impl<'a> System<'a> for DefaultMoveAI {
                 Movement::Random => {
                     let mut x = pos.x;
                     let mut y = pos.y;
-                    let move_roll = crate::rng::roll_dice(1, 5);
+                    let move_roll = rng.roll_dice(1, 5);
                     match move_roll {
                         1 => x -= 1,
                         2 => x += 1,
@@ -41,10 +46,19 @@ impl<'a> System<'a> for DefaultMoveAI {
 
                     if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 {
                         let dest_idx = map.xy_idx(x, y);
-                        if !crate::spatial::is_blocked(dest_idx) {
+                        if !map.spatial.is_blocked(dest_idx) {
                             apply_move.insert(entity, ApplyMove{ dest_idx })
                                 .expect("Unable to insert");
                             turn_done.push(entity);
+                        } else if let Some(digger) = diggers.get(entity) {
+                            // Burrowers wander straight through the rock
+                            if map.tile_set.has_flag(map.tiles[dest_idx], "diggable") {
+                                effects.add(
+                                    Some(entity),
+                                    EffectType::Dig{ turns : digger.turns },
+                                    Targets::Tile{ tile_idx : dest_idx as i32 }
+                                );
+                            }
                         }
                     }
                 },
@@ -53,21 +67,46 @@ impl<'a> System<'a> for DefaultMoveAI {
                     if let Some(path) = path {
                         // We have a target - go there
                         if path.len()>1 {
-                            if !crate::spatial::is_blocked(path[1] as usize) {
+                            if !map.spatial.is_blocked(path[1] as usize) {
                                 apply_move.insert(entity, ApplyMove{ dest_idx : path[1] })
                                     .expect("Unable to insert");
                                 path.remove(0); // Remove the first step in the path
                                 turn_done.push(entity);
+                            } else if let Some(digger) = diggers.get(entity) {
+                                // Burrowers dig through any rock in their way, and give up
+                                // on anything else that won't move
+                                let next = path[1];
+                                if map.tile_set.has_flag(map.tiles[next], "diggable") {
+                                    effects.add(
+                                        Some(entity),
+                                        EffectType::Dig{ turns : digger.turns },
+                                        Targets::Tile{ tile_idx : next as i32 }
+                                    );
+                                } else if !map.walkable(next) {
+                                    mode.mode = Movement::RandomWaypoint{ path : None };
+                                }
                             }
                             // Otherwise we wait a turn to see if the path clears up
                         } else {
                             mode.mode = Movement::RandomWaypoint{ path : None };
                         }
                     } else {
-                        let target_x = crate::rng::roll_dice(1, map.width-2);
-                        let target_y = crate::rng::roll_dice(1, map.height-2);
+                        let target_x = rng.roll_dice(1, map.width-2);
+                        let target_y = rng.roll_dice(1, map.height-2);
                         let idx = map.xy_idx(target_x, target_y);
-                        if tile_walkable(map.tiles[idx]) {
+                        if map.walkable(idx) && diggers.get(entity).is_some() {
+                            // Burrowers head straight there, whatever is in the way
+                            let steps : Vec<usize> = rltk::line2d(
+                                rltk::LineAlg::Bresenham,
+                                rltk::Point::new(pos.x, pos.y),
+                                rltk::Point::new(target_x, target_y)
+                            ).iter().map(|pt| map.xy_idx(pt.x, pt.y)).collect();
+                            if steps.len()>1 {
+                                mode.mode = Movement::RandomWaypoint{
+                                    path: Some(steps)
+                                };
+                            }
+                        } else if map.walkable(idx) {
                             let path = rltk::a_star_search(
                                 map.xy_idx(pos.x, pos.y),
                                 map.xy_idx(target_x, target_y),
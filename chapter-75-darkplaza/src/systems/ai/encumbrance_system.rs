@@ -1,6 +1,6 @@
 use specs::prelude::*;
 use crate::{EquipmentChanged, Item, InBackpack, Equipped, Pools, Attributes, AttributeBonus,
-    gamesystem::attr_bonus, StatusEffect, Slow};
+    gamesystem::attr_bonus, StatusEffect, Slow, gamelog::GameLog};
 use std::collections::HashMap;
 
 pub struct EncumbranceSystem {}
@@ -18,12 +18,13 @@ impl<'a> System<'a> for EncumbranceSystem {
         ReadExpect<'a, Entity>,
         ReadStorage<'a, AttributeBonus>,
         ReadStorage<'a, StatusEffect>,
-        ReadStorage<'a, Slow>
+        ReadStorage<'a, Slow>,
+        WriteExpect<'a, GameLog>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut equip_dirty, entities, items, backpacks, wielded,
-            mut pools, mut attributes, player, attrbonus, statuses, slowed) = data;
+            mut pools, mut attributes, player, attrbonus, statuses, slowed, mut log) = data;
 
         if equip_dirty.is_empty() { return; }
 
@@ -112,7 +113,7 @@ impl<'a> System<'a> for EncumbranceSystem {
                             crate::gamelog::Logger::new()
                                 .color(rltk::ORANGE)
                                 .append("You are overburdened, and suffering an initiative penalty.")
-                                .log();
+                                .log(&mut log);
                         }
                     }
                 }
@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use crate::{MyTurn, WantsToFlee, Position, Map, ApplyMove};
+use crate::{MyTurn, WantsToFlee, Position, Map, ApplyMove, map::{FlowFields, step_away}};
 
 pub struct FleeAI {}
 
@@ -11,12 +11,17 @@ impl<'a> System<'a> for FleeAI {
         WriteStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
-        WriteStorage<'a, ApplyMove>
+        WriteStorage<'a, ApplyMove>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, FlowFields>
     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, mut want_flee, positions, mut map,
-            entities, mut apply_move) = data;
+        let (mut turns, mut want_flee, positions, map,
+            entities, mut apply_move, player, mut flow) = data;
+
+        flow.sync(&map);
+        let player_idx = positions.get(*player).map(|pos| map.xy_idx(pos.x, pos.y));
 
         let mut turn_done : Vec<Entity> = Vec::new();
         for (entity, pos, flee, _myturn) in
@@ -24,15 +29,13 @@ impl<'a> System<'a> for FleeAI {
         {
             turn_done.push(entity);
             let my_idx = map.xy_idx(pos.x, pos.y);
-                map.populate_blocked();
-                let flee_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize, &flee.indices, &*map, 100.0);
-                let flee_target = rltk::DijkstraMap::find_highest_exit(&flee_map, my_idx, &*map);
-                if let Some(flee_target) = flee_target {
-                    if !crate::spatial::is_blocked(flee_target as usize) {
-                        apply_move.insert(entity, ApplyMove{ dest_idx : flee_target }).expect("Unable to insert");
-                        turn_done.push(entity);
-                    }
-                }
+            let flee_map = match player_idx {
+                Some(player_idx) if flee.indices == [player_idx] => flow.away_from_player(&map, player_idx),
+                _ => flow.toward(&map, &flee.indices)
+            };
+            if let Some(flee_target) = step_away(flee_map, &map, my_idx) {
+                apply_move.insert(entity, ApplyMove{ dest_idx : flee_target }).expect("Unable to insert");
+            }
         }
 
         want_flee.clear();
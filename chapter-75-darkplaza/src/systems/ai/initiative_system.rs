@@ -1,6 +1,8 @@
 use specs::prelude::*;
 use crate::{Initiative, Position, MyTurn, Attributes, RunState, Pools, Duration, 
     EquipmentChanged, StatusEffect, DamageOverTime};
+use crate::effects::{EffectQueue, EffectType, Targets};
+use rltk::RandomNumberGenerator;
 
 pub struct InitiativeSystem {}
 
@@ -18,13 +20,15 @@ impl<'a> System<'a> for InitiativeSystem {
                         WriteStorage<'a, Duration>,
                         WriteStorage<'a, EquipmentChanged>,
                         ReadStorage<'a, StatusEffect>,
-                        ReadStorage<'a, DamageOverTime>
+                        ReadStorage<'a, DamageOverTime>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, EffectQueue>
                     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut initiatives, positions, mut turns, entities, attributes,
             mut runstate, player, player_pos, pools, mut durations, mut dirty,
-            statuses, dots) = data;
+            statuses, dots, mut rng, mut effects) = data;
 
         if *runstate != RunState::Ticking { return; }
 
@@ -38,7 +42,7 @@ impl<'a> System<'a> for InitiativeSystem {
                 let mut myturn = true;
 
                 // Re-roll
-                initiative.current = 6 + crate::rng::roll_dice(1, 6);
+                initiative.current = 6 + rng.roll_dice(1, 6);
 
                 // Give a bonus for quickness
                 if let Some(attr) = attributes.get(entity) {
@@ -73,12 +77,11 @@ impl<'a> System<'a> for InitiativeSystem {
 
         // Handle durations
         if *runstate == RunState::AwaitingInput {
-            use crate::effects::*;
             for (effect_entity, duration, status) in (&entities, &mut durations, &statuses).join() {
                 if entities.is_alive(status.target) {
                     duration.turns -= 1;
                     if let Some(dot) = dots.get(effect_entity) {
-                        add_effect(
+                        effects.add(
                             None, 
                             EffectType::Damage{ amount : dot.damage }, 
                             Targets::Single{ target : status.target 
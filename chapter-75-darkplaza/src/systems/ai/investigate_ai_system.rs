@@ -0,0 +1,55 @@
+use specs::prelude::*;
+use crate::{MyTurn, WantsToInvestigate, Position, Map, ApplyMove};
+
+pub struct InvestigateAI {}
+
+impl<'a> System<'a> for InvestigateAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, WantsToInvestigate>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, ApplyMove>
+    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut turns, mut want_investigate, positions, mut map,
+            entities, mut apply_move) = data;
+
+        let mut turn_done : Vec<Entity> = Vec::new();
+        let mut finished : Vec<Entity> = Vec::new();
+        for (entity, pos, investigate, _myturn) in
+            (&entities, &positions, &mut want_investigate, &turns).join()
+        {
+            investigate.turns -= 1;
+            let target = rltk::Point::new(investigate.idx % map.width, investigate.idx / map.width);
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(rltk::Point::new(pos.x, pos.y), target);
+            if investigate.turns < 1 || distance < 1.5 {
+                // Nothing to see here; go back to whatever we were doing
+                finished.push(entity);
+                continue;
+            }
+
+            turn_done.push(entity);
+            let path = rltk::a_star_search(
+                map.xy_idx(pos.x, pos.y),
+                investigate.idx as usize,
+                &mut *map
+            );
+            if path.success && path.steps.len()>1 {
+                apply_move.insert(entity, ApplyMove{ dest_idx: path.steps[1] }).expect("Unable to insert");
+            } else {
+                finished.push(entity);
+            }
+        }
+
+        for done in finished.iter() {
+            want_investigate.remove(*done);
+        }
+        for done in turn_done.iter() {
+            turns.remove(*done);
+        }
+    }
+}
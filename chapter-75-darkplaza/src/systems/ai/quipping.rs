@@ -1,5 +1,7 @@
 use specs::prelude::*;
 use crate::{Quips, Name, MyTurn, Viewshed};
+use crate::gamelog::GameLog;
+use rltk::RandomNumberGenerator;
 
 pub struct QuipSystem {}
 
@@ -10,22 +12,24 @@ impl<'a> System<'a> for QuipSystem {
         ReadStorage<'a, Name>,
         ReadStorage<'a, MyTurn>,
         ReadExpect<'a, rltk::Point>,
-        ReadStorage<'a, Viewshed>);
+        ReadStorage<'a, Viewshed>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, GameLog>);
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut quips, names, turns, player_pos, viewsheds) = data;
+        let (mut quips, names, turns, player_pos, viewsheds, mut rng, mut log) = data;
 
         for (quip, name, viewshed, _turn) in (&mut quips, &names, &viewsheds, &turns).join() {
-            if !quip.available.is_empty() && viewshed.visible_tiles.contains(&player_pos) && crate::rng::roll_dice(1,6)==1 {
+            if !quip.available.is_empty() && viewshed.visible_tiles.contains(&player_pos) && rng.roll_dice(1,6)==1 {
                 let quip_index =
                     if quip.available.len() == 1 { 0 }
-                    else { (crate::rng::roll_dice(1, quip.available.len() as i32)-1) as usize };
+                    else { (rng.roll_dice(1, quip.available.len() as i32)-1) as usize };
 
                 crate::gamelog::Logger::new()
                     .npc_name(&name.name)
                     .append("says")
                     .item_name(&quip.available[quip_index])
-                    .log();
+                    .log(&mut log);
                 quip.available.remove(quip_index);
             }
         }
@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use crate::{MyTurn, Confusion, RunState, StatusEffect, effects::add_effect, effects::EffectType, effects::Targets};
+use crate::{MyTurn, Confusion, RunState, StatusEffect, effects::EffectQueue, effects::EffectType, effects::Targets};
 use std::collections::HashSet;
 
 pub struct TurnStatusSystem {}
@@ -10,11 +10,12 @@ impl<'a> System<'a> for TurnStatusSystem {
                         ReadStorage<'a, Confusion>,
                         Entities<'a>,
                         ReadExpect<'a, RunState>,
-                        ReadStorage<'a, StatusEffect>
+                        ReadStorage<'a, StatusEffect>,
+                        WriteExpect<'a, EffectQueue>
                     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, confusion, entities, runstate, statuses) = data;
+        let (mut turns, confusion, entities, runstate, statuses, mut effects) = data;
 
         if *runstate != RunState::Ticking { return; }
 
@@ -30,7 +31,7 @@ impl<'a> System<'a> for TurnStatusSystem {
             if entity_turns.contains(&status_effect.target) {
                 // Skip turn for confusion
                 if confusion.get(effect_entity).is_some() {
-                    add_effect(
+                    effects.add(
                         None, 
                         EffectType::Particle{
                             glyph : rltk::to_cp437('?'),
@@ -1,7 +1,10 @@
 use specs::prelude::*;
 use crate::{MyTurn, Faction, Position, Map, raws::Reaction, Viewshed, WantsToFlee,
     WantsToApproach, Chasing, SpecialAbilities, WantsToCastSpell, Name, SpellTemplate,
-    Equipped, Weapon, WantsToShoot};
+    Equipped, Weapon, WantsToShoot, Darkvision};
+use crate::raws::RawMaster;
+use rltk::RandomNumberGenerator;
+use std::sync::Arc;
 
 pub struct VisibleAI {}
 
@@ -24,13 +27,16 @@ impl<'a> System<'a> for VisibleAI {
         ReadStorage<'a, SpellTemplate>,
         ReadStorage<'a, Equipped>,
         ReadStorage<'a, Weapon>,
-        WriteStorage<'a, WantsToShoot>
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Darkvision>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadExpect<'a, Arc<RawMaster>>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (turns, factions, positions, map, mut want_approach, mut want_flee, entities, player,
             viewsheds, mut chasing, abilities, mut casting, names, spells,
-            equipped, weapons, mut wants_shoot) = data;
+            equipped, weapons, mut wants_shoot, darkvision, mut rng, raws) = data;
 
         for (entity, _turn, my_faction, pos, viewshed) in (&entities, &turns, &factions, &positions, &viewsheds).join() {
             if entity != *player {
@@ -39,8 +45,9 @@ impl<'a> System<'a> for VisibleAI {
                 let mut flee : Vec<usize> = Vec::new();
                 for visible_tile in viewshed.visible_tiles.iter() {
                     let idx = map.xy_idx(visible_tile.x, visible_tile.y);
-                    if my_idx != idx {
-                        evaluate(idx, &map, &factions, &my_faction.name, &mut reactions);
+                    // Anything lurking in the dark goes unnoticed
+                    if my_idx != idx && map.can_make_out(rltk::Point::new(pos.x, pos.y), darkvision.get(entity), idx) {
+                        evaluate(idx, &map, &raws, &factions, &my_faction.name, &mut reactions);
                     }
                 }
 
@@ -55,7 +62,7 @@ impl<'a> System<'a> for VisibleAI {
                             if let Some(abilities) = abilities.get(entity) {
                                 for ability in abilities.abilities.iter() {
                                     if range >= ability.min_range && range <= ability.range &&
-                                        crate::rng::roll_dice(1,100) <= (ability.chance * 100.0) as i32
+                                        rng.roll_dice(1,100) <= (ability.chance * 100.0) as i32
                                     {
                                         use crate::raws::find_spell_entity_by_name;
                                         casting.insert(
@@ -105,12 +112,12 @@ impl<'a> System<'a> for VisibleAI {
     }
 }
 
-fn evaluate(idx : usize, map : &Map, factions : &ReadStorage<Faction>, my_faction : &str, reactions : &mut Vec<(usize, Reaction, Entity)>) {
-    crate::spatial::for_each_tile_content(idx, |other_entity| {
+fn evaluate(idx : usize, map : &Map, raws : &RawMaster, factions : &ReadStorage<Faction>, my_faction : &str, reactions : &mut Vec<(usize, Reaction, Entity)>) {
+    map.spatial.for_each_tile_content(idx, |other_entity| {
         if let Some(faction) = factions.get(other_entity) {
             reactions.push((
                 idx,
-                crate::raws::faction_reaction(my_faction, &faction.name, &crate::raws::RAWS.lock().unwrap()),
+                crate::raws::faction_reaction(my_faction, &faction.name, raws),
                 other_entity
             ));
         }
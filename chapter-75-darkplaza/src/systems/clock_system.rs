@@ -0,0 +1,31 @@
+use specs::prelude::*;
+use crate::{Map, MyTurn, Viewshed, GameClock};
+
+pub struct ClockSystem {}
+
+impl<'a> System<'a> for ClockSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteExpect<'a, GameClock>,
+                        ReadExpect<'a, Map>,
+                        ReadStorage<'a, MyTurn>,
+                        ReadExpect<'a, Entity>,
+                        WriteStorage<'a, Viewshed>
+                    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut clock, map, turns, player, mut viewsheds) = data;
+
+        // Time passes on the player's clock
+        if turns.get(*player).is_none() { return; }
+
+        let daylight = clock.daylight();
+        clock.advance();
+
+        if map.outdoors && clock.daylight() != daylight {
+            // The light is changing, so everyone outside sees a different distance
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use specs::prelude::*;
+use crate::{MyTurn, Digging, Position, Map, effects::EffectQueue, effects::EffectType, effects::Targets};
+
+pub struct DiggingSystem {}
+
+impl<'a> System<'a> for DiggingSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteStorage<'a, MyTurn>,
+                        WriteStorage<'a, Digging>,
+                        ReadStorage<'a, Position>,
+                        ReadExpect<'a, Map>,
+                        Entities<'a>,
+                        ReadExpect<'a, Entity>,
+                        WriteExpect<'a, EffectQueue>
+                    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut turns, mut digging, positions, map, entities, player, mut effects) = data;
+
+        let mut stopped : Vec<Entity> = Vec::new();
+        let mut busy : Vec<Entity> = Vec::new();
+        for (entity, dig, pos, _myturn) in (&entities, &digging, &positions, &turns).join() {
+            // The player's digging goes turn by turn through their input, so they can stop
+            if entity == *player { continue; }
+
+            // You have to stay next to the rock to keep digging it
+            let target = rltk::Point::new(dig.tile_idx % map.width, dig.tile_idx / map.width);
+            if i32::abs(target.x - pos.x) > 1 || i32::abs(target.y - pos.y) > 1 {
+                stopped.push(entity);
+                continue;
+            }
+
+            effects.add(
+                Some(entity),
+                EffectType::Dig{ turns : dig.turns },
+                Targets::Tile{ tile_idx : dig.tile_idx }
+            );
+            busy.push(entity);
+        }
+
+        for entity in stopped {
+            digging.remove(entity);
+        }
+        for entity in busy {
+            turns.remove(entity);
+        }
+    }
+}
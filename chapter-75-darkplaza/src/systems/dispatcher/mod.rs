@@ -24,13 +24,18 @@ construct_dispatcher!(
     (VisibilitySystem, "visibility", &[]),
     (EncumbranceSystem, "encumbrance", &[]),
     (InitiativeSystem, "initiative", &[]),
+    (ClockSystem, "clock", &[]),
+    (FireSystem, "fire", &[]),
+    (GasSystem, "gas", &[]),
     (TurnStatusSystem, "turnstatus", &[]),
+    (DiggingSystem, "digging", &[]),
     (QuipSystem, "quips", &[]),
     (AdjacentAI, "adjacent", &[]),
     (VisibleAI, "visible", &[]),
     (ApproachAI, "approach", &[]),
     (FleeAI, "flee", &[]),
     (ChaseAI, "chase", &[]),
+    (InvestigateAI, "investigate", &[]),
     (DefaultMoveAI, "default_move", &[]),
     (MovementSystem, "movement", &[]),
     (TriggerSystem, "triggers", &[]),
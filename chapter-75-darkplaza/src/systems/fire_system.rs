@@ -0,0 +1,55 @@
+use specs::prelude::*;
+use crate::{Map, MyTurn, Flammable, effects::EffectQueue, effects::EffectType, effects::Targets};
+use rltk::RandomNumberGenerator;
+
+/// Damage dealt each turn to anything standing in a fire.
+const FIRE_DAMAGE : i32 = 4;
+
+pub struct FireSystem {}
+
+impl<'a> System<'a> for FireSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteExpect<'a, Map>,
+                        ReadStorage<'a, Flammable>,
+                        ReadStorage<'a, MyTurn>,
+                        ReadExpect<'a, Entity>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, EffectQueue>
+                    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut map, flammable, turns, player, mut rng, mut effects) = data;
+
+        // Fires burn on the player's clock
+        if turns.get(*player).is_none() || map.fires.is_empty() { return; }
+
+        let mut burning : Vec<usize> = map.fires.keys().cloned().collect();
+        burning.sort_unstable();
+        for idx in burning.iter() {
+            effects.add(None, EffectType::Damage{ amount : FIRE_DAMAGE }, Targets::Tile{ tile_idx : *idx as i32 });
+
+            // Spread to flammable neighbours
+            let x = *idx as i32 % map.width;
+            let y = *idx as i32 / map.width;
+            for (nx, ny) in [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter() {
+                if *nx < 1 || *nx > map.width-2 || *ny < 1 || *ny > map.height-2 { continue; }
+                let neighbour = map.xy_idx(*nx, *ny);
+                if map.fires.contains_key(&neighbour) { continue; }
+                let mut catches = map.tile_set.has_flag(map.tiles[neighbour], "flammable");
+                map.spatial.for_each_tile_content(neighbour, |entity| {
+                    if flammable.get(entity).is_some() { catches = true; }
+                });
+                if catches && rng.roll_dice(1, 3) == 1 {
+                    effects.add(None, EffectType::Ignite, Targets::Tile{ tile_idx : neighbour as i32 });
+                }
+            }
+
+            let fuel = map.fires.get_mut(idx).unwrap();
+            *fuel -= 1;
+            if *fuel < 1 {
+                map.fires.remove(idx);
+                effects.add(None, EffectType::BurnOut, Targets::Tile{ tile_idx : *idx as i32 });
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use specs::prelude::*;
+use crate::{Map, MyTurn, Position, Pools, Viewshed, StatusEffect, DamageOverTime, Confusion,
+    map::GasType, effects::EffectQueue, effects::EffectType, effects::Targets};
+use std::collections::HashSet;
+
+/// Damage per turn from breathing poison gas.
+const POISON_DAMAGE : i32 = 4;
+/// How long a lungful of spores leaves you confused.
+const SPORE_CONFUSION_TURNS : i32 = 4;
+
+pub struct GasSystem {}
+
+impl<'a> System<'a> for GasSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteExpect<'a, Map>,
+                        Entities<'a>,
+                        ReadStorage<'a, MyTurn>,
+                        ReadExpect<'a, Entity>,
+                        ReadStorage<'a, Position>,
+                        ReadStorage<'a, Pools>,
+                        WriteStorage<'a, Viewshed>,
+                        ReadStorage<'a, StatusEffect>,
+                        ReadStorage<'a, DamageOverTime>,
+                        ReadStorage<'a, Confusion>,
+                        WriteExpect<'a, EffectQueue>
+                    );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (mut map, entities, turns, player, positions, pools, mut viewsheds,
+            statuses, dots, confusion, mut effects) = data;
+
+        // Gas drifts on the player's clock
+        if turns.get(*player).is_none() || map.gas.is_empty() { return; }
+
+        let had_smoke = map.gas.values().any(|cloud| cloud.gas == GasType::Smoke);
+        map.diffuse_gas();
+        if had_smoke {
+            // The smoke has moved, so what everyone can see has too
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+
+        // Anyone already suffering doesn't get it again until it wears off
+        let poisoned : HashSet<Entity> = (&statuses, &dots).join().map(|(status, _)| status.target).collect();
+        let confused : HashSet<Entity> = (&statuses, &confusion).join().map(|(status, _)| status.target).collect();
+
+        for (entity, pos, _pools) in (&entities, &positions, &pools).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            if let Some(cloud) = map.gas.get(&idx) {
+                match cloud.gas {
+                    GasType::Poison if !poisoned.contains(&entity) => {
+                        effects.add(None, EffectType::DamageOverTime{ damage : POISON_DAMAGE }, Targets::Single{ target : entity });
+                    }
+                    GasType::Spores if !confused.contains(&entity) => {
+                        effects.add(None, EffectType::Confusion{ turns : SPORE_CONFUSION_TURNS }, Targets::Single{ target : entity });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
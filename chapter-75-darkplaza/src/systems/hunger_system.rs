@@ -1,5 +1,6 @@
 use specs::prelude::*;
 use crate::{HungerClock, HungerState, MyTurn, effects::*};
+use crate::gamelog::GameLog;
 
 pub struct HungerSystem {}
 
@@ -9,11 +10,13 @@ impl<'a> System<'a> for HungerSystem {
                         Entities<'a>,
                         WriteStorage<'a, HungerClock>,
                         ReadExpect<'a, Entity>, // The player
-                        ReadStorage<'a, MyTurn>
+                        ReadStorage<'a, MyTurn>,
+                        WriteExpect<'a, EffectQueue>,
+                        WriteExpect<'a, GameLog>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, turns) = data;
+        let (entities, mut hunger_clock, player_entity, turns, mut effects, mut log) = data;
 
         for (entity, mut clock, _myturn) in (&entities, &mut hunger_clock, &turns).join() {
             clock.duration -= 1;
@@ -26,7 +29,7 @@ impl<'a> System<'a> for HungerSystem {
                             crate::gamelog::Logger::new()
                                 .color(rltk::ORANGE)
                                 .append("You are no longer well fed")
-                                .log();
+                                .log(&mut log);
                         }
                     }
                     HungerState::Normal => {
@@ -36,7 +39,7 @@ impl<'a> System<'a> for HungerSystem {
                             crate::gamelog::Logger::new()
                                 .color(rltk::ORANGE)
                                 .append("You are hungry")
-                                .log();
+                                .log(&mut log);
                         }
                     }
                     HungerState::Hungry => {
@@ -46,7 +49,7 @@ impl<'a> System<'a> for HungerSystem {
                             crate::gamelog::Logger::new()
                                 .color(rltk::RED)
                                 .append("You are starving!")
-                                .log();
+                                .log(&mut log);
                         }
                     }
                     HungerState::Starving => {
@@ -55,9 +58,9 @@ impl<'a> System<'a> for HungerSystem {
                             crate::gamelog::Logger::new()
                                 .color(rltk::RED)
                                 .append("Your hunger pangs are getting painful! You suffer 1 hp damage.")
-                                .log();
+                                .log(&mut log);
                         }
-                        add_effect(
+                        effects.add(
                             None,
                             EffectType::Damage{ amount: 1},
                             Targets::Single{ target: entity }
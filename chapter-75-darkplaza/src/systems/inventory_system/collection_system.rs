@@ -1,6 +1,7 @@
 use specs::prelude::*;
 use super::{WantsToPickupItem, Name, InBackpack, Position, EquipmentChanged,
     MagicItem, ObfuscatedName, MasterDungeonMap };
+use crate::gamelog::GameLog;
 
 pub struct ItemCollectionSystem {}
 
@@ -14,12 +15,13 @@ impl<'a> System<'a> for ItemCollectionSystem {
                         WriteStorage<'a, EquipmentChanged>,
                         ReadStorage<'a, MagicItem>,
                         ReadStorage<'a, ObfuscatedName>,
-                        ReadExpect<'a, MasterDungeonMap>
+                        ReadExpect<'a, MasterDungeonMap>,
+                        WriteExpect<'a, GameLog>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
         let (player_entity, mut wants_pickup, mut positions, names,
-            mut backpack, mut dirty, magic_items, obfuscated_names, dm) = data;
+            mut backpack, mut dirty, magic_items, obfuscated_names, dm, mut log) = data;
 
         for pickup in wants_pickup.join() {
             positions.remove(pickup.item);
@@ -32,7 +34,7 @@ impl<'a> System<'a> for ItemCollectionSystem {
                     .item_name(
                         super::obfuscate_name(pickup.item, &names, &magic_items, &obfuscated_names, &dm)
                     )
-                    .log();
+                    .log(&mut log);
             }
         }
 
@@ -1,6 +1,7 @@
 use specs::prelude::*;
 use super::{Name, InBackpack, Position, WantsToDropItem, EquipmentChanged,
     MagicItem, ObfuscatedName, MasterDungeonMap};
+use crate::gamelog::GameLog;
 
 pub struct ItemDropSystem {}
 
@@ -15,12 +16,13 @@ impl<'a> System<'a> for ItemDropSystem {
                         WriteStorage<'a, EquipmentChanged>,
                         ReadStorage<'a, MagicItem>,
                         ReadStorage<'a, ObfuscatedName>,
-                        ReadExpect<'a, MasterDungeonMap>
+                        ReadExpect<'a, MasterDungeonMap>,
+                        WriteExpect<'a, GameLog>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
         let (player_entity, entities, mut wants_drop, names, mut positions,
-            mut backpack, mut dirty, magic_items, obfuscated_names, dm) = data;
+            mut backpack, mut dirty, magic_items, obfuscated_names, dm, mut log) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
             let mut dropper_pos : Position = Position{x:0, y:0};
@@ -39,7 +41,7 @@ impl<'a> System<'a> for ItemDropSystem {
                     .item_name(
                         super::obfuscate_name(to_drop.item, &names, &magic_items, &obfuscated_names, &dm)
                     )
-                    .log();
+                    .log(&mut log);
             }
         }
 
@@ -1,6 +1,7 @@
 use specs::prelude::*;
 use super::{Name, InBackpack, WantsToUseItem, Equippable, Equipped, EquipmentChanged,
     IdentifiedItem, CursedItem};
+use crate::gamelog::GameLog;
 
 pub struct ItemEquipOnUse {}
 
@@ -15,13 +16,14 @@ impl<'a> System<'a> for ItemEquipOnUse {
                         WriteStorage<'a, InBackpack>,
                         WriteStorage<'a, EquipmentChanged>,
                         WriteStorage<'a, IdentifiedItem>,
-                        ReadStorage<'a, CursedItem>
+                        ReadStorage<'a, CursedItem>,
+                        WriteExpect<'a, GameLog>
                       );
 
     #[allow(clippy::cognitive_complexity)]
     fn run(&mut self, data : Self::SystemData) {
         let (player_entity, entities, mut wants_use, names, equippable, 
-            mut equipped, mut backpack, mut dirty, mut identified_item, cursed) = data;
+            mut equipped, mut backpack, mut dirty, mut identified_item, cursed, mut log) = data;
 
         let mut remove_use : Vec<Entity> = Vec::new();
         for (target, useitem) in (&entities, &wants_use).join() {
@@ -39,7 +41,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                                 .append("You cannot unequip")
                                 .item_name(&name.name)
                                 .append("- it is cursed!")
-                                .log();
+                                .log(&mut log);
                             can_equip = false;
                         } else {
                             to_unequip.push(item_entity);
@@ -47,7 +49,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                                 crate::gamelog::Logger::new()
                                     .append("You unequip")
                                     .item_name(&name.name)
-                                    .log();
+                                    .log(&mut log);
                             }
                         }
                     }
@@ -73,7 +75,7 @@ impl<'a> System<'a> for ItemEquipOnUse {
                         crate::gamelog::Logger::new()
                             .append("You equip")
                             .item_name(&names.get(useitem.item).unwrap().name)
-                            .log();
+                            .log(&mut log);
                     }
 
                     dirty.insert(target, EquipmentChanged{}).expect("Unable to insert");
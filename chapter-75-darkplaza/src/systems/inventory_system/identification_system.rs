@@ -1,5 +1,7 @@
 use specs::prelude::*;
 use super::{Name, IdentifiedItem, Item, ObfuscatedName};
+use crate::raws::RawMaster;
+use std::sync::Arc;
 
 pub struct ItemIdentificationSystem {}
 
@@ -12,14 +14,15 @@ impl<'a> System<'a> for ItemIdentificationSystem {
                         ReadStorage<'a, Item>,
                         ReadStorage<'a, Name>,
                         WriteStorage<'a, ObfuscatedName>,
-                        Entities<'a>
+                        Entities<'a>,
+                        ReadExpect<'a, Arc<RawMaster>>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (player, mut identified, mut dm, items, names, mut obfuscated_names, entities) = data;
+        let (player, mut identified, mut dm, items, names, mut obfuscated_names, entities, raws) = data;
 
         for (_p, id) in (&player, &identified).join() {
-            if !dm.identified_items.contains(&id.name) && crate::raws::is_tag_magic(&id.name) {
+            if !dm.identified_items.contains(&id.name) && crate::raws::is_tag_magic(&raws, &id.name) {
                 dm.identified_items.insert(id.name.clone());
 
                 for (entity, _item, name) in (&entities, &items, &names).join() {
@@ -1,5 +1,6 @@
 use specs::prelude::*;
 use super::{InBackpack, Equipped, WantsToRemoveItem, CursedItem, Name, EquipmentChanged};
+use crate::gamelog::GameLog;
 
 pub struct ItemRemoveSystem {}
 
@@ -12,11 +13,12 @@ impl<'a> System<'a> for ItemRemoveSystem {
                         WriteStorage<'a, InBackpack>,
                         ReadStorage<'a, CursedItem>,
                         ReadStorage<'a, Name>,
-                        WriteStorage<'a, EquipmentChanged>
+                        WriteStorage<'a, EquipmentChanged>,
+                        WriteExpect<'a, GameLog>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack, cursed, names, mut dirty) = data;
+        let (entities, mut wants_remove, mut equipped, mut backpack, cursed, names, mut dirty, mut log) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
             if cursed.get(to_remove.item).is_some() {
@@ -24,7 +26,7 @@ impl<'a> System<'a> for ItemRemoveSystem {
                     .append("You cannot remove")
                     .item_name(&names.get(to_remove.item).unwrap().name)
                     .append(" - it is cursed.")
-                    .log();
+                    .log(&mut log);
             } else {
                 equipped.remove(to_remove.item);
                 backpack.insert(to_remove.item, InBackpack{ owner: entity }).expect("Unable to insert backpack");
@@ -13,13 +13,14 @@ impl<'a> System<'a> for ItemUseSystem {
                         ReadStorage<'a, Name>,
                         ReadStorage<'a, AreaOfEffect>,
                         WriteStorage<'a, EquipmentChanged>,
-                        WriteStorage<'a, IdentifiedItem>
+                        WriteStorage<'a, IdentifiedItem>,
+                        WriteExpect<'a, EffectQueue>
                       );
 
     #[allow(clippy::cognitive_complexity)]
     fn run(&mut self, data : Self::SystemData) {
         let (player_entity, map, entities, mut wants_use, names,
-            aoe, mut dirty, mut identified_item) = data;
+            aoe, mut dirty, mut identified_item, mut effects) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             dirty.insert(entity, EquipmentChanged{}).expect("Unable to insert");
@@ -31,7 +32,7 @@ impl<'a> System<'a> for ItemUseSystem {
             }
 
             // Call the effects system
-            add_effect(
+            effects.add(
                 Some(entity),
                 EffectType::ItemUse{ item : useitem.item },
                 match useitem.target {
@@ -63,13 +64,14 @@ impl<'a> System<'a> for SpellUseSystem {
                         ReadStorage<'a, Name>,
                         ReadStorage<'a, AreaOfEffect>,
                         WriteStorage<'a, EquipmentChanged>,
-                        WriteStorage<'a, IdentifiedItem>
+                        WriteStorage<'a, IdentifiedItem>,
+                        WriteExpect<'a, EffectQueue>
                       );
 
     #[allow(clippy::cognitive_complexity)]
     fn run(&mut self, data : Self::SystemData) {
         let (player_entity, map, entities, mut wants_use, names,
-            aoe, mut dirty, mut identified_item) = data;
+            aoe, mut dirty, mut identified_item, mut effects) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             dirty.insert(entity, EquipmentChanged{}).expect("Unable to insert");
@@ -81,7 +83,7 @@ impl<'a> System<'a> for SpellUseSystem {
             }
 
             // Call the effects system
-            add_effect(
+            effects.add(
                 Some(entity),
                 EffectType::SpellUse{ spell : useitem.spell },
                 match useitem.target {
@@ -1,40 +1,91 @@
 use specs::prelude::*;
-use crate::{Viewshed, Position, Map, LightSource};
+use crate::{Position, Map, LightSource, LightCache, GameClock, rng::VisualRng};
 use rltk::RGB;
 
+/// How far the light from a burning tile reaches.
+const FIRE_LIGHT_RANGE : i32 = 3;
+
 pub struct LightingSystem {}
 
 impl<'a> System<'a> for LightingSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = ( WriteExpect<'a, Map>,
-                        ReadStorage<'a, Viewshed>,
+                        Entities<'a>,
                         ReadStorage<'a, Position>,
-                        ReadStorage<'a, LightSource>);
+                        ReadStorage<'a, LightSource>,
+                        WriteStorage<'a, LightCache>,
+                        ReadExpect<'a, GameClock>,
+                        WriteExpect<'a, VisualRng>);
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut map, viewshed, positions, lighting) = data;
+        let (mut map, entities, positions, lighting, mut caches, clock, mut rng) = data;
 
-        if map.outdoors {
-            return;
+        // Outside, the sky lights everything; underground, it starts out pitch black
+        let ambient = if map.outdoors { clock.ambient_light() } else { RGB::from_f32(0.0, 0.0, 0.0) };
+        for l in map.light.iter_mut() {
+            *l = ambient;
         }
 
-        let black = RGB::from_f32(0.0, 0.0, 0.0);
-        for l in map.light.iter_mut() {
-            *l = black;
+        // Work out where each light reaches, if it has moved or the walls around it have changed
+        // since last time. Lights fixed to the map only ever do this once.
+        for (entity, pos, light) in (&entities, &positions, &lighting).join() {
+            if !light.lit { continue; }
+            let fresh = caches.get(entity).map_or(false, |cache| {
+                cache.x == pos.x && cache.y == pos.y && cache.view_revision == map.view_revision
+            });
+            if !fresh {
+                caches.insert(entity, light_cache(&map, pos, light)).expect("Unable to insert");
+            }
+        }
+
+        // Add up the light falling on each tile
+        for (_pos, light, cache) in (&positions, &lighting, &caches).join() {
+            if !light.lit { continue; }
+            let brightness = if light.flicker > 0.0 {
+                1.0 - light.flicker * rng.0.roll_dice(1, 100) as f32 / 100.0
+            } else {
+                1.0
+            };
+            for (idx, intensity) in cache.tiles.iter() {
+                map.light[*idx] = map.light[*idx] + (light.color * (intensity * brightness));
+            }
         }
 
-        for (viewshed, pos, light) in (&viewshed, &positions, &lighting).join() {
-            let light_point = rltk::Point::new(pos.x, pos.y);
-            let range_f = light.range as f32;
-            for t in viewshed.visible_tiles.iter() {
+        // Fires light up their surroundings
+        let fires : Vec<usize> = map.fires.keys().cloned().collect();
+        let fire_color = RGB::named(rltk::ORANGE);
+        let range_f = FIRE_LIGHT_RANGE as f32;
+        for fire_idx in fires {
+            let fire_point = rltk::Point::new(fire_idx as i32 % map.width, fire_idx as i32 / map.width);
+            for t in rltk::field_of_view(fire_point, FIRE_LIGHT_RANGE, &*map).iter() {
                 if t.x > 0 && t.x < map.width && t.y > 0 && t.y < map.height {
                     let idx = map.xy_idx(t.x, t.y);
-                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(light_point, *t);
+                    let distance = rltk::DistanceAlg::Pythagoras.distance2d(fire_point, *t);
                     let intensity = (range_f - distance) / range_f;
 
-                    map.light[idx] = map.light[idx] + (light.color * intensity);
+                    map.light[idx] = map.light[idx] + (fire_color * intensity);
                 }
             }
         }
+
+        // Overlapping lights add up, but never past full brightness
+        for l in map.light.iter_mut() {
+            *l = RGB::from_f32(f32::min(l.r, 1.0), f32::min(l.g, 1.0), f32::min(l.b, 1.0));
+        }
     }
 }
+
+fn light_cache(map : &Map, pos : &Position, light : &LightSource) -> LightCache {
+    let light_point = rltk::Point::new(pos.x, pos.y);
+    let range_f = light.range as f32;
+    let tiles = rltk::field_of_view(light_point, light.range, map)
+        .iter()
+        .filter(|t| t.x > 0 && t.x < map.width && t.y > 0 && t.y < map.height)
+        .map(|t| {
+            let distance = rltk::DistanceAlg::Pythagoras.distance2d(light_point, *t);
+            (map.xy_idx(t.x, t.y), light.falloff.intensity(distance, range_f))
+        })
+        .collect();
+
+    LightCache{ x : pos.x, y : pos.y, view_revision : map.view_revision, tiles }
+}
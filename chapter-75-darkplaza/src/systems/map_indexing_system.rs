@@ -1,11 +1,11 @@
 use specs::prelude::*;
-use crate::{Map, Position, BlocksTile, Pools, spatial, TileSize};
+use crate::{Map, Position, BlocksTile, Pools, TileSize};
 
 pub struct MapIndexingSystem {}
 
 impl<'a> System<'a> for MapIndexingSystem {
     #[allow(clippy::type_complexity)]
-    type SystemData = ( ReadExpect<'a, Map>,
+    type SystemData = ( WriteExpect<'a, Map>,
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, BlocksTile>,
                         ReadStorage<'a, Pools>,
@@ -13,10 +13,10 @@ impl<'a> System<'a> for MapIndexingSystem {
                         Entities<'a>,);
 
     fn run(&mut self, data : Self::SystemData) {
-        let (map, position, blockers, pools, sizes, entities) = data;
+        let (mut map, position, blockers, pools, sizes, entities) = data;
 
-        spatial::clear();
-        spatial::populate_blocked_from_map(&*map);
+        map.clear_content_index();
+        map.populate_blocked();
         for (entity, position) in (&entities, &position).join() {
             let mut alive = true;
             if let Some(pools) = pools.get(entity) {
@@ -31,14 +31,14 @@ impl<'a> System<'a> for MapIndexingSystem {
                         for x in position.x .. position.x + size.x {
                             if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 {
                                 let idx = map.xy_idx(x, y);
-                                spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                                map.spatial.index_entity(entity, idx, blockers.get(entity).is_some());
                             }
                         }
                     }
                 } else {
                     // Single tile
                     let idx = map.xy_idx(position.x, position.y);
-                    spatial::index_entity(entity, idx, blockers.get(entity).is_some());
+                    map.spatial.index_entity(entity, idx, blockers.get(entity).is_some());
                 }
             }
         }
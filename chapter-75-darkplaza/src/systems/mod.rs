@@ -24,6 +24,14 @@ pub mod particle_system;
 use particle_system::ParticleSpawnSystem;
 mod lighting_system;
 use lighting_system::LightingSystem;
+mod digging_system;
+use digging_system::DiggingSystem;
+mod fire_system;
+use fire_system::FireSystem;
+mod gas_system;
+use gas_system::GasSystem;
+mod clock_system;
+use clock_system::ClockSystem;
 
 pub fn build() -> Box<dyn UnifiedDispatcher + 'static> {
     dispatcher::new()
@@ -25,19 +25,23 @@ impl<'a> System<'a> for MovementSystem {
 
         // Apply teleports
         for (entity, teleport) in (&entities, &apply_teleport).join() {
-            if teleport.dest_depth == map.depth {
+            if teleport.dest_level == map.key() {
                 apply_move.insert(entity, ApplyMove{ dest_idx: map.xy_idx(teleport.dest_x, teleport.dest_y) })
                     .expect("Unable to insert");
             } else if entity == *player_entity {
-                *runstate = RunState::TeleportingToOtherLevel{ x: teleport.dest_x, y: teleport.dest_y, depth: teleport.dest_depth };
+                *runstate = RunState::TeleportingToOtherLevel{
+                    x: teleport.dest_x,
+                    y: teleport.dest_y,
+                    level: teleport.dest_level.clone()
+                };
             } else if let Some(pos) = position.get(entity) {
                 let idx = map.xy_idx(pos.x, pos.y);
                 let dest_idx = map.xy_idx(teleport.dest_x, teleport.dest_y);
-                crate::spatial::move_entity(entity, idx, dest_idx);
+                map.spatial.move_entity(entity, idx, dest_idx);
                 other_level.insert(entity, OtherLevelPosition{
                     x: teleport.dest_x,
                     y: teleport.dest_y,
-                    depth: teleport.dest_depth })
+                    level: teleport.dest_level.clone() })
                     .expect("Unable to insert");
                 position.remove(entity);
             }
@@ -48,7 +52,7 @@ impl<'a> System<'a> for MovementSystem {
         for (entity, movement, mut pos) in (&entities, &apply_move, &mut position).join() {
             let start_idx = map.xy_idx(pos.x, pos.y);
             let dest_idx = movement.dest_idx as usize;
-            crate::spatial::move_entity(entity, start_idx, dest_idx);
+            map.spatial.move_entity(entity, start_idx, dest_idx);
             pos.x = movement.dest_idx as i32 % map.width;
             pos.y = movement.dest_idx as i32 / map.width;
             if let Some(vs) = viewsheds.get_mut(entity) {
@@ -1,8 +1,10 @@
 use specs::prelude::*;
-use crate::{ Rltk, ParticleLifetime, Position, Renderable };
+use crate::{ ParticleLifetime, Position, Renderable };
 use rltk::RGB;
 
-pub fn update_particles(ecs : &mut World, ctx : &Rltk) {
+/// Moves and fades particles as time passes on screen. A particle that has run its course
+/// is hidden rather than deleted; see `clear_particles`.
+pub fn update_particles(ecs : &mut World, frame_time_ms : f32) {
     let mut dead_particles : Vec<Entity> = Vec::new();
     {
         // Age out particles
@@ -10,7 +12,7 @@ pub fn update_particles(ecs : &mut World, ctx : &Rltk) {
         let entities = ecs.entities();
         for (entity, mut particle) in (&entities, &mut particles).join() {
             if let Some(animation) = &mut particle.animation {
-                animation.timer += ctx.frame_time_ms;
+                animation.timer += frame_time_ms;
                 if animation.timer > animation.step_time && animation.current_step < animation.path.len()-2 {
                     animation.current_step += 1;
 
@@ -21,14 +23,27 @@ pub fn update_particles(ecs : &mut World, ctx : &Rltk) {
                 }
             }
 
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
         }
     }
+    let mut renderables = ecs.write_storage::<Renderable>();
     for dead in dead_particles.iter() {
-        ecs.delete_entity(*dead).expect("Particle will not die");
+        renderables.remove(*dead);
+    }
+}
+
+/// Deletes every particle, at the start of each turn. Doing it here, rather than whenever
+/// one fades on screen, means the entity ids they free up are handed out again at the same
+/// points in a run however fast it's played, which replays depend on.
+pub fn clear_particles(ecs : &mut World) {
+    let particles : Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<ParticleLifetime>()).join()
+        .map(|(entity, _)| entity)
+        .collect();
+    for particle in particles.iter() {
+        ecs.delete_entity(*particle).expect("Particle will not die");
     }
 }
 
@@ -4,7 +4,8 @@ use crate::{Attributes, Skills, WantsToShoot, Name,
     Skill, Equipped, Weapon, EquipmentSlot, WeaponAttribute, 
     Wearable, NaturalAttackDefense,
     effects::*, Map, Position};
-use rltk::{to_cp437, RGB, Point};
+use rltk::{to_cp437, RGB, Point, RandomNumberGenerator};
+use crate::gamelog::GameLog;
 
 pub struct RangedCombatSystem {}
 
@@ -22,13 +23,16 @@ impl<'a> System<'a> for RangedCombatSystem {
                         ReadStorage<'a, Wearable>,
                         ReadStorage<'a, NaturalAttackDefense>,
                         ReadStorage<'a, Position>,
-                        ReadExpect<'a, Map>
+                        ReadExpect<'a, Map>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, EffectQueue>,
+                        WriteExpect<'a, GameLog>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
         let (entities, mut wants_shoot, names, attributes, skills,
             hunger_clock, pools, equipped_items, weapon, wearables, natural,
-            positions, map) = data;
+            positions, map, mut rng, mut effects, mut log) = data;
 
         for (entity, wants_shoot, name, attacker_attributes, attacker_skills, attacker_pools) in (&entities, &wants_shoot, &names, &attributes, &skills, &pools).join() {
             // Are the attacker and defender alive? Only attack if they are
@@ -38,10 +42,13 @@ impl<'a> System<'a> for RangedCombatSystem {
             if attacker_pools.hit_points.current > 0 && target_pools.hit_points.current > 0 {
                 let target_name = names.get(wants_shoot.target).unwrap();
 
+                // Fighting is noisy
+                effects.add(Some(entity), EffectType::Noise{ volume : COMBAT_NOISE }, Targets::Single{ target : entity });
+
                 // Fire projectile effect
                 let apos = positions.get(entity).unwrap();
                 let dpos = positions.get(wants_shoot.target).unwrap();
-                add_effect(
+                effects.add(
                     None, 
                     EffectType::ParticleProjectile{ 
                         glyph: to_cp437('*'),
@@ -72,7 +79,7 @@ impl<'a> System<'a> for RangedCombatSystem {
 
                 if let Some(nat) = natural.get(entity) {
                     if !nat.attacks.is_empty() {
-                        let attack_index = if nat.attacks.len()==1 { 0 } else { crate::rng::roll_dice(1, nat.attacks.len() as i32) as usize -1 };
+                        let attack_index = if nat.attacks.len()==1 { 0 } else { rng.roll_dice(1, nat.attacks.len() as i32) as usize -1 };
                         weapon_info.hit_bonus = nat.attacks[attack_index].hit_bonus;
                         weapon_info.damage_n_dice = nat.attacks[attack_index].damage_n_dice;
                         weapon_info.damage_die_type = nat.attacks[attack_index].damage_die_type;
@@ -88,7 +95,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                     }
                 }
 
-                let natural_roll = crate::rng::roll_dice(1, 20);
+                let natural_roll = rng.roll_dice(1, 20);
                 let attribute_hit_bonus = if weapon_info.attribute == WeaponAttribute::Might
                     { attacker_attributes.might.bonus }
                     else { attacker_attributes.quickness.bonus};
@@ -124,7 +131,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                 //println!("Armor class: {}", armor_class);
                 if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
                     // Target hit! Until we support weapons, we're going with 1d4
-                    let base_damage = crate::rng::roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
+                    let base_damage = rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
                     let attr_damage_bonus = attacker_attributes.might.bonus;
                     let skill_damage_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
                     let weapon_damage_bonus = weapon_info.damage_bonus;
@@ -136,7 +143,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                         base_damage, attr_damage_bonus, skill_damage_bonus,
                         weapon_damage_bonus, damage
                     );*/
-                    add_effect(
+                    effects.add(
                         Some(entity),
                         EffectType::Damage{ amount: damage },
                         Targets::Single{ target: wants_shoot.target }
@@ -148,11 +155,11 @@ impl<'a> System<'a> for RangedCombatSystem {
                         .append("for")
                         .damage(damage)
                         .append("hp.")
-                        .log();
+                        .log(&mut log);
 
                     // Proc effects
                     if let Some(chance) = &weapon_info.proc_chance {
-                        let roll = crate::rng::roll_dice(1, 100);
+                        let roll = rng.roll_dice(1, 100);
                         //println!("Roll {}, Chance {}", roll, chance);
                         if roll <= (chance * 100.0) as i32 {
                             //println!("Proc!");
@@ -161,7 +168,7 @@ impl<'a> System<'a> for RangedCombatSystem {
                             } else {
                                 Targets::Single { target : wants_shoot.target }
                             };
-                            add_effect(
+                            effects.add(
                                 Some(entity),
                                 EffectType::ItemUse{ item: weapon_entity.unwrap() },
                                 effect_target
@@ -176,8 +183,8 @@ impl<'a> System<'a> for RangedCombatSystem {
                         .append("considers attacking")
                         .npc_name(&target_name.name)
                         .append("but misjudges the timing!")
-                        .log();
-                    add_effect(
+                        .log(&mut log);
+                    effects.add(
                         None,
                         EffectType::Particle{ glyph: rltk::to_cp437('‼'), fg: rltk::RGB::named(rltk::BLUE), bg : rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
                         Targets::Single{ target: wants_shoot.target }
@@ -190,8 +197,8 @@ impl<'a> System<'a> for RangedCombatSystem {
                         .npc_name(&target_name.name)
                         .color(rltk::WHITE)
                         .append("but can't connect.")
-                        .log();
-                    add_effect(
+                        .log(&mut log);
+                    effects.add(
                         None,
                         EffectType::Particle{ glyph: rltk::to_cp437('‼'), fg: rltk::RGB::named(rltk::CYAN), bg : rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
                         Targets::Single{ target: wants_shoot.target }
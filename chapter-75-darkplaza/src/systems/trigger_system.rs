@@ -1,6 +1,7 @@
 use specs::prelude::*;
 use crate::{EntityMoved, Position, EntryTrigger, Map, Name,
     effects::*, AreaOfEffect};
+use crate::gamelog::GameLog;
 
 pub struct TriggerSystem {}
 
@@ -12,16 +13,18 @@ impl<'a> System<'a> for TriggerSystem {
                         ReadStorage<'a, EntryTrigger>,
                         ReadStorage<'a, Name>,
                         Entities<'a>,
-                        ReadStorage<'a, AreaOfEffect>);
+                        ReadStorage<'a, AreaOfEffect>,
+                        WriteExpect<'a, EffectQueue>,
+                        WriteExpect<'a, GameLog>);
 
     fn run(&mut self, data : Self::SystemData) {
         let (map, mut entity_moved, position, entry_trigger, 
-            names, entities, area_of_effect) = data;
+            names, entities, area_of_effect, mut effects, mut log) = data;
 
         // Iterate the entities that moved and their final position
         for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &position).join() {
             let idx = map.xy_idx(pos.x, pos.y);
-            crate::spatial::for_each_tile_content(idx, |entity_id| {
+            map.spatial.for_each_tile_content(idx, |entity_id| {
                 if entity != entity_id { // Do not bother to check yourself for being a trap!
                     let maybe_trigger = entry_trigger.get(entity_id);
                     match maybe_trigger {
@@ -33,11 +36,11 @@ impl<'a> System<'a> for TriggerSystem {
                                 crate::gamelog::Logger::new()
                                     .item_name(&name.name)
                                     .append("triggers!")
-                                    .log();
+                                    .log(&mut log);
                             }
 
                             // Call the effects system
-                            add_effect(
+                            effects.add(
                                 Some(entity),
                                 EffectType::TriggerFire{ trigger : entity_id },
                                 if let Some(aoe) = area_of_effect.get(entity_id) {
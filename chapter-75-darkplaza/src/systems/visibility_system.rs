@@ -1,6 +1,8 @@
 use specs::prelude::*;
-use crate::{Viewshed, Position, Map, Player, Hidden, BlocksVisibility, Name};
-use rltk::{field_of_view, Point};
+use crate::{Viewshed, Position, Map, Player, Hidden, BlocksVisibility, Name, GameClock};
+use rltk::{field_of_view, Point, RandomNumberGenerator};
+use std::collections::HashSet;
+use crate::gamelog::GameLog;
 
 pub struct VisibilitySystem {}
 
@@ -13,22 +15,26 @@ impl<'a> System<'a> for VisibilitySystem {
                         ReadStorage<'a, Player>,
                         WriteStorage<'a, Hidden>,
                         ReadStorage<'a, Name>,
-                        ReadStorage<'a, BlocksVisibility>);
+                        ReadStorage<'a, BlocksVisibility>,
+                        ReadExpect<'a, GameClock>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, GameLog>);
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut map, entities, mut viewshed, pos, player,
-            mut hidden, names, blocks_visibility) = data;
+            mut hidden, names, blocks_visibility, clock, mut rng, mut log) = data;
 
-        map.view_blocked.clear();
-        for (block_pos, _block) in (&pos, &blocks_visibility).join() {
-            let idx = map.xy_idx(block_pos.x, block_pos.y);
-            map.view_blocked.insert(idx);
-        }
+        let mut view_blocked : HashSet<usize> = (&pos, &blocks_visibility).join()
+            .map(|(block_pos, _block)| map.xy_idx(block_pos.x, block_pos.y))
+            .collect();
+        view_blocked.extend(map.gas.iter().filter(|(_, cloud)| cloud.blocks_view()).map(|(idx, _)| *idx));
+        map.set_view_blocked(view_blocked);
 
         for (ent,viewshed,pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
                 viewshed.dirty = false;
-                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
+                let range = if map.outdoors { clock.outdoor_vision(viewshed.range) } else { viewshed.range };
+                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, &*map);
                 viewshed.visible_tiles.retain(|p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height );
 
                 // If this is the player, reveal what they can see
@@ -42,16 +48,16 @@ impl<'a> System<'a> for VisibilitySystem {
                             map.visible_tiles[idx] = true;
 
                             // Chance to reveal hidden things
-                            crate::spatial::for_each_tile_content(idx, |e| {
+                            map.spatial.for_each_tile_content(idx, |e| {
                                 let maybe_hidden = hidden.get(e);
                                 if let Some(_maybe_hidden) = maybe_hidden {
-                                    if crate::rng::roll_dice(1,24)==1 {
+                                    if rng.roll_dice(1,24)==1 {
                                         let name = names.get(e);
                                         if let Some(name) = name {
                                             crate::gamelog::Logger::new()
                                                 .append("You spotted:")
                                                 .npc_name(&name.name)
-                                                .log();
+                                                .log(&mut log);
                                         }
                                         hidden.remove(e);
                                     }
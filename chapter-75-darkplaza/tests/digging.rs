@@ -0,0 +1,104 @@
+use chapter_75_darkplaza::{Headless, PlayerCommand, Map, Digging, Position, raws};
+use chapter_75_darkplaza::raws::SpawnType;
+use specs::prelude::*;
+
+const SEED : u64 = 12345;
+/// How many turns the pick-axe in the raws takes to get through one tile.
+const PICK_AXE_TURNS : usize = 5;
+
+/// Starts a game with the player holding a pick-axe.
+fn game_with_pick_axe() -> Headless {
+    let mut game = Headless::new(SEED);
+    game.run();
+    let raws = raws::get_raws(&game.state.ecs);
+    let player = *game.state.ecs.fetch::<Entity>();
+    raws::spawn_named_item(&raws, &mut game.state.ecs, "Pick-axe", SpawnType::Equipped{ by : player })
+        .expect("The raws should have a pick-axe");
+    game
+}
+
+/// Turns an empty floor tile next to `(x, y)` into rock, and says which way it is and where.
+fn rock_beside(game : &mut Headless, x : i32, y : i32) -> (i32, i32, usize) {
+    let mut map = game.state.ecs.fetch_mut::<Map>();
+    let map = &mut *map;
+    let wall = map.tile_set.named("Wall").expect("The raws should have walls");
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        let idx = map.xy_idx(x + dx, y + dy);
+        if map.walkable(idx) && !map.spatial.is_blocked(idx) && map.spatial.get_tile_content_clone(idx).is_empty() {
+            map.tiles[idx] = wall;
+            map.spatial.refresh_tile(&map.tiles, &map.tile_set, idx);
+            return (dx, dy, idx);
+        }
+    }
+    panic!("There's no room next to ({}, {})", x, y);
+}
+
+fn player_digging(game : &Headless) -> bool {
+    let player = *game.state.ecs.fetch::<Entity>();
+    game.state.ecs.read_storage::<Digging>().contains(player)
+}
+
+#[test]
+fn digging_breaks_through_rock() {
+    let mut game = game_with_pick_axe();
+    let start = *game.state.ecs.fetch::<rltk::Point>();
+    let (dx, dy, idx) = rock_beside(&mut game, start.x, start.y);
+
+    game.play(vec![PlayerCommand::Move{ dx, dy }]);
+    assert!(player_digging(&game));
+    game.play(vec![PlayerCommand::KeepDigging; PICK_AXE_TURNS - 2]);
+    assert!(!game.state.ecs.fetch::<Map>().walkable(idx), "the rock gave way too soon");
+
+    game.play(vec![PlayerCommand::KeepDigging]);
+    assert!(!player_digging(&game));
+    let map = game.state.ecs.fetch::<Map>();
+    assert!(map.walkable(idx));
+    assert!(!map.spatial.is_blocked(idx));
+}
+
+#[test]
+fn doing_something_else_stops_digging() {
+    let mut game = game_with_pick_axe();
+    let start = *game.state.ecs.fetch::<rltk::Point>();
+    let (dx, dy, idx) = rock_beside(&mut game, start.x, start.y);
+
+    game.play(vec![PlayerCommand::Move{ dx, dy }, PlayerCommand::KeepDigging]);
+    game.play(vec![PlayerCommand::Wait]);
+    assert!(!player_digging(&game));
+
+    // The work done so far isn't lost, and walking back into the rock carries on with it
+    assert_eq!(game.state.ecs.fetch::<Map>().dig_progress.get(&idx), Some(&2));
+    game.play(vec![PlayerCommand::Move{ dx, dy }; PICK_AXE_TURNS - 2]);
+    assert!(game.state.ecs.fetch::<Map>().walkable(idx));
+}
+
+#[test]
+fn burrowers_dig_through_rock() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    let player = *game.state.ecs.fetch::<rltk::Point>();
+    let (x, y) = {
+        let map = game.state.ecs.fetch::<Map>();
+        (player.x - 4 ..= player.x + 4)
+            .flat_map(|x| (player.y - 4 ..= player.y + 4).map(move |y| (x, y)))
+            .filter(|(x, y)| i32::abs(x - player.x) > 1 || i32::abs(y - player.y) > 1)
+            .find(|(x, y)| {
+                let idx = map.xy_idx(*x, *y);
+                map.walkable(idx) && !map.spatial.is_blocked(idx)
+            })
+            .expect("There's no room near the player")
+    };
+    let (_, _, idx) = rock_beside(&mut game, x, y);
+    let raws = raws::get_raws(&game.state.ecs);
+    let golem = raws::spawn_named_mob(&raws, &mut game.state.ecs, "Rock Golem", SpawnType::AtPosition{ x, y })
+        .expect("The raws should have a rock golem");
+    game.state.ecs.write_storage::<Digging>().insert(golem, Digging{ tile_idx : idx as i32, turns : 8 })
+        .expect("Unable to insert");
+
+    for _ in 0..40 {
+        game.play(vec![PlayerCommand::Wait]);
+        if game.state.ecs.fetch::<Map>().walkable(idx) { break; }
+    }
+    assert!(game.state.ecs.fetch::<Map>().walkable(idx), "the golem never got through");
+    assert!(game.state.ecs.read_storage::<Position>().contains(golem));
+}
@@ -0,0 +1,24 @@
+use chapter_75_darkplaza::{Headless, PlayerCommand, Map, GameClock};
+
+const SEED : u64 = 12345;
+
+#[test]
+fn fires_light_up_the_night_outdoors() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    game.state.ecs.fetch_mut::<GameClock>().minutes = 23 * 60;
+    let player = *game.state.ecs.fetch::<rltk::Point>();
+
+    let fire_idx = {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+        assert!(map.outdoors, "the game should start in the town");
+        let fire_idx = map.xy_idx(player.x + 2, player.y);
+        map.fires.insert(fire_idx, 10);
+        fire_idx
+    };
+    game.play(vec![PlayerCommand::Wait]);
+
+    let map = game.state.ecs.fetch::<Map>();
+    let night = game.state.ecs.fetch::<GameClock>().ambient_light();
+    assert!(map.light[fire_idx].r > night.r + 0.5, "the fire gave off no light");
+}
@@ -0,0 +1,126 @@
+use chapter_75_darkplaza::{Headless, PlayerCommand, RunState, GameClock, Map, replay};
+
+const SEED : u64 = 12345;
+
+fn player_position(game : &Headless) -> rltk::Point {
+    *game.state.ecs.fetch::<rltk::Point>()
+}
+
+fn minutes(game : &Headless) -> i32 {
+    game.state.ecs.fetch::<GameClock>().minutes
+}
+
+/// A walk that always goes somewhere, each step followed by a `Cancel` in case it
+/// bumped into a vendor and opened their menu.
+fn wander(steps : usize) -> Vec<PlayerCommand> {
+    const DIRECTIONS : [(i32, i32); 8] = [(-1,-1), (0,-1), (1,-1), (-1,0), (1,0), (-1,1), (0,1), (1,1)];
+    let mut commands = Vec::new();
+    for i in 0..steps {
+        let (dx, dy) = DIRECTIONS[(i * 5 + i / 3) % DIRECTIONS.len()];
+        commands.push(PlayerCommand::Move{ dx, dy });
+        commands.push(PlayerCommand::Cancel);
+    }
+    commands
+}
+
+#[test]
+fn starts_waiting_for_the_player() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    assert!(game.runstate() == RunState::AwaitingInput);
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 1);
+}
+
+#[test]
+fn waiting_passes_turns() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    let before = minutes(&game);
+    game.play(vec![PlayerCommand::Wait; 10]);
+    assert!(game.runstate() == RunState::AwaitingInput);
+    assert_eq!(minutes(&game) - before, 10);
+}
+
+#[test]
+fn walking_moves_the_player() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    let start = player_position(&game);
+    game.play(wander(20));
+    assert!(player_position(&game) != start, "the player never moved from {:?}", start);
+}
+
+#[test]
+fn menus_open_and_close() {
+    let mut game = Headless::new(SEED);
+    game.play(vec![PlayerCommand::OpenInventory]);
+    assert!(game.runstate() == RunState::ShowInventory);
+    game.play(vec![PlayerCommand::Cancel]);
+    assert!(game.runstate() == RunState::AwaitingInput);
+}
+
+#[test]
+fn same_commands_same_game() {
+    let mut first = Headless::new(SEED);
+    let mut second = Headless::new(SEED);
+    first.play(wander(50));
+    second.play(wander(50));
+    assert_eq!(player_position(&first), player_position(&second));
+    assert_eq!(replay::state_hash(&first.state.ecs), replay::state_hash(&second.state.ecs));
+}
+
+#[test]
+fn games_side_by_side_keep_to_themselves() {
+    let mut alone = Headless::new(SEED);
+    alone.play(wander(50));
+
+    let mut first = Headless::new(SEED);
+    let mut other = Headless::new(SEED + 1);
+    for command in wander(50) {
+        first.play(vec![command]);
+        other.play(vec![command]);
+    }
+    assert_eq!(player_position(&first), player_position(&alone));
+    assert_eq!(replay::state_hash(&first.state.ecs), replay::state_hash(&alone.state.ecs));
+}
+
+#[test]
+fn replays_reproduce_the_run() {
+    let path = std::env::temp_dir().join(format!("headless-replay-{}.jsonl", SEED));
+    let path = path.to_str().unwrap();
+
+    let mut game = Headless::new(SEED);
+    game.state.start_recording(path);
+    game.play(wander(60));
+    game.state.stop_recording();
+
+    let recorded = replay::Replay::load(path).expect("Unable to load the replay");
+    assert_eq!(recorded.seed, SEED);
+    let replayed = Headless::replay(recorded).expect("The replay diverged");
+    assert_eq!(player_position(&game), player_position(&replayed));
+    assert_eq!(replay::state_hash(&game.state.ecs), replay::state_hash(&replayed.state.ecs));
+
+    std::fs::remove_file(path).expect("Unable to delete the replay");
+}
+
+#[test]
+fn covering_the_lantern_darkens_the_players_tile() {
+    let mut game = Headless::new(SEED);
+    game.run();
+    game.state.ecs.fetch_mut::<GameClock>().minutes = 23 * 60;
+    game.play(vec![PlayerCommand::Wait]);
+    let light_here = |game : &Headless| {
+        let pos = player_position(game);
+        let map = game.state.ecs.fetch::<Map>();
+        map.light[map.xy_idx(pos.x, pos.y)].r
+    };
+    let lit = light_here(&game);
+
+    game.play(vec![PlayerCommand::ToggleLight]);
+    let night = game.state.ecs.fetch::<GameClock>().ambient_light();
+    assert!(lit > night.r + 0.5, "the lantern gave off no light");
+    assert!((light_here(&game) - night.r).abs() < 0.01, "the covered lantern still gave off light");
+
+    game.play(vec![PlayerCommand::ToggleLight]);
+    assert!((light_here(&game) - lit).abs() < 0.01);
+}
@@ -0,0 +1,225 @@
+use chapter_75_darkplaza::{map_builders, raws, raws::RawMaster, rex_assets, LevelKey, TileType};
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+
+// Each build carries its own RNG, so tests can build in parallel; only the raws are shared.
+static RAWS : OnceLock<Arc<RawMaster>> = OnceLock::new();
+
+const FIXED_SEEDS : u64 = 200;
+const RANDOM_SEEDS : u64 = 500;
+const CHAIN_SEEDS : u64 = 10;
+
+fn raws() -> &'static Arc<RawMaster> {
+    RAWS.get_or_init(|| {
+        rex_assets::RexAssets::new();
+        Arc::new(raws::load_raws())
+    })
+}
+
+fn check_depth(depth : i32, seeds : u64) -> Vec<String> {
+    check_level(&LevelKey::main(depth), seeds)
+}
+
+fn check_level(level : &LevelKey, seeds : u64) -> Vec<String> {
+    let mut failures = Vec::new();
+    for seed in 0..seeds {
+        let builder = map_builders::build_level(raws(), seed, level, 80, 50);
+        if let Err(errors) = map_builders::validate_map(&builder.build_data) {
+            for e in errors.iter() {
+                failures.push(format!("{} depth {} seed {}: {}", level.branch, level.depth, seed, e));
+            }
+        }
+    }
+    failures
+}
+
+fn assert_no_failures(failures : Vec<String>) {
+    assert!(
+        failures.is_empty(),
+        "{} problems, starting with:\n{}",
+        failures.len(),
+        failures.iter().take(20).cloned().collect::<Vec<_>>().join("\n")
+    );
+}
+
+#[test]
+fn fixed_depths_are_valid() {
+    let mut failures = Vec::new();
+    for depth in 1 ..= 11 {
+        failures.extend(check_depth(depth, FIXED_SEEDS));
+    }
+    assert_no_failures(failures);
+}
+
+fn step(builder : &str) -> Value {
+    json!({ "builder" : builder })
+}
+
+fn step_with(builder : &str, params : Value) -> Value {
+    json!({ "builder" : builder, "params" : params })
+}
+
+/// Everything `random_builder` can start a level with.
+fn random_starters() -> Vec<Value> {
+    let mut starters : Vec<Value> = ["SimpleMap", "BspDungeon", "BspInterior", "CellularAutomata", "Maze"].iter()
+        .map(|builder| step(builder))
+        .collect();
+    for mode in ["open_area", "open_halls", "winding_passages", "fat_passages", "fearful_symmetry"] {
+        starters.push(step_with("DrunkardsWalk", json!({ "mode" : mode })));
+    }
+    for mode in ["walk_inwards", "walk_outwards", "central_attractor", "insectoid"] {
+        starters.push(step_with("DLA", json!({ "mode" : mode })));
+    }
+    for mode in ["pythagoras", "manhattan"] {
+        starters.push(step_with("Voronoi", json!({ "mode" : mode })));
+    }
+    starters.push(step_with("PrefabLevel", json!({ "level" : "WFC_POPULATED" })));
+    starters
+}
+
+/// The level chains `random_builder` can put together: every starting builder it picks from,
+/// each followed in turn by every variation of the meta builders it might add after that one
+/// (with and without the waveform collapse and the fort it sometimes adds on the end).
+fn random_builder_chains() -> Vec<Value> {
+    const SORTS : [&str; 5] = ["leftmost", "rightmost", "topmost", "bottommost", "central"];
+    const CORRIDORS : [&str; 4] = ["DoglegCorridors", "NearestCorridors", "StraightLineCorridors", "BspCorridors"];
+    const MODIFIERS : [Option<&str>; 3] = [Some("RoomExploder"), Some("RoomCornerRounder"), None];
+
+    let mut chains = Vec::new();
+    for starter in random_starters() {
+        let name = starter["builder"].as_str().unwrap().to_string();
+        for variant in 0..SORTS.len() {
+            let mut steps = Vec::new();
+            if name == "SimpleMap" || name == "BspDungeon" {
+                steps.push(step_with("RoomSorter", json!({ "sort" : SORTS[variant] })));
+                steps.push(step("RoomDrawer"));
+                steps.push(step(CORRIDORS[variant % CORRIDORS.len()]));
+                if variant % 2 == 0 { steps.push(step("CorridorSpawner")); }
+                if let Some(modifier) = MODIFIERS[variant % MODIFIERS.len()] { steps.push(step(modifier)); }
+            }
+            if name == "SimpleMap" || name == "BspDungeon" || name == "BspInterior" {
+                steps.push(if variant % 2 == 0 { step("RoomBasedStartingPosition") } else { step_with("AreaStartingPosition", json!({ "x" : "right", "y" : "bottom" })) });
+                steps.push(step(if (variant / 2) % 2 == 0 { "RoomBasedStairs" } else { "DistantExit" }));
+                steps.push(step(if (variant / 4) % 2 == 0 { "RoomBasedSpawner" } else { "VoronoiSpawning" }));
+            } else {
+                steps.push(step_with("AreaStartingPosition", json!({ "x" : "center", "y" : "center" })));
+                steps.push(step("CullUnreachable"));
+                steps.push(step_with("AreaStartingPosition", json!({ "x" : "left", "y" : "top" })));
+                steps.push(step("VoronoiSpawning"));
+                steps.push(step("DistantExit"));
+            }
+            if variant % 2 == 1 {
+                steps.push(step("WaveformCollapse"));
+                steps.push(step_with("AreaStartingPosition", json!({ "x" : "center", "y" : "center" })));
+                steps.push(step("CullUnreachable"));
+                steps.push(step_with("AreaStartingPosition", json!({ "x" : "left", "y" : "center" })));
+                steps.push(step("VoronoiSpawning"));
+                steps.push(step("DistantExit"));
+            }
+            if variant == SORTS.len() - 1 {
+                steps.push(step_with("PrefabSection", json!({ "section" : "UNDERGROUND_FORT" })));
+                steps.push(step("DistantExit"));
+            }
+            steps.push(step("DoorPlacement"));
+            steps.push(step("PrefabVaults"));
+            chains.push(json!({ "depth" : 12, "name" : "Test", "starter" : starter, "builders" : steps }));
+        }
+    }
+    chains
+}
+
+#[test]
+fn random_builders_are_valid() {
+    // Like build_level, give each chain a few goes at making a valid map before counting it a failure
+    let mut failures = Vec::new();
+    for level_json in random_builder_chains() {
+        let level : raws::LevelChain = serde_json::from_value(level_json.clone()).unwrap();
+        for seed in 0..CHAIN_SEEDS {
+            let mut rng = rltk::RandomNumberGenerator::seeded(seed);
+            let mut errors = Vec::new();
+            for _attempt in 0..map_builders::MAX_BUILD_ATTEMPTS {
+                let mut chain = map_builders::chain_from_raws(raws(), &level, level.depth, 80, 50);
+                chain.build_map(&mut rng);
+                errors = map_builders::validate_map(&chain.build_data).err().unwrap_or_default();
+                if errors.is_empty() { break; }
+            }
+            failures.extend(errors.iter().map(|e| format!("{} seed {}: {}", level_json, seed, e)));
+        }
+    }
+
+    // Anything past the fixed levels comes from random_builder
+    failures.extend(check_depth(12, RANDOM_SEEDS));
+    assert_no_failures(failures);
+}
+
+#[test]
+fn levels_are_deterministic() {
+    for depth in [1, 5, 12] {
+        let a = map_builders::build_level(raws(), 42, &LevelKey::main(depth), 80, 50);
+        let b = map_builders::build_level(raws(), 42, &LevelKey::main(depth), 80, 50);
+        assert!(a.build_data.map.tiles == b.build_data.map.tiles, "depth {} differs between builds", depth);
+        assert_eq!(a.build_data.spawn_list, b.build_data.spawn_list);
+    }
+}
+
+#[test]
+fn spider_nest_is_valid() {
+    let mut failures = Vec::new();
+    for depth in 9 ..= 10 {
+        failures.extend(check_level(&LevelKey::new("spider_nest", depth), FIXED_SEEDS));
+    }
+    assert_no_failures(failures);
+}
+
+#[test]
+fn mushroom_grove_has_spider_nest_entrance() {
+    for seed in 0..FIXED_SEEDS {
+        let builder = map_builders::build_level(raws(), seed, &LevelKey::main(8), 80, 50);
+        let map = &builder.build_data.map;
+        let entrances : Vec<&usize> = map.branch_entrances.keys().collect();
+        assert_eq!(entrances.len(), 1, "seed {} has {} branch entrances", seed, entrances.len());
+        assert_eq!(map.tiles[*entrances[0]], TileType::BranchStairs, "seed {}", seed);
+        assert_eq!(map.branch_entrances[entrances[0]], "spider_nest");
+    }
+}
+
+fn build_chain(level_json : &str) -> map_builders::BuilderChain {
+    let level : raws::LevelChain = serde_json::from_str(level_json).unwrap();
+    let mut chain = map_builders::chain_from_raws(raws(), &level, level.depth, 80, 50);
+    chain.build_map(&mut rltk::RandomNumberGenerator::seeded(1));
+    chain
+}
+
+#[test]
+fn unknown_starting_builder_falls_back() {
+    let chain = build_chain(r#"{ "depth" : 1, "name" : "Test", "starter" : { "builder" : "NoSuchBuilder" }, "builders" : [] }"#);
+    assert!(chain.build_data.map.tiles.iter().any(|tt| *tt == TileType::Floor));
+}
+
+#[test]
+fn prefab_level_uses_its_params() {
+    let default = build_chain(r#"{ "depth" : 1, "name" : "Test", "starter" : { "builder" : "PrefabLevel" }, "builders" : [] }"#);
+    let named = build_chain(r#"{ "depth" : 1, "name" : "Test", "starter" : { "builder" : "PrefabLevel", "params" : { "level" : "WFC_POPULATED" } }, "builders" : [] }"#);
+    let template = build_chain(r#"{ "depth" : 1, "name" : "Test", "starter" : { "builder" : "PrefabLevel", "params" : { "template" : "../../resources/wfc-demo1.xp" } }, "builders" : [] }"#);
+    assert!(default.build_data.map.tiles == named.build_data.map.tiles);
+    assert!(default.build_data.map.tiles != template.build_data.map.tiles);
+    assert!(template.build_data.map.tiles.iter().any(|tt| *tt == TileType::Floor));
+}
+
+#[test]
+fn missing_waveform_sample_falls_back() {
+    let chain = build_chain(r#"{ "depth" : 1, "name" : "Test", "starter" : { "builder" : "CellularAutomata" },
+        "builders" : [ { "builder" : "WaveformCollapse", "params" : { "sample" : "../../resources/no-such-sample.xp" } } ] }"#);
+    assert!(chain.build_data.map.tiles.iter().any(|tt| *tt == TileType::Floor));
+}
+
+#[test]
+fn levels_can_choose_their_size() {
+    let chain = build_chain(r#"{ "depth" : 2, "name" : "Test", "width" : 200, "height" : 200, "starter" : { "builder" : "CellularAutomata" },
+        "builders" : [ { "builder" : "AreaStartingPosition", "params" : { "x" : "center", "y" : "center" } },
+            { "builder" : "CullUnreachable" }, { "builder" : "DistantExit" } ] }"#);
+    let map = &chain.build_data.map;
+    assert_eq!((map.width, map.height), (200, 200));
+    assert_eq!(map.tiles.len(), 200 * 200);
+    assert!(map_builders::validate_map(&chain.build_data).is_ok());
+}
@@ -0,0 +1,132 @@
+use chapter_75_darkplaza::{Headless, PlayerCommand, GameClock, Map, MasterDungeonMap, OtherLevelPosition, LevelKey, RunState, replay};
+use chapter_75_darkplaza::saveload_system::{read_save, load_save, save_game, load_game, list_saves, SaveDir, SaveSlot, SAVE_VERSION};
+use specs::prelude::*;
+use chapter_75_darkplaza::registry::{SAVED_COMPONENTS, saveable_components};
+use chapter_75_darkplaza::raws::load_raws;
+
+/// A save written by the game before save files had versions: the player on the first level
+/// below the town, with a few things left behind up there.
+const BASELINE_SAVE : &str = include_str!("fixtures/baseline_save.json");
+
+/// A directory for a test's saves, out of the way of the player's.
+fn temp_saves(test : &str) -> SaveDir {
+    let dir = std::env::temp_dir().join(format!("saves-{}-{}", test, std::process::id()));
+    SaveDir(dir.to_str().unwrap().to_string())
+}
+
+/// A save from before versions, with nothing in it: one empty storage per component.
+fn empty_v1_save() -> String {
+    vec!["[]"; 80].concat()
+}
+
+#[test]
+fn old_saves_are_migrated() {
+    let save = read_save(&empty_v1_save(), &load_raws()).expect("The old save should load");
+    assert_eq!(save.header.version, SAVE_VERSION);
+    assert_eq!(save.header.raws_hash, None);
+    assert!(save.components.contains_key("Position"));
+    assert!(save.components.contains_key("WantsToShoot"));
+}
+
+#[test]
+fn saves_from_before_versions_still_play() {
+    let mut game = Headless::new(1);
+    game.run();
+    load_save(&mut game.state.ecs, BASELINE_SAVE).expect("The old save should load");
+
+    assert_eq!(*game.state.ecs.fetch::<rltk::Point>(), rltk::Point::new(2, 22));
+    assert_eq!(game.state.ecs.fetch::<Map>().key(), LevelKey::main(2));
+    assert!(game.state.ecs.fetch::<MasterDungeonMap>().get_map(&LevelKey::main(1)).is_some());
+    let left_behind : Vec<LevelKey> = game.state.ecs.read_storage::<OtherLevelPosition>().join().map(|p| p.level.clone()).collect();
+    assert_eq!(left_behind.len(), 6);
+    assert!(left_behind.iter().all(|level| *level == LevelKey::main(1)));
+
+    game.play(vec![PlayerCommand::Wait; 5]);
+    assert!(game.runstate() == RunState::AwaitingInput);
+}
+
+#[test]
+fn saves_from_newer_games_are_refused() {
+    let text = format!(r#"{{"header":{{"version":{},"raws_hash":null}},"components":{{}}}}"#, SAVE_VERSION + 1);
+    let error = read_save(&text, &load_raws()).err().expect("A newer save shouldn't load");
+    assert!(error.contains("newer version"));
+}
+
+#[test]
+fn damaged_saves_are_refused() {
+    let raws = load_raws();
+    assert!(read_save("", &raws).is_err());
+    assert!(read_save(r#"{"header":{"version":"#, &raws).is_err());
+    // Missing the last component
+    assert!(read_save(&vec!["[]"; 79].concat(), &raws).is_err());
+}
+
+#[test]
+fn saves_load_back_from_their_slot() {
+    let saves = temp_saves("load");
+    let mut game = Headless::new(54321);
+    game.state.ecs.insert(saves.clone());
+    game.play(vec![PlayerCommand::Wait; 3]);
+    save_game(&game.state.ecs, "test");
+
+    let listed = list_saves(&game.state.ecs).into_iter().find(|s| s.name == "test").expect("The save should be listed");
+    let summary = listed.summary.expect("The save should say what's in it");
+    assert_eq!(summary.depth, 1);
+
+    let mut other = Headless::new(1);
+    other.state.ecs.insert(saves.clone());
+    other.run();
+    let loaded = load_game(&mut other.state.ecs, "test");
+    let _ = std::fs::remove_dir_all(&saves.0);
+    loaded.expect("The save should load");
+
+    assert_eq!(*other.state.ecs.fetch::<rltk::Point>(), *game.state.ecs.fetch::<rltk::Point>());
+    assert_eq!(other.state.ecs.fetch::<GameClock>().minutes, game.state.ecs.fetch::<GameClock>().minutes);
+}
+
+#[test]
+fn saving_asks_which_slot() {
+    let saves = temp_saves("menu");
+    let mut game = Headless::new(54321);
+    game.state.ecs.insert(saves.clone());
+    game.play(vec![PlayerCommand::SaveAndQuit]);
+    assert!(matches!(game.runstate(), RunState::ShowSaveMenu{ .. }));
+
+    // Rub out the name it suggests, and give it another
+    let mut commands = vec![PlayerCommand::Backspace; 30];
+    commands.extend("mine".chars().map(PlayerCommand::TypeChar));
+    commands.push(PlayerCommand::Confirm);
+    game.play(commands);
+
+    let names : Vec<String> = game.state.ecs.fetch::<Vec<SaveSlot>>().iter().map(|s| s.name.clone()).collect();
+    let _ = std::fs::remove_dir_all(&saves.0);
+    assert!(matches!(game.runstate(), RunState::MainMenu{ .. }));
+    assert_eq!(names, vec!["mine".to_string()]);
+}
+
+#[test]
+fn saving_leaves_the_game_as_it_was() {
+    let saves = temp_saves("autosave");
+    let commands = || {
+        let mut commands = vec![PlayerCommand::OpenCheatMenu, PlayerCommand::Select(0)];
+        commands.extend(vec![PlayerCommand::Wait; 20]);
+        commands
+    };
+    let mut autosaved = Headless::new(54321);
+    autosaved.state.ecs.insert(saves.clone());
+    autosaved.state.options.autosave = true;
+    autosaved.play(commands());
+    let mut unsaved = Headless::new(54321);
+    unsaved.play(commands());
+
+    let _ = std::fs::remove_dir_all(&saves.0);
+    assert_eq!(replay::state_hash(&autosaved.state.ecs), replay::state_hash(&unsaved.state.ecs));
+}
+
+#[test]
+fn every_saveable_component_is_saved() {
+    let saveable = saveable_components();
+    assert!(SAVED_COMPONENTS.iter().all(|name| saveable.contains(name)), "everything saved should be saveable");
+    let left_out : Vec<&&str> = saveable.iter().filter(|name| !SAVED_COMPONENTS.contains(name)).collect();
+    assert!(left_out.is_empty(), "these would be lost on saving: {:?}", left_out);
+}
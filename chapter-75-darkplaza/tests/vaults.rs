@@ -0,0 +1,55 @@
+use chapter_75_darkplaza::map_builders::Vault;
+use rltk::RandomNumberGenerator;
+
+const ARROW_VAULT : &str = "name: Arrow\nlegend: → = Dart Trap\n---\n#→.\n...\n";
+
+fn rows(vault : &Vault) -> Vec<String> {
+    vault.glyphs.chunks(vault.width).map(|row| row.iter().collect()).collect()
+}
+
+#[test]
+fn rotation_turns_glyphs_and_legend() {
+    let vault = Vault::parse("arrow.txt", ARROW_VAULT).unwrap();
+    let turned = vault.transformed(false, 1);
+    assert_eq!((turned.width, turned.height), (2, 3));
+    assert_eq!(rows(&turned), vec![".#", ".↓", ".."]);
+    assert_eq!(turned.legend.get(&'↓').map(|s| s.as_str()), Some("Dart Trap"));
+    assert!(!turned.legend.contains_key(&'→'));
+
+    let full_circle = vault.transformed(false, 4);
+    assert_eq!(rows(&full_circle), rows(&vault));
+    assert_eq!(full_circle.legend, vault.legend);
+}
+
+#[test]
+fn mirroring_flips_glyphs_and_legend() {
+    let vault = Vault::parse("arrow.txt", ARROW_VAULT).unwrap();
+    let mirrored = vault.transformed(true, 0);
+    assert_eq!(rows(&mirrored), vec![".←#", "..."]);
+    assert_eq!(mirrored.legend.get(&'←').map(|s| s.as_str()), Some("Dart Trap"));
+    assert!(!mirrored.legend.contains_key(&'→'));
+}
+
+#[test]
+fn every_orientation_keeps_the_legend_on_its_glyph() {
+    let vault = Vault::parse("arrow.txt", ARROW_VAULT).unwrap();
+    for mirror in [false, true] {
+        for quarter_turns in 0..4 {
+            let oriented = vault.transformed(mirror, quarter_turns);
+            let traps = oriented.glyphs.iter().filter(|ch| oriented.legend.contains_key(ch)).count();
+            assert_eq!(traps, 1, "mirror {} turns {}", mirror, quarter_turns);
+        }
+    }
+}
+
+#[test]
+fn transform_can_be_turned_off() {
+    let vault = Vault::parse("arrow.txt", &format!("transform: no\n{}", ARROW_VAULT)).unwrap();
+    assert!(!vault.transform);
+    let mut rng = RandomNumberGenerator::seeded(7);
+    for _ in 0..20 {
+        let placed = vault.random_orientation(&mut rng);
+        assert_eq!(rows(&placed), rows(&vault));
+        assert_eq!(placed.legend, vault.legend);
+    }
+}